@@ -0,0 +1,248 @@
+use crate::core::frame_buffer::FrameBuffer;
+use crate::core::line_renderer::{LineBuffer, LineDepthTest, LineStyle, ScreenLine};
+use crate::core::rasterizer::Rasterizer;
+use crate::core::renderer::TransformedGeometry;
+use crate::core::shadow_map::ShadowMap;
+use crate::geometry::transform::{clip_to_screen, point_to_clip, transform_point};
+use crate::io::render_settings::RenderSettings;
+use crate::scene::scene_utils::Scene;
+use nalgebra::{Matrix4, Point3, Vector3};
+use rayon::prelude::*;
+
+/// 法线线段颜色
+const NORMAL_COLOR: Vector3<f32> = Vector3::new(0.2, 0.9, 1.0);
+/// 切线线段颜色
+const TANGENT_COLOR: Vector3<f32> = Vector3::new(1.0, 0.35, 0.2);
+/// 包围盒颜色
+const BOUNDING_BOX_COLOR: Vector3<f32> = Vector3::new(1.0, 0.85, 0.1);
+/// 阴影视锥颜色
+const SHADOW_FRUSTUM_COLOR: Vector3<f32> = Vector3::new(1.0, 0.3, 0.8);
+
+/// 立方体 12 条边的角点索引（角点按 xyz 三位二进制编号）
+const BOX_EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (2, 3),
+    (4, 5),
+    (6, 7),
+    (0, 2),
+    (1, 3),
+    (4, 6),
+    (5, 7),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+/// 在最终画面上绘制网格检查用的线段叠加层
+pub fn draw_debug_overlays(
+    frame_buffer: &FrameBuffer,
+    scene: &Scene,
+    geometry: &TransformedGeometry,
+    shadow_map: Option<&ShadowMap>,
+    settings: &RenderSettings,
+) {
+    let camera = &scene.active_camera;
+    let projector = ViewProjector {
+        projection: camera.projection_matrix(),
+        near: camera.near(),
+        width: frame_buffer.width as f32,
+        height: frame_buffer.height as f32,
+    };
+    let view_matrix = camera.view_matrix();
+    let surface_depth_test = if settings.use_zbuffer {
+        LineDepthTest::VisibleOnly(&frame_buffer.depth_buffer)
+    } else {
+        LineDepthTest::Disabled
+    };
+
+    if settings.debug_normals {
+        let length = settings.debug_normal_length;
+        let lines: Vec<ScreenLine> = geometry
+            .view_coords
+            .par_iter()
+            .zip(geometry.view_normals.par_iter())
+            .filter_map(|(position, normal)| {
+                projector.project_segment(*position, position + normal * length)
+            })
+            .collect();
+        composite_lines(
+            frame_buffer,
+            &lines,
+            surface_depth_test,
+            &NORMAL_COLOR,
+            settings,
+        );
+    }
+
+    if settings.debug_tangents {
+        let lines = tangent_lines(scene, geometry, &projector, settings.debug_normal_length);
+        composite_lines(
+            frame_buffer,
+            &lines,
+            surface_depth_test,
+            &TANGENT_COLOR,
+            settings,
+        );
+    }
+
+    if settings.debug_bounding_boxes {
+        let model_view = view_matrix * scene.object.transform;
+        let lines: Vec<ScreenLine> = scene
+            .object
+            .model
+            .meshes
+            .iter()
+            .filter_map(|mesh| {
+                // 包围盒跟随当前的骨骼与变形姿态
+                let posed = scene.object.posed_vertices(mesh);
+                let mut min = Point3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+                let mut max = Point3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+                for vertex in posed.iter() {
+                    min = min.inf(&vertex.position);
+                    max = max.sup(&vertex.position);
+                }
+                (!posed.is_empty()).then_some((min, max))
+            })
+            .flat_map(|(min, max)| {
+                let corners: Vec<Point3<f32>> = (0..8)
+                    .map(|i| {
+                        let corner = Point3::new(
+                            if i & 1 == 0 { min.x } else { max.x },
+                            if i & 2 == 0 { min.y } else { max.y },
+                            if i & 4 == 0 { min.z } else { max.z },
+                        );
+                        transform_point(&corner, &model_view)
+                    })
+                    .collect();
+                BOX_EDGES
+                    .iter()
+                    .filter_map(|&(a, b)| projector.project_segment(corners[a], corners[b]))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        composite_lines(
+            frame_buffer,
+            &lines,
+            LineDepthTest::Disabled,
+            &BOUNDING_BOX_COLOR,
+            settings,
+        );
+    }
+
+    if settings.debug_shadow_frustum
+        && let Some(shadow_map) = shadow_map.filter(|map| map.is_valid)
+        && let Some(light_to_world) = shadow_map.light_view_proj_matrix.try_inverse()
+    {
+        // 光源裁剪空间的单位立方体即阴影贴图覆盖的范围
+        let light_to_view = view_matrix * light_to_world;
+        let corners: Vec<Point3<f32>> = (0..8)
+            .map(|i| {
+                let ndc = Point3::new(
+                    if i & 1 == 0 { -1.0 } else { 1.0 },
+                    if i & 2 == 0 { -1.0 } else { 1.0 },
+                    if i & 4 == 0 { -1.0 } else { 1.0 },
+                );
+                transform_point(&ndc, &light_to_view)
+            })
+            .collect();
+        let lines: Vec<ScreenLine> = BOX_EDGES
+            .iter()
+            .filter_map(|&(a, b)| projector.project_segment(corners[a], corners[b]))
+            .collect();
+        composite_lines(
+            frame_buffer,
+            &lines,
+            LineDepthTest::Disabled,
+            &SHADOW_FRUSTUM_COLOR,
+            settings,
+        );
+    }
+}
+
+/// 每个三角形重心处沿 UV 切线方向的线段
+fn tangent_lines(
+    scene: &Scene,
+    geometry: &TransformedGeometry,
+    projector: &ViewProjector,
+    length: f32,
+) -> Vec<ScreenLine> {
+    let view = &geometry.view_coords;
+    scene
+        .object
+        .model
+        .meshes
+        .par_iter()
+        .enumerate()
+        .flat_map(|(mesh_index, mesh)| {
+            let offset = geometry.mesh_offsets[mesh_index];
+            mesh.indices.par_chunks_exact(3).filter_map(move |indices| {
+                let [i0, i1, i2] = [0, 1, 2].map(|k| indices[k] as usize);
+                let positions = [view[offset + i0], view[offset + i1], view[offset + i2]];
+                let texcoords = [
+                    mesh.vertices[i0].texcoord,
+                    mesh.vertices[i1].texcoord,
+                    mesh.vertices[i2].texcoord,
+                ];
+                let tangent = Rasterizer::compute_triangle_tangent(positions, texcoords)?;
+                let centroid = Point3::from(
+                    (positions[0].coords + positions[1].coords + positions[2].coords) / 3.0,
+                );
+                projector.project_segment(centroid, centroid + tangent * length)
+            })
+        })
+        .collect()
+}
+
+fn composite_lines(
+    frame_buffer: &FrameBuffer,
+    lines: &[ScreenLine],
+    depth_test: LineDepthTest,
+    color: &Vector3<f32>,
+    settings: &RenderSettings,
+) {
+    let line_buffer = LineBuffer::new(frame_buffer.width, frame_buffer.height);
+    line_buffer.draw_lines(
+        lines,
+        &LineStyle::solid(1.0),
+        depth_test,
+        settings.is_perspective(),
+    );
+    line_buffer.composite(&frame_buffer.color_buffer, color, settings.use_gamma);
+}
+
+/// 将视图空间线段投影到屏幕，并裁剪到近平面之前
+struct ViewProjector {
+    projection: Matrix4<f32>,
+    near: f32,
+    width: f32,
+    height: f32,
+}
+
+impl ViewProjector {
+    fn project_segment(&self, start: Point3<f32>, end: Point3<f32>) -> Option<ScreenLine> {
+        let near_z = -self.near;
+        let (mut start, mut end) = (start, end);
+        if start.z > near_z && end.z > near_z {
+            return None;
+        }
+        if start.z > near_z || end.z > near_z {
+            let t = (near_z - start.z) / (end.z - start.z);
+            let clipped = start + (end - start) * t;
+            if start.z > near_z {
+                start = clipped;
+            } else {
+                end = clipped;
+            }
+        }
+        let project = |p: &Point3<f32>| {
+            clip_to_screen(&point_to_clip(p, &self.projection), self.width, self.height)
+        };
+        Some(ScreenLine {
+            start: project(&start),
+            end: project(&end),
+            start_depth: -start.z,
+            end_depth: -end.z,
+        })
+    }
+}
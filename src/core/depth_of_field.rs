@@ -0,0 +1,179 @@
+use crate::core::frame_buffer::{FrameBuffer, screen_to_ground_intersection};
+use crate::geometry::camera::Camera;
+use crate::io::render_settings::RenderSettings;
+use log::debug;
+use rayon::prelude::*;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+/// 散景采样数的档位，搜索半径越大采样越多
+const BOKEH_SAMPLE_COUNTS: [usize; 3] = [16, 32, 64];
+/// 弥散圆半径上限，占图像高度的比例
+const MAX_COC_FRACTION: f32 = 0.03;
+/// 统计邻域最大弥散圆的分块边长（像素）
+const TILE_SIZE: usize = 16;
+/// 黄金角（弧度），用于生成均匀分布的圆盘采样
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+/// 像素处的视图深度：深度缓冲为空时取程序化地面的交点深度，仍为空则为无穷远
+pub fn pixel_depth(
+    frame_buffer: &FrameBuffer,
+    camera: &Camera,
+    settings: &RenderSettings,
+    x: usize,
+    y: usize,
+) -> f32 {
+    let depth = frame_buffer.depth_buffer[y * frame_buffer.width + x].load(Ordering::Relaxed);
+    if depth.is_finite() || !settings.uses_procedural_ground() {
+        return depth;
+    }
+    let t_x = x as f32 / (frame_buffer.width - 1).max(1) as f32;
+    let t_y = y as f32 / (frame_buffer.height - 1).max(1) as f32;
+    match screen_to_ground_intersection(settings, camera, t_x, t_y) {
+        (point, true) => -(camera.view_matrix() * point.to_homogeneous()).z,
+        _ => f32::INFINITY,
+    }
+}
+
+/// 按深度缓冲和相机镜头参数做散景模糊，直接修改颜色缓冲区
+///
+/// 每个像素在圆盘内收集邻近像素：采样点的弥散圆覆盖到当前像素时才计入，权重与弥散圆面积成反比。
+/// 位于当前像素之后的采样点半径不超过当前像素的半径，清晰的前景不会被背景渗入，模糊的前景则可以溢出到背景上。
+pub fn apply_depth_of_field(
+    frame_buffer: &FrameBuffer,
+    camera: &Camera,
+    settings: &RenderSettings,
+) {
+    if !settings.use_zbuffer || camera.focal_length_mm().is_none() {
+        return;
+    }
+    let start = Instant::now();
+    let (width, height) = (frame_buffer.width, frame_buffer.height);
+    let max_radius = (height as f32 * MAX_COC_FRACTION).max(1.0);
+
+    let depths: Vec<f32> = (0..width * height)
+        .into_par_iter()
+        .map(|index| pixel_depth(frame_buffer, camera, settings, index % width, index / width))
+        .collect();
+    let radii: Vec<f32> = depths
+        .par_iter()
+        .map(|&depth| camera.circle_of_confusion(depth, height).min(max_radius))
+        .collect();
+    if radii.iter().all(|&radius| radius < 0.5) {
+        return;
+    }
+
+    // 焦平面之后的采样点半径不超过后方像素，只有焦平面之前的模糊会溢出到较远的像素上
+    let focus = camera.focus_distance();
+    let near_radii: Vec<f32> = depths
+        .iter()
+        .zip(&radii)
+        .map(|(&depth, &radius)| if depth < focus { radius } else { 0.0 })
+        .collect();
+    let search_radii = neighbourhood_max_radius(&near_radii, width, height, max_radius);
+    // 在线性空间中混合，高光散景不会因 gamma 编码而变暗
+    let colors: Vec<[f32; 3]> = frame_buffer
+        .get_color_buffer_linear(settings.use_gamma)
+        .chunks_exact(3)
+        .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+        .collect();
+    let rotation = camera.sample_rotation();
+    let spirals = BOKEH_SAMPLE_COUNTS.map(|count| disk_spiral(count, rotation));
+
+    let blurred: Vec<[f32; 3]> = (0..width * height)
+        .into_par_iter()
+        .map(|index| {
+            let search = search_radii[index].max(radii[index]);
+            if search < 0.5 {
+                return colors[index];
+            }
+            let (x, y) = ((index % width) as f32, (index / width) as f32);
+            let (depth, radius) = (depths[index], radii[index]);
+            let spiral = match search {
+                ..3.0 => &spirals[0],
+                ..6.0 => &spirals[1],
+                _ => &spirals[2],
+            };
+            // 每个圆盘采样代表的像素面积
+            let sample_area = std::f32::consts::PI * search * search / spiral.len() as f32;
+
+            let own_weight = 1.0 / radius.max(1.0).powi(2);
+            let mut sum = colors[index].map(|c| c * own_weight);
+            let mut total = own_weight;
+            for &(dx, dy, r) in spiral {
+                let sx = (x + dx * search).round().clamp(0.0, (width - 1) as f32) as usize;
+                let sy = (y + dy * search).round().clamp(0.0, (height - 1) as f32) as usize;
+                let sample = sy * width + sx;
+                let sample_radius = if depths[sample] > depth {
+                    radii[sample].min(radius)
+                } else {
+                    radii[sample]
+                };
+                let coverage = (sample_radius - r * search + 1.0).clamp(0.0, 1.0);
+                if coverage <= 0.0 {
+                    continue;
+                }
+                let weight = coverage * sample_area / sample_radius.max(1.0).powi(2);
+                for (channel, value) in sum.iter_mut().zip(colors[sample]) {
+                    *channel += value * weight;
+                }
+                total += weight;
+            }
+            sum.map(|c| c / total)
+        })
+        .collect();
+
+    frame_buffer.set_color_buffer_linear(blurred.as_flattened(), settings.use_gamma);
+    debug!(
+        "景深后处理耗时 {:?}，对焦距离 {:.3}",
+        start.elapsed(),
+        focus
+    );
+}
+
+/// 单位圆盘内按面积均匀分布的 Vogel 螺旋采样点：(x, y, 到圆心的距离)
+///
+/// 整个螺旋旋转 `rotation` 弧度，多帧累积时每帧的采样点互相错开。
+fn disk_spiral(count: usize, rotation: f32) -> Vec<(f32, f32, f32)> {
+    (0..count)
+        .map(|sample| {
+            let r = ((sample as f32 + 0.5) / count as f32).sqrt();
+            let theta = sample as f32 * GOLDEN_ANGLE + rotation;
+            (r * theta.cos(), r * theta.sin(), r)
+        })
+        .collect()
+}
+
+/// 每个像素邻域内可能溢出到它的最大弥散圆半径：按分块取最大值，再向周围分块扩展
+fn neighbourhood_max_radius(
+    radii: &[f32],
+    width: usize,
+    height: usize,
+    max_radius: f32,
+) -> Vec<f32> {
+    let tiles_x = width.div_ceil(TILE_SIZE);
+    let tiles_y = height.div_ceil(TILE_SIZE);
+    let mut tile_max = vec![0.0_f32; tiles_x * tiles_y];
+    for (index, &radius) in radii.iter().enumerate() {
+        let tile = (index / width / TILE_SIZE) * tiles_x + (index % width) / TILE_SIZE;
+        tile_max[tile] = tile_max[tile].max(radius);
+    }
+
+    let reach = (max_radius / TILE_SIZE as f32).ceil() as usize;
+    let mut dilated = vec![0.0_f32; tiles_x * tiles_y];
+    for ty in 0..tiles_y {
+        for tx in 0..tiles_x {
+            let mut radius = 0.0_f32;
+            for ny in ty.saturating_sub(reach)..(ty + reach + 1).min(tiles_y) {
+                for nx in tx.saturating_sub(reach)..(tx + reach + 1).min(tiles_x) {
+                    radius = radius.max(tile_max[ny * tiles_x + nx]);
+                }
+            }
+            dilated[ty * tiles_x + tx] = radius;
+        }
+    }
+
+    (0..width * height)
+        .map(|index| dilated[(index / width / TILE_SIZE) * tiles_x + (index % width) / TILE_SIZE])
+        .collect()
+}
@@ -86,7 +86,7 @@ impl FrameBuffer {
         let background_pixels_ref = self.compute_background_cache(settings, width, height);
 
         // 2. 地面本体缓存（不含阴影）
-        let (ground_factors_ref, ground_colors_ref) = if settings.enable_ground_plane {
+        let (ground_factors_ref, ground_colors_ref) = if settings.uses_procedural_ground() {
             self.compute_ground_base_cache(settings, camera, width, height)
         } else {
             (
@@ -96,11 +96,12 @@ impl FrameBuffer {
         };
 
         // 3. 地面阴影缓存（仅阴影因子）
-        let shadow_factors_ref = if settings.enable_ground_plane && settings.enable_shadow_mapping {
-            self.compute_ground_shadow_cache(settings, camera, shadow_map, width, height)
-        } else {
-            Arc::new(vec![1.0; width * height])
-        };
+        let shadow_factors_ref =
+            if settings.uses_procedural_ground() && settings.enable_shadow_mapping {
+                self.compute_ground_shadow_cache(settings, camera, shadow_map, width, height)
+            } else {
+                Arc::new(vec![1.0; width * height])
+            };
 
         // 4. 并行合成最终颜色
         self.compose_final_colors(
@@ -269,7 +270,7 @@ impl FrameBuffer {
 
                 let mut final_color = background_pixels_ref[buffer_index];
 
-                if settings.enable_ground_plane {
+                if settings.uses_procedural_ground() {
                     let ground_factor = ground_factors_ref[buffer_index];
                     if ground_factor > 0.0 {
                         let mut ground_color = ground_colors_ref[buffer_index];
@@ -302,10 +303,11 @@ impl FrameBuffer {
 
         let current_path = settings.background_image_path.as_ref()?;
 
-        if let Some(cached_path) = &self.cached_path {
-            if cached_path == current_path && self.cached_background.is_some() {
-                return self.cached_background.as_ref();
-            }
+        if let Some(cached_path) = &self.cached_path
+            && cached_path == current_path
+            && self.cached_background.is_some()
+        {
+            return self.cached_background.as_ref();
         }
 
         match Texture::from_file(current_path) {
@@ -351,6 +353,37 @@ impl FrameBuffer {
             .collect()
     }
 
+    /// 用字节数据覆盖颜色缓冲区（长度需为 width * height * 3）
+    pub fn set_color_buffer_bytes(&self, bytes: &[u8]) {
+        self.color_buffer
+            .par_iter()
+            .zip(bytes.par_iter())
+            .for_each(|(atomic_color, &value)| atomic_color.store(value, Ordering::Relaxed));
+    }
+
+    /// 以线性值读取颜色缓冲区，`use_gamma` 时先解码gamma，供多次渲染在线性空间平均
+    pub fn get_color_buffer_linear(&self, use_gamma: bool) -> Vec<f32> {
+        self.color_buffer
+            .iter()
+            .map(|atomic_color| {
+                color::u8_to_linear_channel(atomic_color.load(Ordering::Relaxed), use_gamma)
+            })
+            .collect()
+    }
+
+    /// 用线性值覆盖颜色缓冲区，`use_gamma` 时重新编码gamma
+    pub fn set_color_buffer_linear(&self, linear: &[f32], use_gamma: bool) {
+        self.color_buffer
+            .par_iter()
+            .zip(linear.par_iter())
+            .for_each(|(atomic_color, &value)| {
+                atomic_color.store(
+                    color::linear_channel_to_u8(value, use_gamma),
+                    Ordering::Relaxed,
+                )
+            });
+    }
+
     pub fn get_depth_buffer_f32(&self) -> Vec<f32> {
         self.depth_buffer
             .iter()
@@ -532,10 +565,8 @@ pub fn compute_ground_shadow(
                     &intersection,
                     &Matrix4::identity(),
                     settings.shadow_bias,
-                    settings.enable_pcf,
-                    &settings.pcf_type,
-                    settings.pcf_kernel,
-                    settings.pcf_sigma,
+                    camera.sample_rotation(),
+                    settings,
                 )
             } else {
                 1.0
@@ -569,7 +600,7 @@ pub fn screen_to_ground_intersection(
         return (Point3::origin(), false);
     }
     let intersection = origin + t * dir;
-    let max_render_distance = 100.0;
+    let max_render_distance = camera.far();
     let horizontal_distance =
         ((intersection.x - origin.x).powi(2) + (intersection.z - origin.z).powi(2)).sqrt();
     if horizontal_distance > max_render_distance {
@@ -587,8 +618,10 @@ fn screen_to_world_ray(camera: Camera, t_x: f32, t_y: f32) -> (Point3<f32>, Vect
     let camera_position = camera.position();
     let view_matrix = camera.view_matrix();
 
-    let ndc_x = t_x * 2.0 - 1.0;
-    let ndc_y = 1.0 - t_y * 2.0;
+    // 几何体随子像素抖动平移，地面射线反向偏移以保持对齐
+    let (jitter_x, jitter_y) = camera.subpixel_jitter_ndc();
+    let ndc_x = t_x * 2.0 - 1.0 - jitter_x;
+    let ndc_y = 1.0 - t_y * 2.0 - jitter_y;
 
     let view_x = ndc_x * aspect_ratio * (fov_y_rad / 2.0).tan();
     let view_y = ndc_y * (fov_y_rad / 2.0).tan();
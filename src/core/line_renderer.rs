@@ -0,0 +1,356 @@
+use crate::core::renderer::TransformedGeometry;
+use crate::geometry::culling::is_backface;
+use crate::geometry::edges::EdgeAdjacency;
+use crate::io::render_settings::RenderSettings;
+use crate::material_system::color::linear_rgb_to_u8;
+use atomic_float::AtomicF32;
+use nalgebra::{Point2, Vector3};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 深度测试的相对容差，线条与其所在表面深度几乎相同
+const LINE_DEPTH_TOLERANCE: f32 = 0.005;
+
+/// 屏幕空间线段
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenLine {
+    pub start: Point2<f32>,
+    pub end: Point2<f32>,
+    /// 端点深度（视图空间距离，正值，越小越近）
+    pub start_depth: f32,
+    pub end_depth: f32,
+}
+
+/// 屏幕空间点（圆形斑点）
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenPoint {
+    pub center: Point2<f32>,
+    /// 视图空间距离（正值，越小越近）
+    pub depth: f32,
+    /// 屏幕半径（像素）
+    pub radius: f32,
+}
+
+/// 线条外观
+#[derive(Debug, Clone, Copy)]
+pub struct LineStyle {
+    /// 线宽（像素）
+    pub width: f32,
+    /// 虚线的单段长度（像素），None 表示实线
+    pub dash_length: Option<f32>,
+    /// 不透明度(0.0-1.0)
+    pub opacity: f32,
+}
+
+impl LineStyle {
+    pub fn solid(width: f32) -> Self {
+        Self {
+            width,
+            dash_length: None,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// 线段的深度测试方式
+#[derive(Clone, Copy)]
+pub enum LineDepthTest<'a> {
+    /// 不做深度测试
+    Disabled,
+    /// 只绘制未被表面遮挡的部分
+    VisibleOnly(&'a [AtomicF32]),
+    /// 只绘制被表面遮挡的部分（隐藏线）
+    HiddenOnly(&'a [AtomicF32]),
+    /// 逐像素严格比较深度（独立的线段/点图元）
+    PerPixel(&'a [AtomicF32]),
+}
+
+/// 线条/点覆盖率缓冲区
+///
+/// 每个像素只记录最大覆盖率，所有线段绘制完后再统一与颜色缓冲区混合，
+/// 避免线段交汇处重复叠加和并行写入的竞争。
+pub struct LineBuffer {
+    width: usize,
+    height: usize,
+    coverage: Vec<AtomicF32>,
+}
+
+impl LineBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            coverage: (0..width * height).map(|_| AtomicF32::new(0.0)).collect(),
+        }
+    }
+
+    /// 并行绘制一组抗锯齿线段
+    pub fn draw_lines(
+        &self,
+        lines: &[ScreenLine],
+        style: &LineStyle,
+        depth_test: LineDepthTest,
+        is_perspective: bool,
+    ) {
+        lines.par_iter().for_each(|line| {
+            self.draw_line(line, style, depth_test, is_perspective);
+        });
+    }
+
+    fn draw_line(
+        &self,
+        line: &ScreenLine,
+        style: &LineStyle,
+        depth_test: LineDepthTest,
+        is_perspective: bool,
+    ) {
+        let half_width = (style.width * 0.5).max(0.5);
+        let extent = half_width + 1.0;
+        let min_x = (line.start.x.min(line.end.x) - extent).floor().max(0.0) as usize;
+        let min_y = (line.start.y.min(line.end.y) - extent).floor().max(0.0) as usize;
+        let max_x = (line.start.x.max(line.end.x) + extent)
+            .ceil()
+            .min(self.width as f32) as usize;
+        let max_y = (line.start.y.max(line.end.y) + extent)
+            .ceil()
+            .min(self.height as f32) as usize;
+        if max_x <= min_x || max_y <= min_y {
+            return;
+        }
+
+        let direction = line.end - line.start;
+        let length_sq = direction.norm_squared();
+        // 虚线按主方向上的屏幕坐标取相位，使相连的短边之间图案连续
+        let major_axis_is_x = direction.x.abs() >= direction.y.abs();
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let pixel_center = Point2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let t = if length_sq > 1e-8 {
+                    ((pixel_center - line.start).dot(&direction) / length_sq).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let closest = line.start + direction * t;
+                let distance = (pixel_center - closest).norm();
+                let coverage = (half_width + 0.5 - distance).clamp(0.0, 1.0) * style.opacity;
+                if coverage <= 0.0 {
+                    continue;
+                }
+
+                let dash_phase = if major_axis_is_x {
+                    closest.x
+                } else {
+                    closest.y
+                };
+                if let Some(dash_length) = style.dash_length
+                    && ((dash_phase / dash_length.max(1.0)).floor() as i64).rem_euclid(2) == 1
+                {
+                    continue;
+                }
+
+                let passes = match depth_test {
+                    LineDepthTest::Disabled => true,
+                    LineDepthTest::VisibleOnly(depth_buffer) => {
+                        let depth = interpolate_line_depth(line, t, is_perspective);
+                        self.is_visible(depth_buffer, &closest, depth)
+                    }
+                    LineDepthTest::HiddenOnly(depth_buffer) => {
+                        let depth = interpolate_line_depth(line, t, is_perspective);
+                        self.is_hidden(depth_buffer, &closest, depth)
+                    }
+                    LineDepthTest::PerPixel(depth_buffer) => {
+                        let depth = interpolate_line_depth(line, t, is_perspective);
+                        depth <= depth_buffer[y * self.width + x].load(Ordering::Relaxed)
+                    }
+                };
+                if !passes {
+                    continue;
+                }
+
+                self.coverage[y * self.width + x].fetch_max(coverage, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// 并行绘制一组抗锯齿圆点，提供深度缓冲区时逐像素测试遮挡
+    pub fn draw_points(&self, points: &[ScreenPoint], depth_buffer: Option<&[AtomicF32]>) {
+        points.par_iter().for_each(|point| {
+            let radius = point.radius.max(0.5);
+            let min_x = (point.center.x - radius - 1.0).floor().max(0.0) as usize;
+            let min_y = (point.center.y - radius - 1.0).floor().max(0.0) as usize;
+            let max_x = (point.center.x + radius + 1.0)
+                .ceil()
+                .min(self.width as f32) as usize;
+            let max_y = (point.center.y + radius + 1.0)
+                .ceil()
+                .min(self.height as f32) as usize;
+
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    let pixel_center = Point2::new(x as f32 + 0.5, y as f32 + 0.5);
+                    let distance = (pixel_center - point.center).norm();
+                    let coverage = (radius + 0.5 - distance).clamp(0.0, 1.0);
+                    if coverage <= 0.0 {
+                        continue;
+                    }
+                    let pixel_index = y * self.width + x;
+                    if let Some(depth_buffer) = depth_buffer
+                        && point.depth > depth_buffer[pixel_index].load(Ordering::Relaxed)
+                    {
+                        continue;
+                    }
+                    self.coverage[pixel_index].fetch_max(coverage, Ordering::Relaxed);
+                }
+            }
+        });
+    }
+
+    /// 在线段中心点附近 3x3 邻域取最远深度进行比较，
+    /// 使轮廓线在物体边缘外侧（背景）一侧也能通过测试
+    fn is_visible(&self, depth_buffer: &[AtomicF32], point: &Point2<f32>, depth: f32) -> bool {
+        let (_, farthest) = self.neighborhood_depth_range(depth_buffer, point);
+        depth <= farthest * (1.0 + LINE_DEPTH_TOLERANCE)
+    }
+
+    /// 在同一 3x3 邻域取最近深度进行比较，
+    /// 隐藏线在轮廓旁紧邻背景或更远表面时仍判定为被遮挡
+    fn is_hidden(&self, depth_buffer: &[AtomicF32], point: &Point2<f32>, depth: f32) -> bool {
+        let (nearest, _) = self.neighborhood_depth_range(depth_buffer, point);
+        depth > nearest * (1.0 + LINE_DEPTH_TOLERANCE)
+    }
+
+    /// 点附近 3x3 邻域内的（最近, 最远）深度
+    fn neighborhood_depth_range(
+        &self,
+        depth_buffer: &[AtomicF32],
+        point: &Point2<f32>,
+    ) -> (f32, f32) {
+        let center_x = point.x.floor() as isize;
+        let center_y = point.y.floor() as isize;
+        let mut nearest = f32::INFINITY;
+        let mut farthest = 0.0_f32;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let x = center_x + dx;
+                let y = center_y + dy;
+                if x < 0 || y < 0 || x >= self.width as isize || y >= self.height as isize {
+                    continue;
+                }
+                let index = y as usize * self.width + x as usize;
+                let depth = depth_buffer[index].load(Ordering::Relaxed);
+                nearest = nearest.min(depth);
+                farthest = farthest.max(depth);
+            }
+        }
+        (nearest, farthest)
+    }
+
+    /// 将覆盖率与颜色缓冲区混合
+    pub fn composite(&self, color_buffer: &[AtomicU8], color: &Vector3<f32>, use_gamma: bool) {
+        let line_rgb = linear_rgb_to_u8(color, use_gamma);
+        self.coverage
+            .par_iter()
+            .enumerate()
+            .for_each(|(pixel_index, coverage)| {
+                let coverage = coverage.load(Ordering::Relaxed);
+                if coverage <= 0.0 {
+                    return;
+                }
+                let start = pixel_index * 3;
+                for (channel, &line_value) in line_rgb.iter().enumerate() {
+                    let existing = color_buffer[start + channel].load(Ordering::Relaxed) as f32;
+                    let blended = existing * (1.0 - coverage) + line_value as f32 * coverage;
+                    color_buffer[start + channel].store(blended.round() as u8, Ordering::Relaxed);
+                }
+            });
+    }
+}
+
+/// 沿线段插值深度（透视投影下对 1/z 线性插值）
+fn interpolate_line_depth(line: &ScreenLine, t: f32, is_perspective: bool) -> f32 {
+    if is_perspective {
+        let inv_depth = (1.0 - t) / line.start_depth + t / line.end_depth;
+        1.0 / inv_depth
+    } else {
+        line.start_depth * (1.0 - t) + line.end_depth * t
+    }
+}
+
+/// 将网格边投影为屏幕线段，端点位于相机后方的边被跳过
+pub fn edge_to_screen_line(
+    geometry: &TransformedGeometry,
+    [a, b]: [usize; 2],
+) -> Option<ScreenLine> {
+    let start_depth = -geometry.view_coords[a].z;
+    let end_depth = -geometry.view_coords[b].z;
+    if start_depth <= 1e-4 || end_depth <= 1e-4 {
+        return None;
+    }
+    Some(ScreenLine {
+        start: geometry.screen_coords[a],
+        end: geometry.screen_coords[b],
+        start_depth,
+        end_depth,
+    })
+}
+
+/// 所有唯一的三角形边（线框）
+pub fn extract_wireframe_lines(
+    adjacency: &EdgeAdjacency,
+    geometry: &TransformedGeometry,
+) -> Vec<ScreenLine> {
+    adjacency
+        .edges
+        .par_iter()
+        .filter_map(|edge| edge_to_screen_line(geometry, edge.vertices))
+        .collect()
+}
+
+/// 提取特征线：轮廓线、折痕线和边界线
+pub fn extract_feature_lines(
+    adjacency: &EdgeAdjacency,
+    geometry: &TransformedGeometry,
+    settings: &RenderSettings,
+) -> Vec<ScreenLine> {
+    let view = &geometry.view_coords;
+
+    // 逐面计算视图空间法线与朝向
+    let face_info: Vec<(Vector3<f32>, bool)> = adjacency
+        .faces
+        .par_iter()
+        .map(|&[i0, i1, i2]| {
+            let normal = (view[i1] - view[i0]).cross(&(view[i2] - view[i0]));
+            let normal = if normal.norm_squared() > 1e-20 {
+                normal.normalize()
+            } else {
+                Vector3::zeros()
+            };
+            let front_facing = !is_backface(&view[i0], &view[i1], &view[i2]);
+            (normal, front_facing)
+        })
+        .collect();
+
+    let crease_cos = settings.crease_angle.to_radians().cos();
+
+    adjacency
+        .edges
+        .par_iter()
+        .filter(|edge| {
+            if edge.is_boundary() {
+                return settings.boundary_lines;
+            }
+            if !edge.is_manifold() {
+                // 非流形边总是视为折痕
+                return settings.crease_lines;
+            }
+            let (normal_a, front_a) = face_info[edge.faces[0]];
+            let (normal_b, front_b) = face_info[edge.faces[1]];
+            if settings.silhouette_lines && front_a != front_b {
+                return true;
+            }
+            settings.crease_lines && normal_a.dot(&normal_b) < crease_cos
+        })
+        .filter_map(|edge| edge_to_screen_line(geometry, edge.vertices))
+        .collect()
+}
@@ -1,4 +1,7 @@
+pub mod debug_overlay;
+pub mod depth_of_field;
 pub mod frame_buffer;
+pub mod line_renderer;
 pub mod rasterizer;
 pub mod renderer;
 pub mod shadow_map;
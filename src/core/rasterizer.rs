@@ -1,5 +1,6 @@
 use crate::core::frame_buffer::FrameBuffer;
 use crate::core::renderer::TransformedGeometry;
+use crate::core::shadow_map::ShadowReceiver;
 use crate::geometry::culling::{
     is_backface, is_on_triangle_edge, is_valid_triangle, should_cull_small_triangle,
 };
@@ -36,9 +37,22 @@ pub struct RasterTriangle<'a> {
     pub ambient_color: Vector3<f32>,
     pub is_perspective: bool,
     pub face_seed: Option<u64>,
+    /// 视图空间切线（由UV推导，仅各向异性材质需要）
+    pub tangent_view: Option<Vector3<f32>>,
+    /// 是否朝向相机（面朝向调试显示使用）
+    pub front_facing: bool,
+    /// 接收阴影时使用的阴影贴图
+    pub shadow: Option<&'a ShadowReceiver<'a>>,
+    /// 是否属于主对象，着色线框只叠加在主对象上
+    pub main_object: bool,
 }
 
 impl<'a> RasterTriangle<'a> {
+    /// 是否有顶点位于相机平面上或相机后方（此时投影无效）
+    pub fn crosses_camera_plane(&self) -> bool {
+        self.vertices.iter().any(|vertex| vertex.z_view > -1e-4)
+    }
+
     pub fn is_valid(&self) -> bool {
         is_valid_triangle(
             &self.vertices[0].pix,
@@ -148,13 +162,16 @@ impl Rasterizer {
             (None, Vector3::new(0.7, 0.7, 0.7), None)
         };
 
+        let needs_texcoord = texture.is_some()
+            || settings.debug_uv_checker
+            || material_opt.is_some_and(|m| m.alpha_texture.is_some());
         let vertex_data = [
             Self::create_vertex(
                 &pix0,
                 view_pos0,
                 &vertices[i0],
                 global_i0,
-                texture,
+                needs_texcoord,
                 geometry,
             ),
             Self::create_vertex(
@@ -162,7 +179,7 @@ impl Rasterizer {
                 view_pos1,
                 &vertices[i1],
                 global_i1,
-                texture,
+                needs_texcoord,
                 geometry,
             ),
             Self::create_vertex(
@@ -170,11 +187,24 @@ impl Rasterizer {
                 view_pos2,
                 &vertices[i2],
                 global_i2,
-                texture,
+                needs_texcoord,
                 geometry,
             ),
         ];
 
+        let tangent_view = material_opt
+            .filter(|m| m.anisotropy.abs() > 1e-3)
+            .and_then(|_| {
+                Self::compute_triangle_tangent(
+                    [view_pos0, view_pos1, view_pos2],
+                    [
+                        vertices[i0].texcoord,
+                        vertices[i1].texcoord,
+                        vertices[i2].texcoord,
+                    ],
+                )
+            });
+
         Some(RasterTriangle {
             vertices: vertex_data,
             base_color,
@@ -185,21 +215,42 @@ impl Rasterizer {
             ambient_color,
             is_perspective: settings.is_perspective(),
             face_seed,
+            tangent_view,
+            front_facing: !is_backface(&view_pos0, &view_pos1, &view_pos2),
+            shadow: None,
+            main_object: false,
         })
     }
 
+    /// 根据视图空间位置与纹理坐标计算三角形切线（沿U方向）
+    pub fn compute_triangle_tangent(
+        positions: [Point3<f32>; 3],
+        texcoords: [Vector2<f32>; 3],
+    ) -> Option<Vector3<f32>> {
+        let edge1 = positions[1] - positions[0];
+        let edge2 = positions[2] - positions[0];
+        let duv1 = texcoords[1] - texcoords[0];
+        let duv2 = texcoords[2] - texcoords[0];
+        let det = duv1.x * duv2.y - duv2.x * duv1.y;
+        if det.abs() < 1e-8 {
+            return None;
+        }
+        let tangent = (edge1 * duv2.y - edge2 * duv1.y) / det;
+        (tangent.norm_squared() > 1e-12).then(|| tangent.normalize())
+    }
+
     fn create_vertex(
         pix: &Point2<f32>,
         view_pos: Point3<f32>,
         vertex: &Vertex,
         global_index: usize,
-        texture: Option<&Texture>,
+        needs_texcoord: bool,
         geometry: &TransformedGeometry,
     ) -> RasterVertex {
         RasterVertex {
             pix: *pix,
             z_view: view_pos.z,
-            texcoord: if texture.is_some() {
+            texcoord: if needs_texcoord {
                 Some(vertex.texcoord)
             } else {
                 None
@@ -221,6 +272,9 @@ impl Rasterizer {
         if triangles.is_empty() {
             return;
         }
+        let wireframe_color = settings
+            .shaded_wireframe
+            .then(|| settings.get_wireframe_color_vec());
         triangles.par_iter().for_each(|tri| {
             Self::rasterize_triangle(
                 tri,
@@ -230,10 +284,12 @@ impl Rasterizer {
                 color_buffer,
                 settings,
                 frame_buffer,
+                wireframe_color.as_ref(),
             )
         });
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn rasterize_triangle(
         triangle: &RasterTriangle,
         width: usize,
@@ -242,6 +298,7 @@ impl Rasterizer {
         color_buffer: &[AtomicU8],
         settings: &RenderSettings,
         frame_buffer: &FrameBuffer,
+        wireframe_color: Option<&Vector3<f32>>,
     ) {
         if !triangle.is_valid() {
             return;
@@ -268,6 +325,7 @@ impl Rasterizer {
                     color_buffer,
                     settings,
                     frame_buffer,
+                    wireframe_color,
                 );
             }
         }
@@ -301,6 +359,7 @@ impl Rasterizer {
         color_buffer: &[AtomicU8],
         settings: &RenderSettings,
         frame_buffer: &FrameBuffer,
+        wireframe_color: Option<&Vector3<f32>>,
     ) {
         let v0 = &triangle.vertices[0].pix;
         let v1 = &triangle.vertices[1].pix;
@@ -319,6 +378,9 @@ impl Rasterizer {
         if settings.wireframe && !is_on_triangle_edge(pixel_center, *v0, *v1, *v2, 1.0) {
             return;
         }
+        if Self::is_alpha_cutout(triangle, bary, settings) {
+            return;
+        }
         let interpolated_depth = interpolate_depth(
             bary,
             triangle.vertices[0].z_view,
@@ -336,8 +398,20 @@ impl Rasterizer {
                 return;
             }
         }
-        let material_color =
+        let mut material_color =
             Self::calculate_color(triangle, bary, settings, use_lighting, ambient_contribution);
+        if settings.debug_face_orientation {
+            let heat = if triangle.front_facing {
+                Vector3::new(0.1, 0.3, 1.0)
+            } else {
+                Vector3::new(1.0, 0.1, 0.1)
+            };
+            material_color = material_color * 0.4 + heat * 0.6;
+        }
+        if let Some(wire_color) = wireframe_color.filter(|_| triangle.main_object) {
+            let coverage = Self::edge_coverage(triangle, bary, settings.wireframe_width);
+            material_color = material_color * (1.0 - coverage) + wire_color * coverage;
+        }
         let final_color = Self::apply_alpha_blending(
             &material_color,
             final_alpha,
@@ -345,7 +419,7 @@ impl Rasterizer {
             pixel_y,
             frame_buffer,
         );
-        Self::write_pixel_color(pixel_index, &final_color, color_buffer, &settings);
+        Self::write_pixel_color(pixel_index, &final_color, color_buffer, settings);
     }
 
     fn calculate_color(
@@ -355,22 +429,13 @@ impl Rasterizer {
         use_lighting: bool,
         ambient_contribution: &Vector3<f32>,
     ) -> Vector3<f32> {
-        let surface_color = if let Some(tex) = triangle.texture {
-            if let (Some(tc1), Some(tc2), Some(tc3)) = (
-                triangle.vertices[0].texcoord,
-                triangle.vertices[1].texcoord,
-                triangle.vertices[2].texcoord,
-            ) {
-                let tc = interpolate_texcoords(
-                    bary,
-                    tc1,
-                    tc2,
-                    tc3,
-                    triangle.vertices[0].z_view,
-                    triangle.vertices[1].z_view,
-                    triangle.vertices[2].z_view,
-                    triangle.is_perspective,
-                );
+        let surface_color = if settings.debug_uv_checker {
+            Self::interpolate_triangle_texcoord(triangle, bary)
+                .map_or(Vector3::new(1.0, 0.0, 1.0), |tc| {
+                    uv_checker_color(tc.x, tc.y)
+                })
+        } else if let Some(tex) = triangle.texture {
+            if let Some(tc) = Self::interpolate_triangle_texcoord(triangle, bary) {
                 let arr = tex.sample(tc.x, tc.y);
                 Vector3::new(arr[0], arr[1], arr[2])
             } else {
@@ -382,8 +447,34 @@ impl Rasterizer {
             triangle.base_color
         };
 
-        if use_lighting
-            && triangle.material.is_some()
+        // 无光照/Matcap 模型不受光源与环境光影响，只按法线计算一次响应
+        if let Some(material) = triangle.material
+            && material.material_type.is_unlit()
+        {
+            let normal = match (
+                triangle.vertices[0].normal_view,
+                triangle.vertices[1].normal_view,
+                triangle.vertices[2].normal_view,
+            ) {
+                (Some(n0), Some(n1), Some(n2)) => interpolate_normal(
+                    bary,
+                    n0,
+                    n1,
+                    n2,
+                    triangle.is_perspective,
+                    triangle.vertices[0].z_view,
+                    triangle.vertices[1].z_view,
+                    triangle.vertices[2].z_view,
+                ),
+                _ => Vector3::z(),
+            };
+            let response =
+                compute_material_response(material, &Vector3::z(), &Vector3::z(), &normal, None);
+            return surface_color.component_mul(&response);
+        }
+
+        if let Some(material) = triangle.material
+            && use_lighting
             && triangle.vertices[0].normal_view.is_some()
             && triangle.vertices[0].position_view.is_some()
             && !triangle.lights.is_empty()
@@ -410,14 +501,21 @@ impl Rasterizer {
             );
             let view_dir = (-interp_position.coords).normalize();
             let mut total_direct_light = Vector3::zeros();
-            for light in triangle.lights {
+            for (light_index, light) in triangle.lights.iter().enumerate() {
                 let light_dir = light.get_direction(&interp_position);
-                let light_intensity = light.get_intensity(&interp_position);
+                let mut light_intensity = light.get_intensity(&interp_position);
+                if let Some(shadow) = triangle.shadow
+                    && shadow.light_index == light_index
+                {
+                    light_intensity *=
+                        shadow.visibility(&interp_position, &interp_normal, &light_dir, settings);
+                }
                 let response = compute_material_response(
-                    triangle.material.unwrap(),
+                    material,
                     &light_dir,
                     &view_dir,
                     &interp_normal,
+                    triangle.tangent_view.as_ref(),
                 );
                 total_direct_light += Vector3::new(
                     response.x * light_intensity.x,
@@ -433,6 +531,58 @@ impl Rasterizer {
         }
     }
 
+    /// 由重心坐标换算像素到最近边的距离，得到抗锯齿线框覆盖率
+    fn edge_coverage(triangle: &RasterTriangle, bary: Vector3<f32>, line_width: f32) -> f32 {
+        let p0 = triangle.vertices[0].pix;
+        let p1 = triangle.vertices[1].pix;
+        let p2 = triangle.vertices[2].pix;
+        let double_area = ((p1 - p0).perp(&(p2 - p0))).abs();
+        // 顶点 i 的重心坐标乘以 2*面积/对边长度 即为到对边的距离
+        let opposite_lengths = [(p2 - p1).norm(), (p0 - p2).norm(), (p1 - p0).norm()];
+        let distance = (0..3)
+            .filter(|&i| opposite_lengths[i] > 1e-6)
+            .map(|i| bary[i] * double_area / opposite_lengths[i])
+            .fold(f32::INFINITY, f32::min);
+        // 线宽的一半落在相邻三角形内，由其各自绘制
+        (line_width * 0.5 + 0.5 - distance).clamp(0.0, 1.0)
+    }
+
+    fn interpolate_triangle_texcoord(
+        triangle: &RasterTriangle,
+        bary: Vector3<f32>,
+    ) -> Option<Vector2<f32>> {
+        let (tc1, tc2, tc3) = (
+            triangle.vertices[0].texcoord?,
+            triangle.vertices[1].texcoord?,
+            triangle.vertices[2].texcoord?,
+        );
+        Some(interpolate_texcoords(
+            bary,
+            tc1,
+            tc2,
+            tc3,
+            triangle.vertices[0].z_view,
+            triangle.vertices[1].z_view,
+            triangle.vertices[2].z_view,
+            triangle.is_perspective,
+        ))
+    }
+
+    /// Alpha 测试：遮罩纹理（map_d）与漫反射纹理 alpha 的乘积低于阈值时丢弃片元
+    fn is_alpha_cutout(
+        triangle: &RasterTriangle,
+        bary: Vector3<f32>,
+        settings: &RenderSettings,
+    ) -> bool {
+        let Some(material) = triangle.material.filter(|m| m.has_alpha_cutout()) else {
+            return false;
+        };
+        let Some(tc) = Self::interpolate_triangle_texcoord(triangle, bary) else {
+            return false;
+        };
+        material.alpha_coverage(tc.x, tc.y) < settings.alpha_cutoff
+    }
+
     fn calculate_ambient(triangle: &RasterTriangle) -> Vector3<f32> {
         let ambient_color = triangle.ambient_color;
         let ambient_intensity = triangle.ambient_intensity;
@@ -502,3 +652,16 @@ impl Rasterizer {
         }
     }
 }
+
+/// UV 检查棋盘格：8x8 格子，颜色随 UV 渐变以便分辨方向与拉伸
+fn uv_checker_color(u: f32, v: f32) -> Vector3<f32> {
+    let cells = 8.0;
+    let is_light = ((u * cells).floor() as i64 + (v * cells).floor() as i64).rem_euclid(2) == 0;
+    let brightness = if is_light { 0.9 } else { 0.25 };
+    let gradient = Vector3::new(
+        0.5 + 0.5 * u.rem_euclid(1.0),
+        0.5 + 0.5 * v.rem_euclid(1.0),
+        0.75,
+    );
+    gradient * brightness
+}
@@ -1,12 +1,19 @@
+use crate::core::debug_overlay::draw_debug_overlays;
+use crate::core::depth_of_field::apply_depth_of_field;
 use crate::core::frame_buffer::FrameBuffer;
+use crate::core::line_renderer::{
+    LineBuffer, LineDepthTest, LineStyle, ScreenLine, ScreenPoint, edge_to_screen_line,
+    extract_feature_lines, extract_wireframe_lines,
+};
 use crate::core::rasterizer::Rasterizer;
-use crate::core::shadow_map::ShadowMap;
+use crate::core::shadow_map::{ShadowMap, ShadowReceiver};
 use crate::geometry::camera::Camera;
+use crate::geometry::edges::EdgeAdjacency;
 use crate::geometry::transform::{
     TransformFactory, clip_to_screen, compute_normal_matrix, point_to_clip, transform_normal,
     transform_point,
 };
-use crate::io::render_settings::RenderSettings;
+use crate::io::render_settings::{FeatureLineMode, HiddenEdgeStyle, RenderSettings};
 use crate::material_system::light::Light;
 use crate::scene::scene_object::SceneObject;
 use crate::scene::scene_utils::Scene;
@@ -24,18 +31,18 @@ pub struct TransformedGeometry {
 
 pub fn transform_geometry(
     scene_object: &SceneObject,
-    camera: &mut Camera,
+    camera: &Camera,
     frame_width: usize,
     frame_height: usize,
 ) -> TransformedGeometry {
-    camera.update_matrices();
-
     let mut vertices = Vec::new();
     let mut normals = Vec::new();
     let mut mesh_offsets = vec![0];
+    // 蒙皮在模型空间中进行，之后再做模型视图投影变换
     for mesh in &scene_object.model.meshes {
-        vertices.extend(mesh.vertices.iter().map(|v| v.position));
-        normals.extend(mesh.vertices.iter().map(|v| v.normal));
+        let posed = scene_object.posed_vertices(mesh);
+        vertices.extend(posed.iter().map(|v| v.position));
+        normals.extend(posed.iter().map(|v| v.normal));
         mesh_offsets.push(vertices.len());
     }
 
@@ -101,6 +108,15 @@ impl Renderer {
 
         self.resize(settings.width, settings.height);
 
+        if let Some(terrain) = &mut scene.terrain {
+            terrain.update_lod(&scene.active_camera.position(), scene.lod_hysteresis);
+        }
+
+        // 渲染期间各对象的 model.meshes 为所选细节层级，结束时换回原始网格
+        scene.active_camera.update_matrices();
+        scene.update_lods(settings, self.frame_buffer.height);
+        scene.swap_lod_meshes();
+
         if settings.enable_shadow_mapping {
             self.generate_shadow_map(scene, settings);
         }
@@ -108,22 +124,48 @@ impl Renderer {
         self.frame_buffer
             .clear(settings, &scene.active_camera, self.shadow_map.as_ref());
 
-        let geometry = transform_geometry(
-            &scene.object,
-            &mut scene.active_camera,
-            self.frame_buffer.width,
-            self.frame_buffer.height,
-        );
+        let geometries: Vec<TransformedGeometry> = scene
+            .render_objects()
+            .map(|object| {
+                transform_geometry(
+                    object,
+                    &scene.active_camera,
+                    self.frame_buffer.width,
+                    self.frame_buffer.height,
+                )
+            })
+            .collect();
+        // 线条与调试叠加层只作用于主对象
+        let geometry = &geometries[0];
+
+        let shadow_receiver = Self::shadow_receiver(self.shadow_map.as_ref(), scene, settings);
+        let mut triangles = Vec::new();
+        for (object, object_geometry) in scene.render_objects().zip(&geometries) {
+            let mut object_triangles = Rasterizer::prepare_triangles(
+                &object.model,
+                object_geometry,
+                None,
+                settings,
+                &scene.lights,
+                scene.ambient_intensity,
+                scene.ambient_color,
+            );
+            if object.cull_behind_camera {
+                object_triangles.retain(|triangle| !triangle.crosses_camera_plane());
+            }
+            let main_object = std::ptr::eq(object, &scene.object);
+            let shadow = shadow_receiver.as_ref().filter(|_| object.receive_shadows);
+            for triangle in &mut object_triangles {
+                triangle.main_object = main_object;
+                triangle.shadow = shadow;
+            }
+            triangles.extend(object_triangles);
+        }
 
-        let triangles = Rasterizer::prepare_triangles(
-            &scene.object.model,
-            &geometry,
-            None,
-            settings,
-            &scene.lights,
-            scene.ambient_intensity,
-            scene.ambient_color,
-        );
+        // 独立线稿模式：表面只写入深度，之后恢复背景颜色
+        let background_snapshot = (settings.enable_feature_lines
+            && settings.feature_line_mode == FeatureLineMode::Standalone)
+            .then(|| self.frame_buffer.get_color_buffer_bytes());
 
         Rasterizer::rasterize_triangles(
             &triangles,
@@ -135,15 +177,223 @@ impl Renderer {
             &self.frame_buffer,
         );
 
+        if let Some(background) = background_snapshot {
+            self.frame_buffer.set_color_buffer_bytes(&background);
+        }
+
+        self.draw_primitives(scene, geometry, settings);
+        let draw_hidden_edges = settings.shaded_wireframe
+            && settings.use_zbuffer
+            && settings.hidden_edge_style != HiddenEdgeStyle::Culled;
+        if settings.enable_feature_lines || draw_hidden_edges {
+            let adjacency = scene.object.edge_adjacency();
+            if draw_hidden_edges {
+                self.draw_hidden_edges(&adjacency, geometry, settings);
+            }
+            if settings.enable_feature_lines {
+                self.draw_feature_lines(&adjacency, geometry, settings);
+            }
+        }
+
+        // 景深作用于完整画面，调试叠加层保持清晰
+        if settings.enable_depth_of_field {
+            apply_depth_of_field(&self.frame_buffer, &scene.active_camera, settings);
+        }
+
+        if settings.has_debug_line_overlays() {
+            let shadow_map = self
+                .shadow_map
+                .as_ref()
+                .filter(|_| settings.enable_shadow_mapping);
+            draw_debug_overlays(&self.frame_buffer, scene, geometry, shadow_map, settings);
+        }
+
+        // 三角形引用了场景中的材质，换回网格前释放
+        let triangle_count = triangles.len();
+        drop(triangles);
+        scene.swap_lod_meshes();
+
         self.last_frame_time = Some(frame_start.elapsed());
         debug!(
-            "渲染完成 '{}': {} 三角形, 耗时: {:?}",
+            "渲染完成 '{}' (LOD{}): {} 三角形, 耗时: {:?}",
             scene.object.model.name,
-            triangles.len(),
+            scene.object.lod_level,
+            triangle_count,
             self.last_frame_time.unwrap()
         );
     }
 
+    /// OBJ 中的线段（`l`）与点（`p`）图元，使用材质基础色，并与已绘制的表面做深度测试
+    fn draw_primitives(
+        &self,
+        scene: &Scene,
+        geometry: &TransformedGeometry,
+        settings: &RenderSettings,
+    ) {
+        let model = &scene.object.model;
+        if model
+            .meshes
+            .iter()
+            .all(|mesh| mesh.lines.is_empty() && mesh.points.is_empty())
+        {
+            return;
+        }
+
+        let is_perspective = settings.is_perspective();
+        let depth_test = if settings.use_zbuffer {
+            LineDepthTest::PerPixel(&self.frame_buffer.depth_buffer)
+        } else {
+            LineDepthTest::Disabled
+        };
+        let depth_buffer = settings
+            .use_zbuffer
+            .then_some(self.frame_buffer.depth_buffer.as_slice());
+        // 视图空间单位到像素的换算，透视投影下还需除以深度
+        let pixels_per_unit =
+            scene.active_camera.projection_matrix()[(1, 1)] * self.frame_buffer.height as f32 * 0.5;
+
+        for (mesh_index, mesh) in model.meshes.iter().enumerate() {
+            if mesh.lines.is_empty() && mesh.points.is_empty() {
+                continue;
+            }
+            let offset = geometry.mesh_offsets[mesh_index];
+            let color = model
+                .materials
+                .get(mesh.material_id)
+                .map_or(Vector3::new(0.7, 0.7, 0.7), |m| m.base_color);
+
+            let lines: Vec<ScreenLine> = mesh
+                .lines
+                .chunks_exact(2)
+                .filter_map(|pair| {
+                    edge_to_screen_line(
+                        geometry,
+                        [offset + pair[0] as usize, offset + pair[1] as usize],
+                    )
+                })
+                .collect();
+            let points: Vec<ScreenPoint> = mesh
+                .points
+                .iter()
+                .filter_map(|&index| {
+                    let global_index = offset + index as usize;
+                    let depth = -geometry.view_coords[global_index].z;
+                    if depth <= 1e-4 {
+                        return None;
+                    }
+                    let world_radius = settings.point_size * 0.5 * pixels_per_unit;
+                    Some(ScreenPoint {
+                        center: geometry.screen_coords[global_index],
+                        depth,
+                        radius: if is_perspective {
+                            world_radius / depth
+                        } else {
+                            world_radius
+                        },
+                    })
+                })
+                .collect();
+
+            let line_buffer = LineBuffer::new(self.frame_buffer.width, self.frame_buffer.height);
+            line_buffer.draw_lines(
+                &lines,
+                &LineStyle::solid(settings.primitive_line_width),
+                depth_test,
+                is_perspective,
+            );
+            line_buffer.draw_points(&points, depth_buffer);
+            line_buffer.composite(&self.frame_buffer.color_buffer, &color, settings.use_gamma);
+        }
+    }
+
+    /// 着色+线框模式下被遮挡的边（可见边已在光栅化时按重心距离绘制）
+    fn draw_hidden_edges(
+        &self,
+        adjacency: &EdgeAdjacency,
+        geometry: &TransformedGeometry,
+        settings: &RenderSettings,
+    ) {
+        let lines = extract_wireframe_lines(adjacency, geometry);
+        let style = match settings.hidden_edge_style {
+            HiddenEdgeStyle::Dashed => LineStyle {
+                dash_length: Some(4.0),
+                ..LineStyle::solid(settings.wireframe_width)
+            },
+            _ => LineStyle {
+                opacity: 0.3,
+                ..LineStyle::solid(settings.wireframe_width)
+            },
+        };
+
+        let line_buffer = LineBuffer::new(self.frame_buffer.width, self.frame_buffer.height);
+        line_buffer.draw_lines(
+            &lines,
+            &style,
+            LineDepthTest::HiddenOnly(&self.frame_buffer.depth_buffer),
+            settings.is_perspective(),
+        );
+        line_buffer.composite(
+            &self.frame_buffer.color_buffer,
+            &settings.get_wireframe_color_vec(),
+            settings.use_gamma,
+        );
+    }
+
+    fn draw_feature_lines(
+        &self,
+        adjacency: &EdgeAdjacency,
+        geometry: &TransformedGeometry,
+        settings: &RenderSettings,
+    ) {
+        let lines = extract_feature_lines(adjacency, geometry, settings);
+
+        let line_buffer = LineBuffer::new(self.frame_buffer.width, self.frame_buffer.height);
+        let depth_test = if settings.use_zbuffer {
+            LineDepthTest::VisibleOnly(&self.frame_buffer.depth_buffer)
+        } else {
+            LineDepthTest::Disabled
+        };
+        line_buffer.draw_lines(
+            &lines,
+            &LineStyle::solid(settings.line_width),
+            depth_test,
+            settings.is_perspective(),
+        );
+        line_buffer.composite(
+            &self.frame_buffer.color_buffer,
+            &settings.get_line_color_vec(),
+            settings.use_gamma,
+        );
+
+        debug!(
+            "特征线: {} 条边中提取 {} 条",
+            adjacency.edges.len(),
+            lines.len()
+        );
+    }
+
+    /// 接收阴影的对象在着色时使用的阴影数据
+    fn shadow_receiver<'a>(
+        shadow_map: Option<&'a ShadowMap>,
+        scene: &Scene,
+        settings: &RenderSettings,
+    ) -> Option<ShadowReceiver<'a>> {
+        if !settings.enable_shadow_mapping {
+            return None;
+        }
+        let shadow_map = shadow_map.filter(|map| map.is_valid)?;
+        let light_index = scene
+            .lights
+            .iter()
+            .position(|light| matches!(light, Light::Directional { enabled: true, .. }))?;
+        Some(ShadowReceiver {
+            shadow_map,
+            view_to_world: scene.active_camera.view_matrix().try_inverse()?,
+            light_index,
+            kernel_rotation: scene.active_camera.sample_rotation(),
+        })
+    }
+
     fn generate_shadow_map(&mut self, scene: &Scene, settings: &RenderSettings) {
         if self.shadow_map.is_none()
             || self.shadow_map.as_ref().unwrap().size != settings.shadow_map_size
@@ -163,7 +413,16 @@ impl Renderer {
             .find(|light| matches!(light, Light::Directional { enabled: true, .. }))
         {
             let scene_bounds = Self::compute_scene_bounds(scene, settings);
-            shadow_map.generate(&scene.object, directional_light, scene_bounds);
+            let casters: Vec<&SceneObject> = scene
+                .render_objects()
+                .filter(|object| object.cast_shadows)
+                .collect();
+            shadow_map.generate(
+                &casters,
+                directional_light,
+                scene_bounds,
+                settings.alpha_cutoff,
+            );
 
             if shadow_map.is_valid {
                 debug!("阴影贴图已更新");
@@ -185,19 +444,23 @@ impl Renderer {
             nalgebra::Point3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
         let mut has_vertices = false;
 
-        for mesh in &scene.object.model.meshes {
-            for vertex in &mesh.vertices {
-                let world_pos = scene.object.transform.transform_point(&vertex.position);
+        for object in scene.render_objects().filter(|object| object.cast_shadows) {
+            // 阴影范围按当前姿态计算，动画中的肢体不会移出阴影贴图
+            for mesh in &object.model.meshes {
+                let posed = object.posed_vertices(mesh);
+                for vertex in posed.iter() {
+                    let world_pos = object.transform.transform_point(&vertex.position);
 
-                min_pos.x = min_pos.x.min(world_pos.x);
-                min_pos.y = min_pos.y.min(world_pos.y);
-                min_pos.z = min_pos.z.min(world_pos.z);
+                    min_pos.x = min_pos.x.min(world_pos.x);
+                    min_pos.y = min_pos.y.min(world_pos.y);
+                    min_pos.z = min_pos.z.min(world_pos.z);
 
-                max_pos.x = max_pos.x.max(world_pos.x);
-                max_pos.y = max_pos.y.max(world_pos.y);
-                max_pos.z = max_pos.z.max(world_pos.z);
+                    max_pos.x = max_pos.x.max(world_pos.x);
+                    max_pos.y = max_pos.y.max(world_pos.y);
+                    max_pos.z = max_pos.z.max(world_pos.z);
 
-                has_vertices = true;
+                    has_vertices = true;
+                }
             }
         }
 
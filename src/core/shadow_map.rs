@@ -1,9 +1,66 @@
 use crate::geometry::interpolation::barycentric_coordinates;
 use crate::geometry::transform::{TransformFactory, transform_point};
+use crate::io::render_settings::RenderSettings;
 use crate::material_system::light::Light;
+use crate::material_system::materials::Material;
 use crate::scene::scene_object::SceneObject;
 use log::debug;
-use nalgebra::{Matrix4, Point2, Point3, Vector3};
+use nalgebra::{Matrix4, Point2, Point3, Vector2, Vector3};
+
+/// 着色阶段查询阴影所需的数据
+pub struct ShadowReceiver<'a> {
+    pub shadow_map: &'a ShadowMap,
+    /// 视图空间到世界空间的变换
+    pub view_to_world: Matrix4<f32>,
+    /// 生成阴影贴图的方向光在光源列表中的索引
+    pub light_index: usize,
+    /// PCF 采样核的旋转角（弧度），见 [`Camera::sample_rotation`](crate::geometry::camera::Camera::sample_rotation)
+    pub kernel_rotation: f32,
+}
+
+impl ShadowReceiver<'_> {
+    /// 视图空间位置处的光照可见度(0.0-1.0)
+    ///
+    /// 偏移量随表面与光线的夹角增大，减少掠射角处的自阴影条纹。
+    pub fn visibility(
+        &self,
+        view_position: &Point3<f32>,
+        normal: &Vector3<f32>,
+        light_dir: &Vector3<f32>,
+        settings: &RenderSettings,
+    ) -> f32 {
+        let cos_theta = normal.dot(light_dir);
+        if cos_theta <= 0.0 {
+            // 背光面不受直接光照，无需查询
+            return 1.0;
+        }
+        let cos_theta = cos_theta.max(0.1);
+        let slope = (1.0 - cos_theta * cos_theta).sqrt() / cos_theta;
+        self.shadow_map.compute_shadow_factor(
+            view_position,
+            &self.view_to_world,
+            settings.shadow_bias * (1.0 + slope),
+            self.kernel_rotation,
+            settings,
+        )
+    }
+}
+
+/// 阴影投射三角形的 alpha 测试数据
+struct AlphaCutout<'a> {
+    material: &'a Material,
+    texcoords: [Vector2<f32>; 3],
+    cutoff: f32,
+}
+
+impl AlphaCutout<'_> {
+    /// 重心坐标处的覆盖率低于阈值，该处不写入深度（光源为正交投影，纹理坐标线性插值即可）
+    fn discards(&self, bary: Vector3<f32>) -> bool {
+        let [tc0, tc1, tc2] = self.texcoords;
+        let tc = tc0 * bary.x + tc1 * bary.y + tc2 * bary.z;
+        self.material.alpha_coverage(tc.x, tc.y) < self.cutoff
+    }
+}
 
 /// 简化阴影贴图
 #[derive(Debug, Clone)]
@@ -26,9 +83,10 @@ impl ShadowMap {
 
     pub fn generate(
         &mut self,
-        scene_object: &SceneObject,
+        casters: &[&SceneObject],
         directional_light: &Light,
         scene_bounds: (Point3<f32>, f32),
+        alpha_cutoff: f32,
     ) -> bool {
         if let Light::Directional {
             direction, enabled, ..
@@ -39,7 +97,10 @@ impl ShadowMap {
             }
 
             self.setup_light_matrices(direction, scene_bounds);
-            self.render_shadow_casters(scene_object);
+            self.depth_buffer.fill(f32::INFINITY);
+            for scene_object in casters {
+                self.render_shadow_casters(scene_object, alpha_cutoff);
+            }
             self.is_valid = true;
             debug!("阴影贴图生成完成: {}x{}", self.size, self.size);
             true
@@ -75,26 +136,29 @@ impl ShadowMap {
         self.light_view_proj_matrix = light_proj * light_view;
     }
 
-    fn render_shadow_casters(&mut self, scene_object: &SceneObject) {
-        self.depth_buffer.fill(f32::INFINITY);
+    fn render_shadow_casters(&mut self, scene_object: &SceneObject, alpha_cutoff: f32) {
         let full_transform = self.light_view_proj_matrix * scene_object.transform;
 
         let mut triangles_processed = 0;
         let mut triangles_culled = 0;
 
         for mesh in &scene_object.model.meshes {
+            let posed = scene_object.posed_vertices(mesh);
+            // 与主渲染相同的 alpha 测试，镂空部分不投射阴影
+            let cutout_material = scene_object
+                .model
+                .materials
+                .get(mesh.material_id)
+                .filter(|material| material.has_alpha_cutout());
             for indices in mesh.indices.chunks_exact(3) {
-                let vertices = [
-                    mesh.vertices[indices[0] as usize].position,
-                    mesh.vertices[indices[1] as usize].position,
-                    mesh.vertices[indices[2] as usize].position,
+                let corners = [
+                    &posed[indices[0] as usize],
+                    &posed[indices[1] as usize],
+                    &posed[indices[2] as usize],
                 ];
 
-                let transformed_vertices = [
-                    transform_point(&vertices[0], &full_transform),
-                    transform_point(&vertices[1], &full_transform),
-                    transform_point(&vertices[2], &full_transform),
-                ];
+                let transformed_vertices =
+                    corners.map(|vertex| transform_point(&vertex.position, &full_transform));
 
                 if self.is_triangle_outside_frustum(&transformed_vertices) {
                     triangles_culled += 1;
@@ -107,7 +171,12 @@ impl ShadowMap {
                     self.ndc_to_shadow_coord(transformed_vertices[2].x, transformed_vertices[2].y),
                 ];
 
-                self.rasterize_triangle(&transformed_vertices, &screen_coords);
+                let cutout = cutout_material.map(|material| AlphaCutout {
+                    material,
+                    texcoords: corners.map(|vertex| vertex.texcoord),
+                    cutoff: alpha_cutoff,
+                });
+                self.rasterize_triangle(&transformed_vertices, &screen_coords, cutout.as_ref());
                 triangles_processed += 1;
             }
         }
@@ -125,7 +194,12 @@ impl ShadowMap {
         outside_bounds(|v| v.x) || outside_bounds(|v| v.y) || outside_bounds(|v| v.z)
     }
 
-    fn rasterize_triangle(&mut self, vertices: &[Point3<f32>; 3], screen_coords: &[(f32, f32); 3]) {
+    fn rasterize_triangle(
+        &mut self,
+        vertices: &[Point3<f32>; 3],
+        screen_coords: &[(f32, f32); 3],
+        cutout: Option<&AlphaCutout>,
+    ) {
         let (min_x, max_x) = screen_coords
             .iter()
             .map(|(x, _)| *x as i32)
@@ -163,18 +237,23 @@ impl ShadowMap {
                     triangle_points[0],
                     triangle_points[1],
                     triangle_points[2],
-                ) {
-                    if bary.x >= 0.0 && bary.y >= 0.0 && bary.z >= 0.0 {
-                        let depth = bary.x * vertices[0].z
-                            + bary.y * vertices[1].z
-                            + bary.z * vertices[2].z;
-                        let shadow_index = (y as usize) * self.size + (x as usize);
-
-                        if shadow_index < self.depth_buffer.len()
-                            && depth < self.depth_buffer[shadow_index]
-                        {
-                            self.depth_buffer[shadow_index] = depth;
-                        }
+                ) && bary.x >= 0.0
+                    && bary.y >= 0.0
+                    && bary.z >= 0.0
+                {
+                    if let Some(cutout) = cutout
+                        && cutout.discards(bary)
+                    {
+                        continue;
+                    }
+                    let depth =
+                        bary.x * vertices[0].z + bary.y * vertices[1].z + bary.z * vertices[2].z;
+                    let shadow_index = (y as usize) * self.size + (x as usize);
+
+                    if shadow_index < self.depth_buffer.len()
+                        && depth < self.depth_buffer[shadow_index]
+                    {
+                        self.depth_buffer[shadow_index] = depth;
                     }
                 }
             }
@@ -202,15 +281,16 @@ impl ShadowMap {
             .unwrap_or(f32::INFINITY)
     }
 
+    /// 世界坐标处的光照可见度，PCF 开关和核参数取自 `settings`
+    ///
+    /// PCF 采样网格绕中心旋转 `kernel_rotation` 弧度，多帧累积时逐帧改变角度可以平均出更平滑的半影。
     pub fn compute_shadow_factor(
         &self,
         world_pos: &Point3<f32>,
         model_matrix: &Matrix4<f32>,
         bias: f32,
-        enable_pcf: bool,
-        pcf_type: &str,
-        pcf_kernel: usize,
-        pcf_sigma: f32,
+        kernel_rotation: f32,
+        settings: &RenderSettings,
     ) -> f32 {
         if !self.is_valid {
             return 1.0;
@@ -230,19 +310,23 @@ impl ShadowMap {
 
         let current_depth = light_space_pos.z;
 
-        if enable_pcf {
-            let kernel = pcf_kernel as i32;
-            let sigma = pcf_sigma;
+        if settings.enable_pcf {
+            let kernel = settings.pcf_kernel as i32;
+            let sigma = settings.pcf_sigma;
+
+            let (sin, cos) = kernel_rotation.sin_cos();
+            let texel = 1.0 / self.size as f32;
 
             let mut shadow = 0.0;
             let mut total_weight = 0.0;
 
             for dx in -kernel..=kernel {
                 for dy in -kernel..=kernel {
-                    let u = shadow_coords.0 + dx as f32 / self.size as f32;
-                    let v = shadow_coords.1 + dy as f32 / self.size as f32;
+                    let (fx, fy) = (dx as f32, dy as f32);
+                    let u = shadow_coords.0 + (fx * cos - fy * sin) * texel;
+                    let v = shadow_coords.1 + (fx * sin + fy * cos) * texel;
 
-                    let weight = if pcf_type == "Gauss" {
+                    let weight = if settings.pcf_type == "Gauss" {
                         (-((dx * dx + dy * dy) as f32) / (2.0 * sigma * sigma)).exp()
                     } else {
                         1.0 // Box
@@ -24,6 +24,27 @@ impl ProjectionType {
     }
 }
 
+/// 物理镜头参数，用于景深计算；场景单位按米换算
+#[derive(Debug, Clone, PartialEq)]
+pub struct LensParams {
+    /// 光圈 f 值，越小景深越浅
+    pub f_stop: f32,
+    /// 对焦距离（场景单位），未指定时对焦到观察目标
+    pub focus_distance: Option<f32>,
+    /// 传感器高度（毫米），与垂直视场角共同决定焦距
+    pub sensor_height_mm: f32,
+}
+
+impl Default for LensParams {
+    fn default() -> Self {
+        Self {
+            f_stop: 2.8,
+            focus_distance: None,
+            sensor_height_mm: 24.0,
+        }
+    }
+}
+
 /// 相机参数结构体，包含所有相机配置信息
 #[derive(Debug, Clone)]
 pub struct CameraParams {
@@ -33,6 +54,7 @@ pub struct CameraParams {
     pub projection: ProjectionType,
     pub near: f32,
     pub far: f32,
+    pub lens: LensParams,
 }
 
 impl Default for CameraParams {
@@ -47,6 +69,7 @@ impl Default for CameraParams {
             },
             near: 0.1,
             far: 100.0,
+            lens: LensParams::default(),
         }
     }
 }
@@ -59,6 +82,11 @@ pub struct Camera {
     // 预计算的矩阵 - 每次创建时计算一次
     view_matrix: Matrix4<f32>,
     projection_matrix: Matrix4<f32>,
+
+    /// 子像素抖动在 NDC 中的平移量，叠加在投影矩阵上
+    jitter_ndc: (f32, f32),
+    /// PCF 采样核和景深采样圆盘的旋转角（弧度）
+    sample_rotation: f32,
 }
 
 impl Camera {
@@ -68,6 +96,8 @@ impl Camera {
             params,
             view_matrix: Matrix4::identity(),
             projection_matrix: Matrix4::identity(),
+            jitter_ndc: (0.0, 0.0),
+            sample_rotation: 0.0,
         };
         camera.update_matrices();
         camera
@@ -93,6 +123,7 @@ impl Camera {
             },
             near,
             far,
+            lens: LensParams::default(),
         };
         Self::new(params)
     }
@@ -114,6 +145,7 @@ impl Camera {
             projection: ProjectionType::Orthographic { width, height },
             near,
             far,
+            lens: LensParams::default(),
         };
         Self::new(params)
     }
@@ -140,6 +172,49 @@ impl Camera {
         self.params.near
     }
 
+    /// 实际对焦距离：未指定时为相机到观察目标的距离
+    pub fn focus_distance(&self) -> f32 {
+        self.params
+            .lens
+            .focus_distance
+            .unwrap_or_else(|| (self.params.target - self.params.position).norm())
+            .max(self.params.near)
+    }
+
+    /// 由传感器高度和垂直视场角得到的焦距（毫米），正交投影没有焦距
+    pub fn focal_length_mm(&self) -> Option<f32> {
+        match &self.params.projection {
+            ProjectionType::Perspective { fov_y_degrees, .. } => Some(
+                self.params.lens.sensor_height_mm * 0.5 / (fov_y_degrees.to_radians() * 0.5).tan(),
+            ),
+            ProjectionType::Orthographic { .. } => None,
+        }
+    }
+
+    /// 视图深度 `depth` 处的弥散圆半径（像素），`depth` 为无穷大时取无限远处的值
+    ///
+    /// 按薄透镜公式计算传感器上的弥散圆直径，再按传感器高度换算到图像高度。
+    pub fn circle_of_confusion(&self, depth: f32, image_height: usize) -> f32 {
+        let Some(focal_length_mm) = self.focal_length_mm() else {
+            return 0.0;
+        };
+        let lens = &self.params.lens;
+        let focal_length = focal_length_mm * 0.001;
+        let focus = self.focus_distance();
+        if focus <= focal_length || lens.f_stop <= 0.0 {
+            return 0.0;
+        }
+        let aperture_mm = focal_length_mm / lens.f_stop;
+        // 无限远时 |d - s| / d 趋于 1
+        let defocus = if depth.is_finite() {
+            (depth - focus).abs() / depth.max(focal_length)
+        } else {
+            1.0
+        };
+        let diameter_mm = aperture_mm * defocus * focal_length / (focus - focal_length);
+        diameter_mm / lens.sensor_height_mm * image_height as f32 * 0.5
+    }
+
     // ============ 矩阵访问方法 ============
 
     /// 获取视图矩阵
@@ -318,6 +393,38 @@ impl Camera {
         true
     }
 
+    /// 设置子像素抖动（单位：像素，x 向右、y 向下），用于多帧累积抗锯齿；(0, 0) 取消抖动
+    pub fn set_subpixel_jitter(
+        &mut self,
+        offset_x: f32,
+        offset_y: f32,
+        width: usize,
+        height: usize,
+    ) {
+        self.jitter_ndc = (
+            2.0 * offset_x / width.max(1) as f32,
+            -2.0 * offset_y / height.max(1) as f32,
+        );
+        self.update_projection_matrix();
+    }
+
+    /// 当前子像素抖动在 NDC 中的平移量
+    pub fn subpixel_jitter_ndc(&self) -> (f32, f32) {
+        self.jitter_ndc
+    }
+
+    /// 设置 PCF 采样核和景深采样圆盘的旋转角（弧度）
+    ///
+    /// 多帧累积时每个样本取不同角度，软阴影和散景的采样位置逐帧错开，平均后收敛；0 为默认朝向。
+    pub fn set_sample_rotation(&mut self, radians: f32) {
+        self.sample_rotation = radians;
+    }
+
+    /// 当前 PCF 采样核和景深采样圆盘的旋转角（弧度）
+    pub fn sample_rotation(&self) -> f32 {
+        self.sample_rotation
+    }
+
     // ============ 内部实现方法 ============
 
     /// 更新所有矩阵（创建时和修改后调用）
@@ -334,7 +441,7 @@ impl Camera {
 
     /// 更新投影矩阵
     fn update_projection_matrix(&mut self) {
-        self.projection_matrix = match &self.params.projection {
+        let projection = match &self.params.projection {
             ProjectionType::Perspective {
                 fov_y_degrees,
                 aspect_ratio,
@@ -353,6 +460,10 @@ impl Camera {
                 self.params.far,
             ),
         };
+        // 在裁剪空间按 w 平移，透视和正交投影都整体移动同样的像素数
+        let (jitter_x, jitter_y) = self.jitter_ndc;
+        self.projection_matrix =
+            Matrix4::new_translation(&Vector3::new(jitter_x, jitter_y, 0.0)) * projection;
     }
 }
 
@@ -0,0 +1,87 @@
+use crate::material_system::materials::Model;
+use std::collections::HashMap;
+
+/// 网格中的一条边
+#[derive(Debug, Clone)]
+pub struct MeshEdge {
+    /// 边的两个端点（全局顶点索引，与 `TransformedGeometry` 中的顺序一致）
+    pub vertices: [usize; 2],
+    /// 共享该边的面（索引指向 `EdgeAdjacency::faces`）
+    pub faces: Vec<usize>,
+}
+
+impl MeshEdge {
+    /// 只属于一个面的边（开放网格的边界）
+    pub fn is_boundary(&self) -> bool {
+        self.faces.len() == 1
+    }
+
+    /// 恰好被两个面共享的边
+    pub fn is_manifold(&self) -> bool {
+        self.faces.len() == 2
+    }
+}
+
+/// 模型的边-面邻接关系
+///
+/// OBJ 加载时会在 UV/法线接缝处复制顶点，这里按位置焊接顶点，
+/// 因此接缝不会被误判为边界边。
+#[derive(Debug, Clone, Default)]
+pub struct EdgeAdjacency {
+    /// 所有三角形面（全局顶点索引）
+    pub faces: Vec<[usize; 3]>,
+    /// 所有唯一边
+    pub edges: Vec<MeshEdge>,
+}
+
+impl EdgeAdjacency {
+    pub fn build(model: &Model) -> Self {
+        let mut faces = Vec::new();
+        let mut welded_ids: HashMap<[u32; 3], usize> = HashMap::new();
+        let mut welded = Vec::new();
+
+        let mut vertex_offset = 0;
+        for mesh in &model.meshes {
+            for vertex in &mesh.vertices {
+                let key = [
+                    vertex.position.x.to_bits(),
+                    vertex.position.y.to_bits(),
+                    vertex.position.z.to_bits(),
+                ];
+                let next_id = welded_ids.len();
+                welded.push(*welded_ids.entry(key).or_insert(next_id));
+            }
+            for indices in mesh.indices.chunks_exact(3) {
+                faces.push([
+                    vertex_offset + indices[0] as usize,
+                    vertex_offset + indices[1] as usize,
+                    vertex_offset + indices[2] as usize,
+                ]);
+            }
+            vertex_offset += mesh.vertices.len();
+        }
+
+        let mut edge_ids: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut edges: Vec<MeshEdge> = Vec::new();
+        for (face_index, face) in faces.iter().enumerate() {
+            for k in 0..3 {
+                let (a, b) = (face[k], face[(k + 1) % 3]);
+                let (wa, wb) = (welded[a], welded[b]);
+                if wa == wb {
+                    continue; // 退化边
+                }
+                let key = (wa.min(wb), wa.max(wb));
+                let edge_index = *edge_ids.entry(key).or_insert_with(|| {
+                    edges.push(MeshEdge {
+                        vertices: [a, b],
+                        faces: Vec::with_capacity(2),
+                    });
+                    edges.len() - 1
+                });
+                edges[edge_index].faces.push(face_index);
+            }
+        }
+
+        Self { faces, edges }
+    }
+}
@@ -0,0 +1,790 @@
+use crate::io::render_settings::RenderSettings;
+use crate::material_system::materials::{Mesh, Model, Vertex};
+use log::{info, warn};
+use nalgebra::{Matrix4, Point3, Vector3, Vector4};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// 简化时允许的最大法线偏转（余弦），防止三角形翻转或变得过于狭长
+const MIN_COLLAPSE_NORMAL_COS: f32 = 0.2;
+
+/// 按设置对模型执行加载时的网格处理：细分 → 简化 → 重算法线
+///
+/// 细分改变了曲面形状，之后总会重算法线；未启用重算法线时按完全平滑处理。
+pub fn apply_mesh_operations(model: &mut Model, settings: &RenderSettings) {
+    if !settings.has_mesh_operations() {
+        return;
+    }
+    let original_triangles = triangle_count(model);
+
+    if settings.subdivision_level > 0 {
+        for mesh in model.meshes.iter_mut().filter(|m| !m.indices.is_empty()) {
+            for _ in 0..settings.subdivision_level {
+                *mesh = subdivide_loop(mesh);
+            }
+        }
+    }
+
+    if settings.decimate_target > 0 {
+        let total = triangle_count(model);
+        if total > settings.decimate_target {
+            // 目标数量按各网格的三角形数量比例分配
+            for mesh in model.meshes.iter_mut().filter(|m| !m.indices.is_empty()) {
+                let share = mesh.indices.len() / 3 * settings.decimate_target / total;
+                *mesh = decimate(mesh, share.max(1));
+            }
+            let reached = triangle_count(model);
+            if reached > settings.decimate_target {
+                warn!(
+                    "简化未能达到目标 {} 个三角形（剩余 {reached} 个），边界和 UV 接缝处的顶点不会被合并",
+                    settings.decimate_target
+                );
+            }
+        }
+    }
+
+    if settings.recompute_normals || settings.subdivision_level > 0 {
+        let smoothing_angle = if settings.recompute_normals {
+            settings.smoothing_angle
+        } else {
+            180.0
+        };
+        for mesh in model.meshes.iter_mut().filter(|m| !m.indices.is_empty()) {
+            recompute_normals(mesh, smoothing_angle);
+        }
+    }
+
+    info!(
+        "网格处理完成: {} → {} 个三角形",
+        original_triangles,
+        triangle_count(model)
+    );
+}
+
+/// 逐级简化生成细节层级，每级保留上一级 `ratio` 比例的三角形
+///
+/// 某一级简化后三角形数量几乎不再减少时（边界和接缝被锁定）停止生成。
+pub fn generate_lods(model: &mut Model, levels: usize, ratio: f32) {
+    model.lods.clear();
+    let mut previous_count = triangle_count(model);
+    for level in 1..=levels {
+        let source = model.lods.last().unwrap_or(&model.meshes);
+        let meshes: Vec<Mesh> = source
+            .iter()
+            .map(|mesh| {
+                let triangles = mesh.indices.len() / 3;
+                if triangles == 0 {
+                    mesh.clone()
+                } else {
+                    decimate(mesh, ((triangles as f32 * ratio) as usize).max(1))
+                }
+            })
+            .collect();
+        let count: usize = meshes.iter().map(|mesh| mesh.indices.len() / 3).sum();
+        if count as f32 > previous_count as f32 * 0.9 {
+            warn!("LOD{level} 只能简化到 {count} 个三角形，停止生成更低的层级");
+            break;
+        }
+        info!("生成 LOD{level}: {count} 个三角形");
+        model.lods.push(meshes);
+        previous_count = count;
+    }
+}
+
+/// 模型中的三角形总数
+pub fn triangle_count(model: &Model) -> usize {
+    model.meshes.iter().map(|mesh| mesh.indices.len() / 3).sum()
+}
+
+/// 一次 Loop 细分：每个三角形分为 4 个，顶点按 Loop 规则平滑
+///
+/// 拓扑按位置焊接，UV 接缝两侧的顶点获得相同的位置，只有纹理坐标各自插值；
+/// 边界边和非流形边按边界规则处理（沿边界平滑，不向内收缩）。
+pub fn subdivide_loop(mesh: &Mesh) -> Mesh {
+    let faces = triangles(mesh);
+    let (welded, positions) = weld(&mesh.vertices);
+
+    // 焊接后的每条边及其对顶点
+    let mut edge_opposites: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for face in &faces {
+        for k in 0..3 {
+            let a = welded[face[k]];
+            let b = welded[face[(k + 1) % 3]];
+            let c = welded[face[(k + 2) % 3]];
+            if a != b {
+                edge_opposites
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push(c);
+            }
+        }
+    }
+
+    let mut neighbors = vec![Vec::new(); positions.len()];
+    let mut boundary_neighbors = vec![Vec::new(); positions.len()];
+    for (&(a, b), opposites) in &edge_opposites {
+        neighbors[a].push(b);
+        neighbors[b].push(a);
+        if opposites.len() != 2 {
+            boundary_neighbors[a].push(b);
+            boundary_neighbors[b].push(a);
+        }
+    }
+
+    // 固定求和顺序，保证结果可复现
+    neighbors.iter_mut().for_each(|list| list.sort_unstable());
+    boundary_neighbors
+        .iter_mut()
+        .for_each(|list| list.sort_unstable());
+
+    // 原有顶点的新位置
+    let moved: Vec<Point3<f32>> = positions
+        .iter()
+        .enumerate()
+        .map(|(w, position)| {
+            let boundary = &boundary_neighbors[w];
+            if !boundary.is_empty() {
+                // 多条边界交汇的角点保持不动
+                if boundary.len() == 2 {
+                    let sum = positions[boundary[0]].coords + positions[boundary[1]].coords;
+                    Point3::from(position.coords * 0.75 + sum * 0.125)
+                } else {
+                    *position
+                }
+            } else if neighbors[w].len() >= 3 {
+                let valence = neighbors[w].len();
+                let beta = if valence == 3 {
+                    3.0 / 16.0
+                } else {
+                    3.0 / (8.0 * valence as f32)
+                };
+                let sum: Vector3<f32> = neighbors[w].iter().map(|&n| positions[n].coords).sum();
+                Point3::from(position.coords * (1.0 - valence as f32 * beta) + sum * beta)
+            } else {
+                *position
+            }
+        })
+        .collect();
+
+    let edge_point = |a: usize, b: usize| -> Point3<f32> {
+        let midpoint = Point3::from((positions[a].coords + positions[b].coords) * 0.5);
+        match edge_opposites.get(&(a.min(b), a.max(b))) {
+            Some(opposites) if opposites.len() == 2 => Point3::from(
+                (positions[a].coords + positions[b].coords) * 0.375
+                    + (positions[opposites[0]].coords + positions[opposites[1]].coords) * 0.125,
+            ),
+            _ => midpoint,
+        }
+    };
+
+    let mut vertices: Vec<Vertex> = mesh
+        .vertices
+        .iter()
+        .zip(&welded)
+        .map(|(vertex, &w)| Vertex {
+            position: moved[w],
+            ..*vertex
+        })
+        .collect();
+
+    // 未焊接的边共享中点，接缝两侧各自生成一个（位置相同、UV 不同）
+    let mut midpoints: HashMap<(usize, usize), u32> = HashMap::new();
+    let mut midpoint_index = |a: usize, b: usize, vertices: &mut Vec<Vertex>| -> u32 {
+        *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+            let (va, vb) = (&mesh.vertices[a], &mesh.vertices[b]);
+            let normal = va.normal + vb.normal;
+            vertices.push(Vertex {
+                position: edge_point(welded[a], welded[b]),
+                normal: normal.try_normalize(1e-12).unwrap_or(va.normal),
+                texcoord: (va.texcoord + vb.texcoord) * 0.5,
+            });
+            (vertices.len() - 1) as u32
+        })
+    };
+
+    let mut indices = Vec::with_capacity(faces.len() * 12);
+    for &[v0, v1, v2] in &faces {
+        let m01 = midpoint_index(v0, v1, &mut vertices);
+        let m12 = midpoint_index(v1, v2, &mut vertices);
+        let m20 = midpoint_index(v2, v0, &mut vertices);
+        let [v0, v1, v2] = [v0 as u32, v1 as u32, v2 as u32];
+        indices.extend_from_slice(&[v0, m01, m20, v1, m12, m01, v2, m20, m12, m01, m12, m20]);
+    }
+
+    Mesh {
+        vertices,
+        indices,
+        skin_weights: Vec::new(),
+        morph_targets: Vec::new(),
+        ..mesh.clone()
+    }
+}
+
+/// 按平滑角重新计算顶点法线
+///
+/// 每个顶点周围的面按共享边分组，夹角不超过平滑角的相邻面属于同一平滑组，
+/// 组内按角度加权平均面法线；不同组的角点拆分为独立顶点，形成硬边。
+pub fn recompute_normals(mesh: &mut Mesh, smoothing_angle_deg: f32) {
+    let faces = triangles(mesh);
+    let (welded, positions) = weld(&mesh.vertices);
+    let cos_threshold = smoothing_angle_deg.clamp(0.0, 180.0).to_radians().cos();
+
+    let face_normals: Vec<Vector3<f32>> = faces
+        .iter()
+        .map(|face| {
+            let [p0, p1, p2] = face.map(|i| positions[welded[i]]);
+            (p1 - p0)
+                .cross(&(p2 - p0))
+                .try_normalize(1e-20)
+                .unwrap_or_else(Vector3::zeros)
+        })
+        .collect();
+
+    let mut corners_of: Vec<Vec<(usize, usize)>> = vec![Vec::new(); positions.len()];
+    for (face_index, face) in faces.iter().enumerate() {
+        for (corner, &vertex) in face.iter().enumerate() {
+            corners_of[welded[vertex]].push((face_index, corner));
+        }
+    }
+
+    let mut vertices: Vec<Vertex> = Vec::with_capacity(mesh.vertices.len());
+    let mut indices = vec![0u32; faces.len() * 3];
+    let mut remap: HashMap<(usize, usize), u32> = HashMap::new();
+
+    for (w, corners) in corners_of.iter().enumerate() {
+        // 并查集：共享一条经过该顶点的边、且夹角足够小的面合并为一组
+        let mut parent: Vec<usize> = (0..corners.len()).collect();
+        for i in 0..corners.len() {
+            for j in i + 1..corners.len() {
+                let (fi, fj) = (corners[i].0, corners[j].0);
+                let others_i = faces[fi].map(|v| welded[v]);
+                let shares_edge = faces[fj]
+                    .iter()
+                    .any(|&v| welded[v] != w && others_i.contains(&welded[v]));
+                if shares_edge && face_normals[fi].dot(&face_normals[fj]) >= cos_threshold {
+                    let (ri, rj) = (find_root(&mut parent, i), find_root(&mut parent, j));
+                    parent[ri] = rj;
+                }
+            }
+        }
+
+        let mut sums = vec![Vector3::zeros(); corners.len()];
+        for (i, &(face_index, corner)) in corners.iter().enumerate() {
+            let face = faces[face_index];
+            let p = positions[w];
+            let e1 = positions[welded[face[(corner + 1) % 3]]] - p;
+            let e2 = positions[welded[face[(corner + 2) % 3]]] - p;
+            let angle = if e1.norm_squared() > 0.0 && e2.norm_squared() > 0.0 {
+                e1.angle(&e2)
+            } else {
+                0.0
+            };
+            let root = find_root(&mut parent, i);
+            sums[root] += face_normals[face_index] * angle;
+        }
+
+        for (i, &(face_index, corner)) in corners.iter().enumerate() {
+            let root = find_root(&mut parent, i);
+            let source = faces[face_index][corner];
+            let new_index = *remap.entry((source, root)).or_insert_with(|| {
+                let original = &mesh.vertices[source];
+                let normal = sums[root]
+                    .try_normalize(1e-20)
+                    .or_else(|| face_normals[face_index].try_normalize(1e-20))
+                    .unwrap_or(original.normal);
+                vertices.push(Vertex {
+                    normal,
+                    ..*original
+                });
+                (vertices.len() - 1) as u32
+            });
+            indices[face_index * 3 + corner] = new_index;
+        }
+    }
+
+    mesh.vertices = vertices;
+    mesh.indices = indices;
+}
+
+/// 二次误差度量的边折叠简化，直到三角形数量不超过目标
+///
+/// 采用半边折叠：被移除的顶点并入相邻顶点，保留顶点的位置、法线和 UV 不变。
+/// 网格边界和 UV 接缝上的顶点不会被移除，以免产生裂缝和纹理错位。
+pub fn decimate(mesh: &Mesh, target_triangles: usize) -> Mesh {
+    let mut faces = triangles(mesh);
+    let (welded, positions) = weld(&mesh.vertices);
+    let vertex_count = positions.len();
+
+    let mut faces_of: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    let mut wedges: Vec<HashSet<usize>> = vec![HashSet::new(); vertex_count];
+    let mut edge_faces: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut quadrics = vec![Matrix4::<f64>::zeros(); vertex_count];
+    for (face_index, face) in faces.iter().enumerate() {
+        let w = face.map(|i| welded[i]);
+        for k in 0..3 {
+            faces_of[w[k]].push(face_index);
+            wedges[w[k]].insert(face[k]);
+            let (a, b) = (w[k], w[(k + 1) % 3]);
+            if a != b {
+                *edge_faces.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+        // 面所在平面的基本误差二次型（不按面积加权，细小特征同样被保留）
+        let [p0, p1, p2] = w.map(|i| positions[i].coords.cast::<f64>());
+        if let Some(normal) = (p1 - p0).cross(&(p2 - p0)).try_normalize(1e-30) {
+            let plane = Vector4::new(normal.x, normal.y, normal.z, -normal.dot(&p0));
+            let quadric = plane * plane.transpose();
+            for &i in &w {
+                quadrics[i] += quadric;
+            }
+        }
+    }
+
+    let mut locked: Vec<bool> = wedges.iter().map(|set| set.len() > 1).collect();
+    for (&(a, b), &count) in &edge_faces {
+        if count != 2 {
+            locked[a] = true;
+            locked[b] = true;
+        }
+    }
+
+    // 原始面法线：连续多次折叠的偏转会累积，翻转检查以原始朝向为准
+    let original_normals: Vec<Option<Vector3<f32>>> = faces
+        .iter()
+        .map(|face| {
+            let [p0, p1, p2] = face.map(|i| positions[welded[i]]);
+            (p1 - p0).cross(&(p2 - p0)).try_normalize(1e-20)
+        })
+        .collect();
+
+    let mut alive = vec![true; faces.len()];
+    let mut alive_count = faces.len();
+    let mut removed = vec![false; vertex_count];
+    let mut versions = vec![0u32; vertex_count];
+
+    let collapse_cost = |quadrics: &[Matrix4<f64>], from: usize, to: usize| -> f64 {
+        let p = positions[to].coords.cast::<f64>();
+        let h = Vector4::new(p.x, p.y, p.z, 1.0);
+        (h.transpose() * (quadrics[from] + quadrics[to]) * h)[0].max(0.0)
+    };
+
+    let mut heap = BinaryHeap::new();
+    for &(a, b) in edge_faces.keys() {
+        for (from, to) in [(a, b), (b, a)] {
+            if !locked[from] {
+                heap.push(Collapse {
+                    cost: collapse_cost(&quadrics, from, to),
+                    from,
+                    to,
+                    versions: (0, 0),
+                });
+            }
+        }
+    }
+
+    let neighbors_of = |faces_of: &[Vec<usize>], faces: &[[usize; 3]], alive: &[bool], w: usize| {
+        let mut set = HashSet::new();
+        for &f in faces_of[w].iter().filter(|&&f| alive[f]) {
+            for &v in &faces[f] {
+                if welded[v] != w {
+                    set.insert(welded[v]);
+                }
+            }
+        }
+        set
+    };
+
+    while alive_count > target_triangles {
+        let Some(collapse) = heap.pop() else {
+            break;
+        };
+        let Collapse { from, to, .. } = collapse;
+        if removed[from]
+            || removed[to]
+            || collapse.versions != (versions[from], versions[to])
+            || locked[from]
+        {
+            continue;
+        }
+
+        let from_faces: Vec<usize> = faces_of[from]
+            .iter()
+            .copied()
+            .filter(|&f| alive[f])
+            .collect();
+        let (shared, moving): (Vec<usize>, Vec<usize>) = from_faces
+            .iter()
+            .partition(|&&f| faces[f].iter().any(|&v| welded[v] == to));
+        if shared.is_empty() {
+            continue;
+        }
+
+        // 连接条件：两端点的公共邻居恰好是被删除三角形的第三个顶点，否则折叠会产生非流形
+        let from_neighbors = neighbors_of(&faces_of, &faces, &alive, from);
+        let to_neighbors = neighbors_of(&faces_of, &faces, &alive, to);
+        if from_neighbors.intersection(&to_neighbors).count() != shared.len() {
+            continue;
+        }
+
+        // 检查剩余三角形移动后是否翻转
+        let target_position = positions[to];
+        let flips = moving.iter().any(|&f| {
+            let corners = faces[f].map(|v| positions[welded[v]]);
+            let moved = faces[f].map(|v| {
+                if welded[v] == from {
+                    target_position
+                } else {
+                    positions[welded[v]]
+                }
+            });
+            let before = (corners[1] - corners[0]).cross(&(corners[2] - corners[0]));
+            let after = (moved[1] - moved[0]).cross(&(moved[2] - moved[0]));
+            match (before.try_normalize(1e-20), after.try_normalize(1e-20)) {
+                (Some(before), Some(after)) => {
+                    let reference = original_normals[f].unwrap_or(before);
+                    before.dot(&after) < MIN_COLLAPSE_NORMAL_COS
+                        || reference.dot(&after) < MIN_COLLAPSE_NORMAL_COS
+                }
+                _ => true,
+            }
+        });
+        if flips {
+            continue;
+        }
+
+        // 保留顶点在该侧使用的索引（from 不在接缝上，共享三角形中 to 的索引相同）
+        let to_index = faces[shared[0]]
+            .iter()
+            .copied()
+            .find(|&v| welded[v] == to)
+            .unwrap_or(to);
+        for &f in &shared {
+            alive[f] = false;
+            alive_count -= 1;
+        }
+        for &f in &moving {
+            for v in faces[f].iter_mut() {
+                if welded[*v] == from {
+                    *v = to_index;
+                }
+            }
+            faces_of[to].push(f);
+        }
+        removed[from] = true;
+        quadrics[to] = quadrics[to] + quadrics[from];
+        versions[to] += 1;
+
+        for neighbor in neighbors_of(&faces_of, &faces, &alive, to) {
+            for (a, b) in [(to, neighbor), (neighbor, to)] {
+                if !locked[a] {
+                    heap.push(Collapse {
+                        cost: collapse_cost(&quadrics, a, b),
+                        from: a,
+                        to: b,
+                        versions: (versions[a], versions[b]),
+                    });
+                }
+            }
+        }
+    }
+
+    // 压缩未使用的顶点
+    let mut remap: HashMap<usize, u32> = HashMap::new();
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(alive_count * 3);
+    for face in faces
+        .iter()
+        .zip(&alive)
+        .filter(|(_, a)| **a)
+        .map(|(f, _)| f)
+    {
+        for &v in face {
+            let index = *remap.entry(v).or_insert_with(|| {
+                vertices.push(mesh.vertices[v]);
+                (vertices.len() - 1) as u32
+            });
+            indices.push(index);
+        }
+    }
+
+    Mesh {
+        vertices,
+        indices,
+        skin_weights: Vec::new(),
+        morph_targets: Vec::new(),
+        ..mesh.clone()
+    }
+}
+
+/// 优先队列中的候选折叠，代价最小者优先
+struct Collapse {
+    cost: f64,
+    from: usize,
+    to: usize,
+    /// 入队时两端点的版本号，端点被修改后该候选失效
+    versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // 代价相同时按顶点编号排序，保证结果可复现
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.from.cmp(&self.from))
+            .then_with(|| other.to.cmp(&self.to))
+    }
+}
+
+/// 并查集查找（带路径压缩）
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// 网格的三角形（顶点索引）
+fn triangles(mesh: &Mesh) -> Vec<[usize; 3]> {
+    mesh.indices
+        .chunks_exact(3)
+        .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+        .collect()
+}
+
+/// 按位置焊接顶点，返回每个顶点的焊接编号和焊接后的位置
+fn weld(vertices: &[Vertex]) -> (Vec<usize>, Vec<Point3<f32>>) {
+    let mut ids: HashMap<[u32; 3], usize> = HashMap::new();
+    let mut positions = Vec::new();
+    let welded = vertices
+        .iter()
+        .map(|vertex| {
+            let key = [
+                vertex.position.x.to_bits(),
+                vertex.position.y.to_bits(),
+                vertex.position.z.to_bits(),
+            ];
+            *ids.entry(key).or_insert_with(|| {
+                positions.push(vertex.position);
+                positions.len() - 1
+            })
+        })
+        .collect();
+    (welded, positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::primitives::PrimitiveShape;
+    use nalgebra::Vector2;
+
+    fn unit_sphere(segments: usize, rings: usize) -> Mesh {
+        PrimitiveShape::Sphere {
+            radius: 1.0,
+            segments,
+            rings,
+        }
+        .generate("sphere")
+    }
+
+    /// 8 个共享顶点的立方体，法线全部为零
+    fn welded_cube() -> Mesh {
+        let vertices = (0..8)
+            .map(|i| Vertex {
+                position: Point3::new(
+                    (i & 1) as f32 - 0.5,
+                    ((i >> 1) & 1) as f32 - 0.5,
+                    ((i >> 2) & 1) as f32 - 0.5,
+                ),
+                normal: Vector3::zeros(),
+                texcoord: Vector2::zeros(),
+            })
+            .collect();
+        #[rustfmt::skip]
+        let indices = vec![
+            0, 2, 1, 1, 2, 3, // -Z
+            4, 5, 6, 5, 7, 6, // +Z
+            0, 1, 4, 1, 5, 4, // -Y
+            2, 6, 3, 3, 6, 7, // +Y
+            0, 4, 2, 2, 4, 6, // -X
+            1, 3, 5, 3, 7, 5, // +X
+        ];
+        Mesh {
+            vertices,
+            indices,
+            lines: Vec::new(),
+            points: Vec::new(),
+            material_id: 0,
+            name: "cube".to_string(),
+            skin_weights: Vec::new(),
+            morph_targets: Vec::new(),
+        }
+    }
+
+    /// 按位置焊接后每条边被几个三角形使用
+    fn welded_edge_counts(mesh: &Mesh) -> HashMap<(usize, usize), usize> {
+        let (welded, _) = weld(&mesh.vertices);
+        let mut counts = HashMap::new();
+        for face in triangles(mesh) {
+            for k in 0..3 {
+                let (a, b) = (welded[face[k]], welded[face[(k + 1) % 3]]);
+                if a != b {
+                    *counts.entry((a.min(b), a.max(b))).or_default() += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// 非退化三角形（排除球极处面积接近零的三角形）的重心和未归一化面法线
+    fn face_normals(mesh: &Mesh) -> Vec<(Point3<f32>, Vector3<f32>)> {
+        triangles(mesh)
+            .into_iter()
+            .filter_map(|face| {
+                let [p0, p1, p2] = face.map(|i| mesh.vertices[i].position);
+                let normal = (p1 - p0).cross(&(p2 - p0));
+                let centroid = Point3::from((p0.coords + p1.coords + p2.coords) / 3.0);
+                (normal.norm() > 1e-6).then_some((centroid, normal))
+            })
+            .collect()
+    }
+
+    fn position_key(position: &Point3<f32>) -> [u32; 3] {
+        [position.x, position.y, position.z].map(f32::to_bits)
+    }
+
+    #[test]
+    fn loop_step_quadruples_cube_and_stays_closed() {
+        let cube = PrimitiveShape::Box {
+            width: 1.0,
+            height: 1.0,
+            depth: 1.0,
+        }
+        .generate("cube");
+        assert!(welded_edge_counts(&cube).values().all(|&count| count == 2));
+
+        let subdivided = subdivide_loop(&cube);
+        assert_eq!(subdivided.indices.len(), cube.indices.len() * 4);
+        let edges = welded_edge_counts(&subdivided);
+        assert!(!edges.is_empty());
+        assert!(edges.values().all(|&count| count == 2), "细分后出现裂缝");
+    }
+
+    #[test]
+    fn decimate_reaches_target_without_flipping_faces() {
+        let sphere = unit_sphere(32, 16);
+        let target = sphere.indices.len() / 3 / 4;
+        // 球面三角形的朝向一致（相对球心同号）
+        let orientation =
+            |(centroid, normal): &(Point3<f32>, Vector3<f32>)| centroid.coords.dot(normal).signum();
+        let original = face_normals(&sphere);
+        let sign = orientation(&original[0]);
+        assert!(original.iter().all(|face| orientation(face) == sign));
+
+        let decimated = decimate(&sphere, target);
+        assert!(decimated.indices.len() / 3 <= target);
+        // 不只是符号相同：面法线与径向保持足够的夹角余量，没有折成竖直的三角形
+        for (centroid, normal) in face_normals(&decimated) {
+            let facing = centroid.coords.normalize().dot(&normal.normalize()) * sign;
+            assert!(facing > 0.1, "简化后出现翻转的三角形: {facing}");
+        }
+    }
+
+    #[test]
+    fn decimate_keeps_boundary_vertices_of_open_mesh() {
+        // 切掉北极附近的三角形，留下一圈边界
+        let mut open = unit_sphere(24, 12);
+        let faces = triangles(&open);
+        open.indices = faces
+            .into_iter()
+            .filter(|face| face.iter().all(|&i| open.vertices[i].position.y < 0.6))
+            .flatten()
+            .map(|i| i as u32)
+            .collect();
+
+        let (_, positions) = weld(&open.vertices);
+        let boundary: HashSet<[u32; 3]> = welded_edge_counts(&open)
+            .into_iter()
+            .filter(|&(_, count)| count == 1)
+            .flat_map(|((a, b), _)| [a, b])
+            .map(|w| position_key(&positions[w]))
+            .collect();
+        assert!(!boundary.is_empty());
+
+        let decimated = decimate(&open, open.indices.len() / 3 / 4);
+        assert!(decimated.indices.len() < open.indices.len());
+        let remaining: HashSet<[u32; 3]> = decimated
+            .indices
+            .iter()
+            .map(|&i| position_key(&decimated.vertices[i as usize].position))
+            .collect();
+        assert!(boundary.is_subset(&remaining), "边界顶点被折叠");
+    }
+
+    #[test]
+    fn recompute_normals_splits_cube_corners() {
+        let mut cube = welded_cube();
+        recompute_normals(&mut cube, 60.0);
+        assert_eq!(cube.indices.len(), 36);
+
+        let mut normals_at: HashMap<[u32; 3], Vec<Vector3<f32>>> = HashMap::new();
+        for vertex in &cube.vertices {
+            normals_at
+                .entry(position_key(&vertex.position))
+                .or_default()
+                .push(vertex.normal);
+        }
+        assert_eq!(normals_at.len(), 8);
+        for normals in normals_at.values() {
+            assert_eq!(normals.len(), 3);
+            for (i, a) in normals.iter().enumerate() {
+                // 每个法线都与某个坐标轴对齐，且互不相同
+                assert!((a.abs().max() - 1.0).abs() < 1e-5);
+                for b in &normals[i + 1..] {
+                    assert!(a.dot(b).abs() < 1e-5);
+                }
+            }
+        }
+
+        // 平滑角大于 90° 时每个角只有一个法线
+        let mut smooth = welded_cube();
+        recompute_normals(&mut smooth, 120.0);
+        assert_eq!(smooth.vertices.len(), 8);
+    }
+
+    #[test]
+    fn generate_lods_reduces_each_level() {
+        let mut model = Model {
+            meshes: vec![unit_sphere(32, 16)],
+            materials: Vec::new(),
+            name: "sphere".to_string(),
+            lods: Vec::new(),
+            skeleton: None,
+            normalization: (Vector3::zeros(), 1.0),
+        };
+        generate_lods(&mut model, 2, 0.5);
+        assert_eq!(model.lods.len(), 2);
+        let mut previous = triangle_count(&model);
+        for level in &model.lods {
+            let count: usize = level.iter().map(|mesh| mesh.indices.len() / 3).sum();
+            assert!(count <= previous / 2);
+            previous = count;
+        }
+    }
+}
@@ -1,4 +1,9 @@
 pub mod camera;
 pub mod culling;
+pub mod edges;
 pub mod interpolation;
+pub mod mesh_ops;
+pub mod morph;
+pub mod primitives;
+pub mod skeleton;
 pub mod transform;
@@ -0,0 +1,82 @@
+use crate::material_system::materials::{Mesh, Vertex};
+use nalgebra::Vector3;
+
+/// 配置中的一个变形目标：拓扑与主模型相同、形状不同的 OBJ 文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct MorphTargetSource {
+    pub name: String,
+    pub obj: String,
+    /// 当前权重，0 为原始形状，1 为完全变形到目标
+    pub weight: f32,
+}
+
+/// 网格上的一个变形目标，按顶点存储相对原始形状的偏移
+#[derive(Debug, Clone)]
+pub struct MorphTarget {
+    pub name: String,
+    pub position_deltas: Vec<Vector3<f32>>,
+    pub normal_deltas: Vec<Vector3<f32>>,
+}
+
+impl MorphTarget {
+    /// 由原始网格和目标网格逐顶点相减得到，两者的顶点必须一一对应
+    pub fn from_meshes(name: &str, base: &Mesh, target: &Mesh) -> Result<Self, String> {
+        if base.vertices.len() != target.vertices.len() {
+            return Err(format!(
+                "网格 '{}' 有 {} 个顶点，变形目标 '{name}' 中对应网格有 {} 个",
+                base.name,
+                base.vertices.len(),
+                target.vertices.len()
+            ));
+        }
+        let (position_deltas, normal_deltas) = base
+            .vertices
+            .iter()
+            .zip(&target.vertices)
+            .map(|(from, to)| (to.position - from.position, to.normal - from.normal))
+            .unzip();
+        Ok(Self {
+            name: name.to_string(),
+            position_deltas,
+            normal_deltas,
+        })
+    }
+}
+
+/// 按权重叠加变形目标的偏移，法线叠加后重新归一化
+///
+/// `weights` 中没有出现或权重为 0 的目标不参与计算。
+pub fn morph_vertices(
+    vertices: &[Vertex],
+    targets: &[MorphTarget],
+    weights: &[(String, f32)],
+) -> Vec<Vertex> {
+    let active: Vec<(&MorphTarget, f32)> = targets
+        .iter()
+        .filter_map(|target| {
+            weights
+                .iter()
+                .find(|(name, _)| *name == target.name)
+                .map(|&(_, weight)| (target, weight))
+        })
+        .filter(|&(_, weight)| weight != 0.0)
+        .collect();
+
+    vertices
+        .iter()
+        .enumerate()
+        .map(|(index, vertex)| {
+            let mut position = vertex.position;
+            let mut normal = vertex.normal;
+            for &(target, weight) in &active {
+                position += target.position_deltas[index] * weight;
+                normal += target.normal_deltas[index] * weight;
+            }
+            Vertex {
+                position,
+                normal: normal.try_normalize(f32::EPSILON).unwrap_or(vertex.normal),
+                texcoord: vertex.texcoord,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,333 @@
+use crate::geometry::transform::{TransformFactory, transform_point};
+use crate::io::render_settings::parse_vec3;
+use crate::material_system::materials::{Mesh, Vertex};
+use nalgebra::{Matrix3, Matrix4, Point3, Vector2, Vector3};
+use std::f32::consts::{PI, TAU};
+
+/// 参数化几何体及其参数
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrimitiveShape {
+    /// UV 球，`segments` 为经线分段数，`rings` 为纬线分段数
+    Sphere {
+        radius: f32,
+        segments: usize,
+        rings: usize,
+    },
+    /// 长方体，尺寸分别沿 X、Y、Z 轴
+    Box { width: f32, height: f32, depth: f32 },
+    /// 沿 Y 轴的圆柱，`capped` 为 false 时没有顶面和底面
+    Cylinder {
+        radius: f32,
+        height: f32,
+        segments: usize,
+        capped: bool,
+    },
+    /// 位于 XZ 平面的圆环，`segments` 沿主圆分段，`sides` 沿截面分段
+    Torus {
+        major_radius: f32,
+        minor_radius: f32,
+        segments: usize,
+        sides: usize,
+    },
+    /// 朝向 +Y 的平面
+    Plane {
+        width: f32,
+        depth: f32,
+        subdivisions: usize,
+    },
+}
+
+impl PrimitiveShape {
+    /// 所有支持的图元类型名
+    pub const KINDS: [&'static str; 5] = ["sphere", "box", "cylinder", "torus", "plane"];
+
+    /// 按类型名创建默认参数的图元
+    pub fn with_defaults(kind: &str) -> Result<Self, String> {
+        match kind {
+            "sphere" => Ok(Self::Sphere {
+                radius: 1.0,
+                segments: 64,
+                rings: 32,
+            }),
+            "box" => Ok(Self::Box {
+                width: 1.0,
+                height: 1.0,
+                depth: 1.0,
+            }),
+            "cylinder" => Ok(Self::Cylinder {
+                radius: 0.5,
+                height: 1.0,
+                segments: 48,
+                capped: true,
+            }),
+            "torus" => Ok(Self::Torus {
+                major_radius: 1.0,
+                minor_radius: 0.3,
+                segments: 64,
+                sides: 32,
+            }),
+            "plane" => Ok(Self::Plane {
+                width: 2.0,
+                depth: 2.0,
+                subdivisions: 1,
+            }),
+            _ => Err(format!("未知的图元类型: {kind}")),
+        }
+    }
+
+    /// 配置中使用的类型名
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Sphere { .. } => "sphere",
+            Self::Box { .. } => "box",
+            Self::Cylinder { .. } => "cylinder",
+            Self::Torus { .. } => "torus",
+            Self::Plane { .. } => "plane",
+        }
+    }
+
+    /// 生成带法线和 UV 的三角网格（位于图元自身的局部空间）
+    pub fn generate(&self, name: &str) -> Mesh {
+        let mut builder = MeshBuilder::default();
+        match *self {
+            Self::Sphere {
+                radius,
+                segments,
+                rings,
+            } => {
+                // 接缝位于 -Z 一侧，u = 0.5 正对 +Z
+                builder.parametric_grid(segments.max(3), rings.max(2), |u, v| {
+                    let longitude = TAU * u - PI;
+                    let latitude = PI * (v - 0.5);
+                    let normal = Vector3::new(
+                        latitude.cos() * longitude.sin(),
+                        latitude.sin(),
+                        latitude.cos() * longitude.cos(),
+                    );
+                    (Point3::from(normal * radius), normal)
+                });
+            }
+            Self::Box {
+                width,
+                height,
+                depth,
+            } => {
+                let size = Vector3::new(width, height, depth);
+                // (法线, u 方向, v 方向)，u × v = 法线，从外侧看纹理不镜像
+                let faces = [
+                    (Vector3::z(), Vector3::x(), Vector3::y()),
+                    (-Vector3::z(), -Vector3::x(), Vector3::y()),
+                    (Vector3::x(), -Vector3::z(), Vector3::y()),
+                    (-Vector3::x(), Vector3::z(), Vector3::y()),
+                    (Vector3::y(), Vector3::x(), -Vector3::z()),
+                    (-Vector3::y(), Vector3::x(), Vector3::z()),
+                ];
+                for (normal, right, up) in faces {
+                    builder.parametric_grid(1, 1, |u, v| {
+                        let unit = normal * 0.5 + right * (u - 0.5) + up * (v - 0.5);
+                        (Point3::from(unit.component_mul(&size)), normal)
+                    });
+                }
+            }
+            Self::Cylinder {
+                radius,
+                height,
+                segments,
+                capped,
+            } => {
+                let segments = segments.max(3);
+                let half_height = height * 0.5;
+                builder.parametric_grid(segments, 1, |u, v| {
+                    let angle = TAU * u - PI;
+                    let normal = Vector3::new(angle.sin(), 0.0, angle.cos());
+                    let position =
+                        Point3::new(normal.x * radius, (v - 0.5) * height, normal.z * radius);
+                    (position, normal)
+                });
+                if capped {
+                    builder.disc(segments, radius, half_height, true);
+                    builder.disc(segments, radius, -half_height, false);
+                }
+            }
+            Self::Torus {
+                major_radius,
+                minor_radius,
+                segments,
+                sides,
+            } => {
+                // 截面的接缝位于圆环内侧
+                builder.parametric_grid(segments.max(3), sides.max(3), |u, v| {
+                    let around = TAU * u - PI;
+                    let section = TAU * v - PI;
+                    let normal = Vector3::new(
+                        section.cos() * around.sin(),
+                        section.sin(),
+                        section.cos() * around.cos(),
+                    );
+                    let center = Vector3::new(around.sin(), 0.0, around.cos()) * major_radius;
+                    (Point3::from(center + normal * minor_radius), normal)
+                });
+            }
+            Self::Plane {
+                width,
+                depth,
+                subdivisions,
+            } => {
+                let subdivisions = subdivisions.max(1);
+                builder.parametric_grid(subdivisions, subdivisions, |u, v| {
+                    let position = Point3::new((u - 0.5) * width, 0.0, (0.5 - v) * depth);
+                    (position, Vector3::y())
+                });
+            }
+        }
+
+        Mesh {
+            vertices: builder.vertices,
+            indices: builder.indices,
+            lines: Vec::new(),
+            points: Vec::new(),
+            material_id: 0,
+            name: name.to_string(),
+            skin_weights: Vec::new(),
+            morph_targets: Vec::new(),
+        }
+    }
+}
+
+/// 配置中 `[[object]]` 引用的一个图元实例
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrimitiveObject {
+    /// 网格名和材质名，可作为 `[[material_override]]` 的 target
+    pub name: String,
+    pub shape: PrimitiveShape,
+    /// 相对于其他图元的位置，格式为"x,y,z"
+    pub position: String,
+    /// 旋转角度（度），格式为"x,y,z"
+    pub rotation: String,
+    /// 缩放，格式为"x,y,z"
+    pub scale: String,
+}
+
+impl PrimitiveObject {
+    pub fn new(name: String, shape: PrimitiveShape) -> Self {
+        Self {
+            name,
+            shape,
+            position: "0,0,0".to_string(),
+            rotation: "0,0,0".to_string(),
+            scale: "1,1,1".to_string(),
+        }
+    }
+
+    /// 生成网格并烘焙自身的变换（顺序与场景对象一致：T * Rz * Ry * Rx * S）
+    pub fn build_mesh(&self) -> Result<Mesh, String> {
+        let position = parse_vec3(&self.position)
+            .map_err(|e| format!("图元 '{}' 位置无效: {e}", self.name))?;
+        let rotation = parse_vec3(&self.rotation)
+            .map_err(|e| format!("图元 '{}' 旋转无效: {e}", self.name))?;
+        let scale =
+            parse_vec3(&self.scale).map_err(|e| format!("图元 '{}' 缩放无效: {e}", self.name))?;
+
+        let transform: Matrix4<f32> = TransformFactory::translation(&position)
+            * TransformFactory::rotation_z(rotation.z.to_radians())
+            * TransformFactory::rotation_y(rotation.y.to_radians())
+            * TransformFactory::rotation_x(rotation.x.to_radians())
+            * TransformFactory::scaling_nonuniform(&scale);
+        let normal_matrix: Matrix3<f32> = transform
+            .fixed_view::<3, 3>(0, 0)
+            .try_inverse()
+            .ok_or_else(|| format!("图元 '{}' 的缩放不能为 0", self.name))?
+            .transpose();
+        // 负缩放会翻转三角形朝向
+        let mirrored = transform.fixed_view::<3, 3>(0, 0).determinant() < 0.0;
+
+        let mut mesh = self.shape.generate(&self.name);
+        for vertex in &mut mesh.vertices {
+            vertex.position = transform_point(&vertex.position, &transform);
+            vertex.normal = (normal_matrix * vertex.normal).normalize();
+        }
+        if mirrored {
+            for triangle in mesh.indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+        Ok(mesh)
+    }
+}
+
+/// 逐块累积顶点和索引
+#[derive(Default)]
+struct MeshBuilder {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    /// 按 (u, v) ∈ [0, 1]² 采样参数曲面，UV 直接取参数值
+    ///
+    /// 曲面需满足 ∂p/∂u × ∂p/∂v 指向外侧，此时三角形从外侧看为逆时针。
+    fn parametric_grid(
+        &mut self,
+        columns: usize,
+        rows: usize,
+        surface: impl Fn(f32, f32) -> (Point3<f32>, Vector3<f32>),
+    ) {
+        let base = self.vertices.len() as u32;
+        for row in 0..=rows {
+            let v = row as f32 / rows as f32;
+            for column in 0..=columns {
+                let u = column as f32 / columns as f32;
+                let (position, normal) = surface(u, v);
+                self.vertices.push(Vertex {
+                    position,
+                    normal,
+                    texcoord: Vector2::new(u, v),
+                });
+            }
+        }
+
+        let stride = (columns + 1) as u32;
+        for row in 0..rows as u32 {
+            for column in 0..columns as u32 {
+                let a = base + row * stride + column;
+                let b = a + 1;
+                let c = a + stride;
+                let d = c + 1;
+                self.indices.extend_from_slice(&[a, b, c, b, d, c]);
+            }
+        }
+    }
+
+    /// 圆柱的顶面或底面，UV 为圆盘在单位正方形内的投影
+    fn disc(&mut self, segments: usize, radius: f32, y: f32, facing_up: bool) {
+        let normal = if facing_up {
+            Vector3::y()
+        } else {
+            -Vector3::y()
+        };
+        let center = self.vertices.len() as u32;
+        self.vertices.push(Vertex {
+            position: Point3::new(0.0, y, 0.0),
+            normal,
+            texcoord: Vector2::new(0.5, 0.5),
+        });
+        for segment in 0..=segments {
+            let angle = TAU * segment as f32 / segments as f32 - PI;
+            let (sin, cos) = angle.sin_cos();
+            self.vertices.push(Vertex {
+                position: Point3::new(sin * radius, y, cos * radius),
+                normal,
+                texcoord: Vector2::new(0.5 + 0.5 * sin, 0.5 - 0.5 * cos * normal.y),
+            });
+        }
+        for segment in 0..segments as u32 {
+            let current = center + 1 + segment;
+            let next = current + 1;
+            if facing_up {
+                self.indices.extend_from_slice(&[center, current, next]);
+            } else {
+                self.indices.extend_from_slice(&[center, next, current]);
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::material_system::materials::{Mesh, Vertex};
+use nalgebra::{Matrix4, Point3, Translation3, UnitQuaternion, Vector3};
+
+/// 每个顶点最多受影响的关节数
+pub const MAX_JOINT_INFLUENCES: usize = 4;
+
+/// 单个顶点的蒙皮权重，未使用的槽位权重为 0
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SkinWeights {
+    pub joints: [u16; MAX_JOINT_INFLUENCES],
+    pub weights: [f32; MAX_JOINT_INFLUENCES],
+}
+
+/// 关节的动画通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JointChannel {
+    XPosition,
+    YPosition,
+    ZPosition,
+    XRotation,
+    YRotation,
+    ZRotation,
+}
+
+#[derive(Debug, Clone)]
+pub struct Joint {
+    pub name: String,
+    /// 父关节索引，父关节总是排在子关节之前
+    pub parent: Option<usize>,
+    /// 静止姿态下相对父关节的偏移
+    pub offset: Vector3<f32>,
+    /// 动画数据中的通道，按帧数据中的顺序排列
+    pub channels: Vec<JointChannel>,
+    /// 末端关节的骨骼终点（相对本关节），用于自动绑定
+    pub end_site: Option<Vector3<f32>>,
+}
+
+/// 按固定帧间隔采样的动画片段
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    /// 帧间隔（秒）
+    pub frame_time: f32,
+    /// 每帧所有关节通道的值，顺序同关节及其 `channels`
+    pub frames: Vec<Vec<f32>>,
+}
+
+impl AnimationClip {
+    pub fn duration(&self) -> f32 {
+        self.frame_time * self.frames.len().saturating_sub(1) as f32
+    }
+}
+
+/// 骨架与动画片段
+///
+/// 关节位置位于骨架空间，`to_model` 把骨架空间映射到模型空间（绑定时的缩放、平移以及模型归一化）。
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    pub joints: Vec<Joint>,
+    /// 每个关节静止姿态全局矩阵的逆
+    pub inverse_bind: Vec<Matrix4<f32>>,
+    pub to_model: Matrix4<f32>,
+    pub clip: Option<AnimationClip>,
+}
+
+/// 单个关节的局部姿态
+#[derive(Clone, Copy)]
+struct LocalPose {
+    translation: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+}
+
+impl Skeleton {
+    pub fn new(joints: Vec<Joint>, clip: Option<AnimationClip>) -> Self {
+        let rest: Vec<LocalPose> = joints
+            .iter()
+            .map(|joint| LocalPose {
+                translation: joint.offset,
+                rotation: UnitQuaternion::identity(),
+            })
+            .collect();
+        let mut skeleton = Self {
+            joints,
+            inverse_bind: Vec::new(),
+            to_model: Matrix4::identity(),
+            clip,
+        };
+        skeleton.inverse_bind = skeleton
+            .global_matrices(&rest)
+            .iter()
+            .map(|matrix| matrix.try_inverse().unwrap_or_else(Matrix4::identity))
+            .collect();
+        skeleton
+    }
+
+    /// 播放速度为 1 时动画片段的时长（秒）
+    pub fn clip_duration(&self) -> f32 {
+        self.clip.as_ref().map_or(0.0, AnimationClip::duration)
+    }
+
+    fn global_matrices(&self, poses: &[LocalPose]) -> Vec<Matrix4<f32>> {
+        let mut globals: Vec<Matrix4<f32>> = Vec::with_capacity(self.joints.len());
+        for (joint, pose) in self.joints.iter().zip(poses) {
+            let local = Translation3::from(pose.translation).to_homogeneous()
+                * pose.rotation.to_homogeneous();
+            globals.push(match joint.parent {
+                Some(parent) => globals[parent] * local,
+                None => local,
+            });
+        }
+        globals
+    }
+
+    /// 第 `frame` 帧的局部姿态：平移通道叠加在偏移上，旋转按通道顺序相乘
+    fn frame_poses(&self, frame: &[f32]) -> Vec<LocalPose> {
+        let mut values = frame.iter().copied();
+        self.joints
+            .iter()
+            .map(|joint| {
+                let mut translation = joint.offset;
+                let mut rotation = UnitQuaternion::identity();
+                for channel in &joint.channels {
+                    let value = values.next().unwrap_or(0.0);
+                    match channel {
+                        JointChannel::XPosition => translation.x += value,
+                        JointChannel::YPosition => translation.y += value,
+                        JointChannel::ZPosition => translation.z += value,
+                        JointChannel::XRotation => {
+                            rotation *= UnitQuaternion::from_axis_angle(
+                                &Vector3::x_axis(),
+                                value.to_radians(),
+                            );
+                        }
+                        JointChannel::YRotation => {
+                            rotation *= UnitQuaternion::from_axis_angle(
+                                &Vector3::y_axis(),
+                                value.to_radians(),
+                            );
+                        }
+                        JointChannel::ZRotation => {
+                            rotation *= UnitQuaternion::from_axis_angle(
+                                &Vector3::z_axis(),
+                                value.to_radians(),
+                            );
+                        }
+                    }
+                }
+                LocalPose {
+                    translation,
+                    rotation,
+                }
+            })
+            .collect()
+    }
+
+    /// 第 `time` 秒的蒙皮矩阵（模型空间），相邻帧之间平移线性插值、旋转球面插值
+    ///
+    /// 没有动画片段时返回单位矩阵，即静止姿态。
+    pub fn skin_matrices(&self, time: f32, looping: bool) -> Vec<Matrix4<f32>> {
+        let Some(clip) = self.clip.as_ref().filter(|clip| !clip.frames.is_empty()) else {
+            return vec![Matrix4::identity(); self.joints.len()];
+        };
+
+        let duration = clip.duration();
+        let time = if looping && duration > 0.0 {
+            time.rem_euclid(duration)
+        } else {
+            time.clamp(0.0, duration)
+        };
+        let position = if clip.frame_time > 0.0 {
+            time / clip.frame_time
+        } else {
+            0.0
+        };
+        let current = (position.floor() as usize).min(clip.frames.len() - 1);
+        let next = (current + 1).min(clip.frames.len() - 1);
+        let t = position - current as f32;
+
+        let poses: Vec<LocalPose> = self
+            .frame_poses(&clip.frames[current])
+            .into_iter()
+            .zip(self.frame_poses(&clip.frames[next]))
+            .map(|(from, to)| LocalPose {
+                translation: from.translation.lerp(&to.translation, t),
+                rotation: from.rotation.slerp(&to.rotation, t),
+            })
+            .collect();
+
+        let from_model = self
+            .to_model
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+        self.global_matrices(&poses)
+            .iter()
+            .zip(&self.inverse_bind)
+            .map(|(global, inverse_bind)| self.to_model * global * inverse_bind * from_model)
+            .collect()
+    }
+
+    /// 静止姿态下的骨骼线段（模型空间）及其所属关节
+    fn rest_bones(&self) -> Vec<(usize, Point3<f32>, Point3<f32>)> {
+        let joint_position = |index: usize| {
+            let global = self.inverse_bind[index]
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity);
+            (
+                global,
+                self.to_model
+                    .transform_point(&Point3::from(global.column(3).xyz())),
+            )
+        };
+
+        let mut bones = Vec::new();
+        for (index, joint) in self.joints.iter().enumerate() {
+            let (global, position) = joint_position(index);
+            // 连接父关节的骨骼随父关节运动
+            if let Some(parent) = joint.parent {
+                bones.push((parent, joint_position(parent).1, position));
+            }
+            if let Some(end_site) = joint.end_site {
+                let end = self
+                    .to_model
+                    .transform_point(&global.transform_point(&Point3::from(end_site)));
+                bones.push((index, position, end));
+            }
+        }
+        // 只有一个关节时退化为一个点
+        if bones.is_empty() && !self.joints.is_empty() {
+            let position = joint_position(0).1;
+            bones.push((0, position, position));
+        }
+        bones
+    }
+
+    /// 按顶点到骨骼线段的距离自动计算蒙皮权重
+    ///
+    /// 取最近的若干根骨骼，权重与距离的四次方成反比，距离越近的骨骼影响越大。
+    pub fn bind_mesh(&self, mesh: &mut Mesh) {
+        let bones = self.rest_bones();
+        mesh.skin_weights = mesh
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let mut nearest: Vec<(f32, usize)> = Vec::with_capacity(bones.len());
+                for &(joint, start, end) in &bones {
+                    let distance = point_segment_distance(&vertex.position, &start, &end);
+                    // 同一关节可能拥有多根骨骼，只保留最近的一根
+                    match nearest.iter_mut().find(|(_, j)| *j == joint) {
+                        Some(entry) => entry.0 = entry.0.min(distance),
+                        None => nearest.push((distance, joint)),
+                    }
+                }
+                nearest.sort_by(|a, b| a.0.total_cmp(&b.0));
+                nearest.truncate(MAX_JOINT_INFLUENCES);
+
+                let mut skin = SkinWeights::default();
+                let closest = nearest.first().map_or(0.0, |n| n.0).max(1e-4);
+                for (slot, (distance, joint)) in nearest.iter().enumerate() {
+                    skin.joints[slot] = *joint as u16;
+                    skin.weights[slot] = (closest / distance.max(1e-4)).powi(4);
+                }
+                let total: f32 = skin.weights.iter().sum();
+                if total > 0.0 {
+                    skin.weights.iter_mut().for_each(|w| *w /= total);
+                }
+                skin
+            })
+            .collect();
+    }
+}
+
+fn point_segment_distance(point: &Point3<f32>, start: &Point3<f32>, end: &Point3<f32>) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.norm_squared();
+    let t = if length_squared > f32::EPSILON {
+        ((point - start).dot(&segment) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (point - (start + segment * t)).norm()
+}
+
+/// 线性混合蒙皮：按权重混合关节矩阵后变换位置和法线
+pub fn skin_vertices(
+    vertices: &[Vertex],
+    skin_weights: &[SkinWeights],
+    skin_matrices: &[Matrix4<f32>],
+) -> Vec<Vertex> {
+    vertices
+        .iter()
+        .zip(skin_weights)
+        .map(|(vertex, skin)| {
+            let mut blended = Matrix4::zeros();
+            for (&joint, &weight) in skin.joints.iter().zip(&skin.weights) {
+                if weight > 0.0
+                    && let Some(matrix) = skin_matrices.get(joint as usize)
+                {
+                    blended += matrix * weight;
+                }
+            }
+            if blended == Matrix4::zeros() {
+                return *vertex;
+            }
+            let normal = blended.fixed_view::<3, 3>(0, 0) * vertex.normal;
+            Vertex {
+                position: blended.transform_point(&vertex.position),
+                normal: normal.try_normalize(f32::EPSILON).unwrap_or(vertex.normal),
+                texcoord: vertex.texcoord,
+            }
+        })
+        .collect()
+}
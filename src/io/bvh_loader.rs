@@ -0,0 +1,153 @@
+use crate::geometry::skeleton::{AnimationClip, Joint, JointChannel, Skeleton};
+use log::info;
+use nalgebra::Vector3;
+use std::fs;
+
+/// 加载 BVH 动作捕捉文件：HIERARCHY 部分为骨架，MOTION 部分为动画片段
+pub fn load_bvh(path: &str) -> Result<Skeleton, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("读取 BVH 文件 '{path}' 失败: {e}"))?;
+    let skeleton = parse_bvh(&content).map_err(|e| format!("解析 BVH 文件 '{path}' 失败: {e}"))?;
+    info!(
+        "BVH '{}': {} 个关节, {} 帧, 时长 {:.2} 秒",
+        path,
+        skeleton.joints.len(),
+        skeleton.clip.as_ref().map_or(0, |clip| clip.frames.len()),
+        skeleton.clip_duration()
+    );
+    Ok(skeleton)
+}
+
+pub fn parse_bvh(content: &str) -> Result<Skeleton, String> {
+    let mut tokens = content.split_whitespace();
+    let mut next = |expected: &str| -> Result<String, String> {
+        tokens
+            .next()
+            .map(str::to_string)
+            .ok_or_else(|| format!("文件意外结束，需要 {expected}"))
+    };
+    let parse_number = |text: &str| {
+        text.parse::<f32>()
+            .map_err(|e| format!("无效数字 '{text}': {e}"))
+    };
+
+    if next("HIERARCHY")? != "HIERARCHY" {
+        return Err("缺少 HIERARCHY".to_string());
+    }
+
+    let mut joints: Vec<Joint> = Vec::new();
+    // 当前所在的关节栈，End Site 用 None 表示
+    let mut stack: Vec<Option<usize>> = Vec::new();
+    let mut pending: Option<Option<usize>> = None;
+    loop {
+        let token = next("MOTION")?;
+        match token.as_str() {
+            "ROOT" | "JOINT" => {
+                let name = next("关节名")?;
+                let parent = stack.last().copied().flatten();
+                if token == "JOINT" && parent.is_none() {
+                    return Err(format!("关节 '{name}' 不在任何关节内"));
+                }
+                joints.push(Joint {
+                    name,
+                    parent,
+                    offset: Vector3::zeros(),
+                    channels: Vec::new(),
+                    end_site: None,
+                });
+                pending = Some(Some(joints.len() - 1));
+            }
+            "End" => {
+                next("Site")?;
+                if stack.last().copied().flatten().is_none() {
+                    return Err("End Site 不在任何关节内".to_string());
+                }
+                pending = Some(None);
+            }
+            "{" => {
+                stack.push(pending.take().ok_or("多余的 '{'")?);
+            }
+            "}" => {
+                stack.pop().ok_or("多余的 '}'")?;
+            }
+            "OFFSET" => {
+                let mut offset = Vector3::zeros();
+                for axis in 0..3 {
+                    offset[axis] = parse_number(&next("OFFSET 数值")?)?;
+                }
+                match stack.last().ok_or("OFFSET 不在任何关节内")? {
+                    Some(joint) => joints[*joint].offset = offset,
+                    None => {
+                        // End Site 属于外层关节
+                        let owner = stack
+                            .iter()
+                            .rev()
+                            .find_map(|entry| *entry)
+                            .ok_or("End Site 不在任何关节内")?;
+                        joints[owner].end_site = Some(offset);
+                    }
+                }
+            }
+            "CHANNELS" => {
+                let joint = stack
+                    .last()
+                    .copied()
+                    .flatten()
+                    .ok_or("CHANNELS 不在任何关节内")?;
+                let count: usize = next("通道数")?
+                    .parse()
+                    .map_err(|e| format!("无效的通道数: {e}"))?;
+                for _ in 0..count {
+                    let channel = match next("通道名")?.as_str() {
+                        "Xposition" => JointChannel::XPosition,
+                        "Yposition" => JointChannel::YPosition,
+                        "Zposition" => JointChannel::ZPosition,
+                        "Xrotation" => JointChannel::XRotation,
+                        "Yrotation" => JointChannel::YRotation,
+                        "Zrotation" => JointChannel::ZRotation,
+                        other => return Err(format!("未知的通道: {other}")),
+                    };
+                    joints[joint].channels.push(channel);
+                }
+            }
+            "MOTION" => break,
+            other => return Err(format!("无法识别的内容: {other}")),
+        }
+    }
+    if !stack.is_empty() {
+        return Err("HIERARCHY 中的括号不匹配".to_string());
+    }
+    if joints.is_empty() {
+        return Err("HIERARCHY 中没有关节".to_string());
+    }
+
+    // MOTION: "Frames: N" 与 "Frame Time: t"
+    if next("Frames:")? != "Frames:" {
+        return Err("缺少 Frames:".to_string());
+    }
+    let frame_count: usize = next("帧数")?
+        .parse()
+        .map_err(|e| format!("无效的帧数: {e}"))?;
+    if next("Frame")? != "Frame" || next("Time:")? != "Time:" {
+        return Err("缺少 Frame Time:".to_string());
+    }
+    let frame_time = parse_number(&next("帧间隔")?)?;
+
+    let channel_count: usize = joints.iter().map(|joint| joint.channels.len()).sum();
+    // 帧数来自文件头，不据此预分配；没有通道时也没有可读的帧数据
+    let frame_count = if channel_count == 0 { 0 } else { frame_count };
+    let mut frames = Vec::new();
+    for frame in 0..frame_count {
+        let values = (0..channel_count)
+            .map(|_| {
+                next("通道数值")
+                    .map_err(|_| format!("第 {frame} 帧数据不完整"))
+                    .and_then(|text| parse_number(&text))
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+        frames.push(values);
+    }
+
+    let clip = (!frames.is_empty()).then_some(AnimationClip { frame_time, frames });
+    Ok(Skeleton::new(joints, clip))
+}
@@ -1,7 +1,15 @@
+use crate::geometry::morph::MorphTargetSource;
+use crate::geometry::primitives::{PrimitiveObject, PrimitiveShape};
 use crate::io::render_settings::{
-    AnimationType, RenderSettings, RotationAxis, parse_point3, parse_vec3,
+    AnimationType, FeatureLineMode, HiddenEdgeStyle, MaterialMode, NormalizeMode, RenderSettings,
+    RotationAxis, VideoFormat, parse_point3, parse_vec3,
 };
 use crate::material_system::light::Light;
+use crate::material_system::materials::{MaterialOverride, MaterialType};
+use crate::scene::camera_path::SplineType;
+use crate::scene::timeline::{
+    Channel, Interpolation, KeyValue, Keyframe, LightProperty, MaterialProperty,
+};
 use log::warn;
 use std::path::Path;
 use toml::Value;
@@ -60,16 +68,46 @@ impl TomlConfigLoader {
             Self::parse_render_section(&mut settings, render)?;
         }
 
+        // [lines] 部分
+        if let Some(lines) = toml.get("lines").and_then(|v| v.as_table()) {
+            Self::parse_lines_section(&mut settings, lines)?;
+        }
+
+        // [debug] 部分
+        if let Some(debug) = toml.get("debug").and_then(|v| v.as_table()) {
+            Self::parse_debug_section(&mut settings, debug)?;
+        }
+
         // [camera] 部分
         if let Some(camera) = toml.get("camera").and_then(|v| v.as_table()) {
             Self::parse_camera_section(&mut settings, camera)?;
         }
 
-        // [object] 部分
-        if let Some(object) = toml.get("object").and_then(|v| v.as_table()) {
-            Self::parse_object_section(&mut settings, object)?;
+        // [object] 部分，写成 [[object]] 数组时可包含程序化图元
+        match toml.get("object") {
+            Some(Value::Table(object)) => Self::parse_object_section(&mut settings, object)?,
+            Some(Value::Array(objects)) => Self::parse_objects_array(&mut settings, objects)?,
+            _ => {}
+        }
+
+        // [mesh] 部分
+        if let Some(mesh) = toml.get("mesh").and_then(|v| v.as_table()) {
+            Self::parse_mesh_section(&mut settings, mesh)?;
+        }
+
+        // [lod] 部分
+        if let Some(lod) = toml.get("lod").and_then(|v| v.as_table()) {
+            Self::parse_lod_section(&mut settings, lod)?;
         }
 
+        // [skeleton] 部分
+        if let Some(skeleton) = toml.get("skeleton").and_then(|v| v.as_table()) {
+            Self::parse_skeleton_section(&mut settings, skeleton)?;
+        }
+
+        // [[morph_target]] 数组 - 变形目标
+        settings.morph_targets = Self::parse_morph_targets_array(&toml)?;
+
         // [lighting] 部分
         if let Some(lighting) = toml.get("lighting").and_then(|v| v.as_table()) {
             Self::parse_lighting_section(&mut settings, lighting)?;
@@ -83,16 +121,42 @@ impl TomlConfigLoader {
             Self::parse_material_section(&mut settings, material)?;
         }
 
+        // [[material_override]] 数组 - 逐材质覆盖
+        settings.material_overrides = Self::parse_material_overrides_array(&toml)?;
+
         // [background] 部分
         if let Some(background) = toml.get("background").and_then(|v| v.as_table()) {
             Self::parse_background_section(&mut settings, background)?;
         }
 
+        // [terrain] 部分
+        if let Some(terrain) = toml.get("terrain").and_then(|v| v.as_table()) {
+            Self::parse_terrain_section(&mut settings, terrain)?;
+        }
+
+        // [[keyframe]] 数组 - 关键帧时间线
+        if let Some(keyframes) = toml.get("keyframe").and_then(|v| v.as_array()) {
+            settings.keyframes = keyframes
+                .iter()
+                .enumerate()
+                .filter_map(|(i, value)| value.as_table().map(|table| (i, table)))
+                .map(|(i, table)| {
+                    Self::parse_single_keyframe(table)
+                        .map_err(|e| format!("第{}个关键帧解析失败: {}", i + 1, e))
+                })
+                .collect::<Result<_, _>>()?;
+        }
+
         // [animation] 部分
         if let Some(animation) = toml.get("animation").and_then(|v| v.as_table()) {
             Self::parse_animation_section(&mut settings, animation)?;
         }
 
+        // [camera_path] 部分
+        if let Some(camera_path) = toml.get("camera_path").and_then(|v| v.as_table()) {
+            Self::parse_camera_path_section(&mut settings, camera_path)?;
+        }
+
         // [shadow] 部分
         if let Some(shadow) = toml.get("shadow").and_then(|v| v.as_table()) {
             Self::parse_shadow_section(&mut settings, shadow)?;
@@ -122,6 +186,18 @@ impl TomlConfigLoader {
         if let Some(bg_image) = files.get("background_image").and_then(|v| v.as_str()) {
             settings.background_image_path = Some(bg_image.to_string());
         }
+        if let Some(toon_ramp) = files.get("toon_ramp").and_then(|v| v.as_str()) {
+            settings.toon_ramp_texture = Some(toon_ramp.to_string());
+        }
+        if let Some(matcap) = files.get("matcap").and_then(|v| v.as_str()) {
+            settings.matcap_texture = Some(matcap.to_string());
+        }
+        if let Some(start) = files.get("sequence_start").and_then(|v| v.as_integer()) {
+            settings.sequence_start = start.max(0) as usize;
+        }
+        if let Some(end) = files.get("sequence_end").and_then(|v| v.as_integer()) {
+            settings.sequence_end = Some(end.max(0) as usize);
+        }
         Ok(())
     }
 
@@ -159,6 +235,23 @@ impl TomlConfigLoader {
         if let Some(wireframe) = render.get("wireframe").and_then(|v| v.as_bool()) {
             settings.wireframe = wireframe;
         }
+        if let Some(shaded_wireframe) = render.get("shaded_wireframe").and_then(|v| v.as_bool()) {
+            settings.shaded_wireframe = shaded_wireframe;
+        }
+        if let Some(hidden_edge_style) = render.get("hidden_edge_style").and_then(|v| v.as_str()) {
+            settings.hidden_edge_style = match hidden_edge_style {
+                "Culled" => HiddenEdgeStyle::Culled,
+                "Dashed" => HiddenEdgeStyle::Dashed,
+                "Faded" => HiddenEdgeStyle::Faded,
+                _ => return Err(format!("未知的隐藏边样式: {hidden_edge_style}")),
+            };
+        }
+        if let Some(wireframe_color) = render.get("wireframe_color").and_then(|v| v.as_str()) {
+            settings.wireframe_color = wireframe_color.to_string();
+        }
+        if let Some(wireframe_width) = render.get("wireframe_width").and_then(|v| v.as_float()) {
+            settings.wireframe_width = (wireframe_width as f32).clamp(0.5, 10.0);
+        }
         if let Some(cull_small_triangles) =
             render.get("cull_small_triangles").and_then(|v| v.as_bool())
         {
@@ -168,12 +261,84 @@ impl TomlConfigLoader {
         {
             settings.min_triangle_area = min_triangle_area as f32;
         }
+        if let Some(primitive_line_width) = render
+            .get("primitive_line_width")
+            .and_then(|v| v.as_float())
+        {
+            settings.primitive_line_width = (primitive_line_width as f32).clamp(0.5, 20.0);
+        }
+        if let Some(point_size) = render.get("point_size").and_then(|v| v.as_float()) {
+            settings.point_size = (point_size as f32).max(0.0);
+        }
         if let Some(save_depth) = render.get("save_depth").and_then(|v| v.as_bool()) {
             settings.save_depth = save_depth;
         }
         Ok(())
     }
 
+    fn parse_lines_section(
+        settings: &mut RenderSettings,
+        lines: &toml::Table,
+    ) -> Result<(), String> {
+        if let Some(enable) = lines.get("enable_feature_lines").and_then(|v| v.as_bool()) {
+            settings.enable_feature_lines = enable;
+        }
+        if let Some(mode) = lines.get("feature_line_mode").and_then(|v| v.as_str()) {
+            settings.feature_line_mode = match mode {
+                "Overlay" => FeatureLineMode::Overlay,
+                "Standalone" => FeatureLineMode::Standalone,
+                _ => return Err(format!("未知的特征线模式: {mode}")),
+            };
+        }
+        if let Some(silhouette) = lines.get("silhouette_lines").and_then(|v| v.as_bool()) {
+            settings.silhouette_lines = silhouette;
+        }
+        if let Some(crease) = lines.get("crease_lines").and_then(|v| v.as_bool()) {
+            settings.crease_lines = crease;
+        }
+        if let Some(boundary) = lines.get("boundary_lines").and_then(|v| v.as_bool()) {
+            settings.boundary_lines = boundary;
+        }
+        if let Some(crease_angle) = lines.get("crease_angle").and_then(|v| v.as_float()) {
+            settings.crease_angle = (crease_angle as f32).clamp(0.0, 180.0);
+        }
+        if let Some(line_width) = lines.get("line_width").and_then(|v| v.as_float()) {
+            settings.line_width = (line_width as f32).clamp(0.5, 20.0);
+        }
+        if let Some(line_color) = lines.get("line_color").and_then(|v| v.as_str()) {
+            settings.line_color = line_color.to_string();
+        }
+        Ok(())
+    }
+
+    fn parse_debug_section(
+        settings: &mut RenderSettings,
+        debug: &toml::Table,
+    ) -> Result<(), String> {
+        if let Some(normals) = debug.get("normals").and_then(|v| v.as_bool()) {
+            settings.debug_normals = normals;
+        }
+        if let Some(tangents) = debug.get("tangents").and_then(|v| v.as_bool()) {
+            settings.debug_tangents = tangents;
+        }
+        if let Some(normal_length) = debug.get("normal_length").and_then(|v| v.as_float()) {
+            settings.debug_normal_length = (normal_length as f32).max(0.0);
+        }
+        if let Some(uv_checker) = debug.get("uv_checker").and_then(|v| v.as_bool()) {
+            settings.debug_uv_checker = uv_checker;
+        }
+        if let Some(face_orientation) = debug.get("face_orientation").and_then(|v| v.as_bool()) {
+            settings.debug_face_orientation = face_orientation;
+        }
+        if let Some(bounding_boxes) = debug.get("bounding_boxes").and_then(|v| v.as_bool()) {
+            settings.debug_bounding_boxes = bounding_boxes;
+        }
+        if let Some(shadow_frustum) = debug.get("shadow_frustum").and_then(|v| v.as_bool()) {
+            settings.debug_shadow_frustum = shadow_frustum;
+        }
+        Ok(())
+    }
+
     fn parse_camera_section(
         settings: &mut RenderSettings,
         camera: &toml::Table,
@@ -190,6 +355,27 @@ impl TomlConfigLoader {
         if let Some(fov) = camera.get("fov").and_then(|v| v.as_float()) {
             settings.camera_fov = fov as f32;
         }
+        if let Some(depth_of_field) = camera.get("depth_of_field").and_then(|v| v.as_bool()) {
+            settings.enable_depth_of_field = depth_of_field;
+        }
+        if let Some(aperture) = camera
+            .get("aperture")
+            .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|i| i as f64)))
+        {
+            settings.camera_aperture = aperture as f32;
+        }
+        if let Some(focus_distance) = camera
+            .get("focus_distance")
+            .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|i| i as f64)))
+        {
+            settings.camera_focus_distance = Some(focus_distance as f32);
+        }
+        if let Some(sensor_size) = camera
+            .get("sensor_size")
+            .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|i| i as f64)))
+        {
+            settings.camera_sensor_size = sensor_size as f32;
+        }
         Ok(())
     }
 
@@ -209,9 +395,251 @@ impl TomlConfigLoader {
         if let Some(scale) = object.get("scale").and_then(|v| v.as_float()) {
             settings.object_scale = scale as f32;
         }
+        if let Some(normalize) = object.get("normalize").and_then(|v| v.as_str()) {
+            settings.object_normalize = match normalize {
+                "unit" => NormalizeMode::Unit,
+                "center_only" => NormalizeMode::CenterOnly,
+                "none" => NormalizeMode::None,
+                _ => return Err(format!("未知的归一化方式: {normalize}")),
+            };
+        }
+        Ok(())
+    }
+
+    /// [[object]] 数组解析：含 primitive 字段的条目为程序化图元，
+    /// 其余条目与 [object] 相同，设置整体变换
+    fn parse_objects_array(settings: &mut RenderSettings, objects: &[Value]) -> Result<(), String> {
+        for (i, object_value) in objects.iter().enumerate() {
+            let Some(object_table) = object_value.as_table() else {
+                continue;
+            };
+            if object_table.contains_key("primitive") {
+                let primitive = Self::parse_single_primitive(object_table, i + 1)
+                    .map_err(|e| format!("第{}个对象解析失败: {}", i + 1, e))?;
+                settings.primitives.push(primitive);
+            } else {
+                Self::parse_object_section(settings, object_table)?;
+            }
+        }
         Ok(())
     }
 
+    fn parse_single_primitive(
+        table: &toml::Table,
+        index: usize,
+    ) -> Result<PrimitiveObject, String> {
+        let kind = table
+            .get("primitive")
+            .and_then(|v| v.as_str())
+            .ok_or("primitive 字段应为字符串")?;
+        let mut shape = PrimitiveShape::with_defaults(kind)?;
+
+        let get_size = |key: &str, value: &mut f32| -> Result<(), String> {
+            // 保存时整数值的浮点数会写成不带小数点的形式，这里一并接受
+            let number = table
+                .get(key)
+                .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|i| i as f64)));
+            if let Some(v) = number {
+                if v <= 0.0 {
+                    return Err(format!("{key} 必须为正数"));
+                }
+                *value = v as f32;
+            }
+            Ok(())
+        };
+        let get_count = |key: &str, value: &mut usize| -> Result<(), String> {
+            if let Some(v) = table.get(key).and_then(|v| v.as_integer()) {
+                if v < 1 {
+                    return Err(format!("{key} 必须为正整数"));
+                }
+                *value = v as usize;
+            }
+            Ok(())
+        };
+
+        match &mut shape {
+            PrimitiveShape::Sphere {
+                radius,
+                segments,
+                rings,
+            } => {
+                get_size("radius", radius)?;
+                get_count("segments", segments)?;
+                get_count("rings", rings)?;
+            }
+            PrimitiveShape::Box {
+                width,
+                height,
+                depth,
+            } => {
+                get_size("width", width)?;
+                get_size("height", height)?;
+                get_size("depth", depth)?;
+            }
+            PrimitiveShape::Cylinder {
+                radius,
+                height,
+                segments,
+                capped,
+            } => {
+                get_size("radius", radius)?;
+                get_size("height", height)?;
+                get_count("segments", segments)?;
+                if let Some(v) = table.get("capped").and_then(|v| v.as_bool()) {
+                    *capped = v;
+                }
+            }
+            PrimitiveShape::Torus {
+                major_radius,
+                minor_radius,
+                segments,
+                sides,
+            } => {
+                get_size("major_radius", major_radius)?;
+                get_size("minor_radius", minor_radius)?;
+                get_count("segments", segments)?;
+                get_count("sides", sides)?;
+            }
+            PrimitiveShape::Plane {
+                width,
+                depth,
+                subdivisions,
+            } => {
+                get_size("width", width)?;
+                get_size("depth", depth)?;
+                get_count("subdivisions", subdivisions)?;
+            }
+        }
+
+        let name = table
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{kind}_{index}"));
+        let mut primitive = PrimitiveObject::new(name, shape);
+        for (key, field) in [
+            ("position", &mut primitive.position),
+            ("rotation", &mut primitive.rotation),
+            ("scale", &mut primitive.scale),
+        ] {
+            if let Some(value) = table.get(key).and_then(|v| v.as_str()) {
+                parse_vec3(value).map_err(|e| format!("解析{key}失败: {e}"))?;
+                *field = value.to_string();
+            }
+        }
+        Ok(primitive)
+    }
+
+    fn parse_mesh_section(settings: &mut RenderSettings, mesh: &toml::Table) -> Result<(), String> {
+        if let Some(subdivision) = mesh.get("subdivision").and_then(|v| v.as_integer()) {
+            if subdivision < 0 {
+                return Err("subdivision 不能为负数".to_string());
+            }
+            settings.subdivision_level = subdivision as usize;
+        }
+        if let Some(target) = mesh.get("decimate_target").and_then(|v| v.as_integer()) {
+            if target < 0 {
+                return Err("decimate_target 不能为负数".to_string());
+            }
+            settings.decimate_target = target as usize;
+        }
+        if let Some(recompute) = mesh.get("recompute_normals").and_then(|v| v.as_bool()) {
+            settings.recompute_normals = recompute;
+        }
+        if let Some(angle) = mesh.get("smoothing_angle").and_then(|v| v.as_float()) {
+            settings.smoothing_angle = angle as f32;
+        }
+        Ok(())
+    }
+
+    fn parse_lod_section(settings: &mut RenderSettings, lod: &toml::Table) -> Result<(), String> {
+        if let Some(enabled) = lod.get("enabled").and_then(|v| v.as_bool()) {
+            settings.enable_lod = enabled;
+        }
+        if let Some(levels) = lod.get("levels").and_then(|v| v.as_integer()) {
+            if levels < 0 {
+                return Err("levels 不能为负数".to_string());
+            }
+            settings.lod_levels = levels as usize;
+        }
+        if let Some(ratio) = lod.get("ratio").and_then(|v| v.as_float()) {
+            settings.lod_ratio = ratio as f32;
+        }
+        // 像素尺寸常写成整数
+        if let Some(size) = lod
+            .get("switch_size")
+            .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|i| i as f64)))
+        {
+            settings.lod_switch_size = size as f32;
+        }
+        if let Some(hysteresis) = lod.get("hysteresis").and_then(|v| v.as_float()) {
+            settings.lod_hysteresis = hysteresis as f32;
+        }
+        Ok(())
+    }
+
+    fn parse_skeleton_section(
+        settings: &mut RenderSettings,
+        skeleton: &toml::Table,
+    ) -> Result<(), String> {
+        let as_number = |value: &Value| {
+            value
+                .as_float()
+                .or_else(|| value.as_integer().map(|i| i as f64))
+        };
+        if let Some(bvh) = skeleton.get("bvh").and_then(|v| v.as_str()) {
+            settings.skeleton_bvh = Some(bvh.to_string());
+        }
+        if let Some(scale) = skeleton.get("scale").and_then(as_number) {
+            settings.skeleton_scale = scale as f32;
+        }
+        if let Some(offset) = skeleton.get("offset").and_then(|v| v.as_str()) {
+            settings.skeleton_offset = offset.to_string();
+        }
+        if let Some(speed) = skeleton.get("speed").and_then(as_number) {
+            settings.skeleton_speed = speed as f32;
+        }
+        if let Some(looping) = skeleton.get("loop").and_then(|v| v.as_bool()) {
+            settings.skeleton_loop = looping;
+        }
+        if let Some(time) = skeleton.get("time").and_then(as_number) {
+            settings.skeleton_time = time as f32;
+        }
+        Ok(())
+    }
+
+    /// 变形目标解析 - [[morph_target]] 数组，每项为 name、obj 和初始 weight
+    fn parse_morph_targets_array(toml: &Value) -> Result<Vec<MorphTargetSource>, String> {
+        let mut targets = Vec::new();
+        for (i, table) in toml
+            .get("morph_target")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_table())
+            .enumerate()
+        {
+            let name = table
+                .get("name")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("第{}个变形目标缺少name字段", i + 1))?;
+            let obj = table
+                .get("obj")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("变形目标 '{name}' 缺少obj字段"))?;
+            let weight = table
+                .get("weight")
+                .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|i| i as f64)))
+                .unwrap_or(0.0);
+            targets.push(MorphTargetSource {
+                name: name.to_string(),
+                obj: obj.to_string(),
+                weight: weight as f32,
+            });
+        }
+        Ok(targets)
+    }
+
     fn parse_lighting_section(
         settings: &mut RenderSettings,
         lighting: &toml::Table,
@@ -333,11 +761,32 @@ impl TomlConfigLoader {
         settings: &mut RenderSettings,
         material: &toml::Table,
     ) -> Result<(), String> {
-        if let Some(use_phong) = material.get("use_phong").and_then(|v| v.as_bool()) {
-            settings.use_phong = use_phong;
+        if let Some(material_mode) = material.get("material_mode").and_then(|v| v.as_str()) {
+            settings.material_mode = match material_mode {
+                "Global" => MaterialMode::Global,
+                "FromFile" => MaterialMode::FromFile,
+                _ => return Err(format!("未知的材质模式: {material_mode}")),
+            };
         }
-        if let Some(use_pbr) = material.get("use_pbr").and_then(|v| v.as_bool()) {
-            settings.use_pbr = use_pbr;
+        if let Some(shading_model) = material.get("shading_model").and_then(|v| v.as_str()) {
+            settings.shading_model = match shading_model {
+                "Phong" => MaterialType::Phong,
+                "PBR" => MaterialType::PBR,
+                "Toon" => MaterialType::Toon,
+                "Lambert" => MaterialType::Lambert,
+                "OrenNayar" => MaterialType::OrenNayar,
+                "Unlit" => MaterialType::Unlit,
+                "Matcap" => MaterialType::Matcap,
+                _ => return Err(format!("未知的着色模型: {shading_model}")),
+            };
+        } else if material.get("use_pbr").and_then(|v| v.as_bool()) == Some(true) {
+            // 兼容旧配置中的 use_pbr / use_phong 开关
+            settings.shading_model = MaterialType::PBR;
+        } else if material.get("use_phong").and_then(|v| v.as_bool()) == Some(true) {
+            settings.shading_model = MaterialType::Phong;
+        }
+        if let Some(toon_bands) = material.get("toon_bands").and_then(|v| v.as_integer()) {
+            settings.toon_bands = toon_bands.max(2) as u32;
         }
         if let Some(diffuse_color) = material.get("diffuse_color").and_then(|v| v.as_str()) {
             settings.diffuse_color = diffuse_color.to_string();
@@ -345,6 +794,9 @@ impl TomlConfigLoader {
         if let Some(alpha) = material.get("alpha").and_then(|v| v.as_float()) {
             settings.alpha = alpha as f32;
         }
+        if let Some(alpha_cutoff) = material.get("alpha_cutoff").and_then(|v| v.as_float()) {
+            settings.alpha_cutoff = alpha_cutoff as f32;
+        }
 
         if let Some(diffuse_intensity) =
             material.get("diffuse_intensity").and_then(|v| v.as_float())
@@ -382,9 +834,92 @@ impl TomlConfigLoader {
         if let Some(emissive) = material.get("emissive").and_then(|v| v.as_str()) {
             settings.emissive = emissive.to_string();
         }
+        if let Some(clearcoat) = material.get("clearcoat").and_then(|v| v.as_float()) {
+            settings.clearcoat = clearcoat as f32;
+        }
+        if let Some(clearcoat_roughness) = material
+            .get("clearcoat_roughness")
+            .and_then(|v| v.as_float())
+        {
+            settings.clearcoat_roughness = clearcoat_roughness as f32;
+        }
+        if let Some(sheen_color) = material.get("sheen_color").and_then(|v| v.as_str()) {
+            settings.sheen_color = sheen_color.to_string();
+        }
+        if let Some(sheen_roughness) = material.get("sheen_roughness").and_then(|v| v.as_float()) {
+            settings.sheen_roughness = sheen_roughness as f32;
+        }
+        if let Some(anisotropy) = material.get("anisotropy").and_then(|v| v.as_float()) {
+            settings.anisotropy = anisotropy as f32;
+        }
+        if let Some(anisotropy_rotation) = material
+            .get("anisotropy_rotation")
+            .and_then(|v| v.as_float())
+        {
+            settings.anisotropy_rotation = anisotropy_rotation as f32;
+        }
+        if let Some(transmission) = material.get("transmission").and_then(|v| v.as_float()) {
+            settings.transmission = transmission as f32;
+        }
         Ok(())
     }
 
+    /// 逐材质覆盖解析 - 支持 [[material_override]] 数组语法
+    fn parse_material_overrides_array(toml: &Value) -> Result<Vec<MaterialOverride>, String> {
+        let mut overrides = Vec::new();
+
+        if let Some(override_array) = toml.get("material_override").and_then(|v| v.as_array()) {
+            for (i, override_value) in override_array.iter().enumerate() {
+                if let Some(override_table) = override_value.as_table() {
+                    let material_override = Self::parse_single_material_override(override_table)
+                        .map_err(|e| format!("第{}个材质覆盖解析失败: {}", i + 1, e))?;
+                    overrides.push(material_override);
+                }
+            }
+        }
+
+        Ok(overrides)
+    }
+
+    fn parse_single_material_override(table: &toml::Table) -> Result<MaterialOverride, String> {
+        let target = table
+            .get("target")
+            .and_then(|v| v.as_str())
+            .ok_or("材质覆盖缺少target字段")?;
+
+        let get_float = |key: &str| table.get(key).and_then(|v| v.as_float()).map(|v| v as f32);
+        let get_color = |key: &str| -> Result<Option<String>, String> {
+            match table.get(key).and_then(|v| v.as_str()) {
+                Some(color) => {
+                    parse_vec3(color).map_err(|e| format!("解析{key}失败: {e}"))?;
+                    Ok(Some(color.to_string()))
+                }
+                None => Ok(None),
+            }
+        };
+
+        Ok(MaterialOverride {
+            target: target.to_string(),
+            base_color: get_color("base_color")?,
+            alpha: get_float("alpha"),
+            emissive: get_color("emissive")?,
+            metallic: get_float("metallic"),
+            roughness: get_float("roughness"),
+            ambient_occlusion: get_float("ambient_occlusion"),
+            clearcoat: get_float("clearcoat"),
+            clearcoat_roughness: get_float("clearcoat_roughness"),
+            sheen_color: get_color("sheen_color")?,
+            sheen_roughness: get_float("sheen_roughness"),
+            anisotropy: get_float("anisotropy"),
+            anisotropy_rotation: get_float("anisotropy_rotation"),
+            transmission: get_float("transmission"),
+            specular_color: get_color("specular_color")?,
+            shininess: get_float("shininess"),
+            diffuse_intensity: get_float("diffuse_intensity"),
+            specular_intensity: get_float("specular_intensity"),
+        })
+    }
+
     fn parse_background_section(
         settings: &mut RenderSettings,
         background: &toml::Table,
@@ -430,6 +965,71 @@ impl TomlConfigLoader {
             .and_then(|v| v.as_float())
         {
             settings.ground_plane_height = ground_plane_height as f32;
+            settings.ground_plane_height_explicit = true;
+        }
+        if let Some(ground_plane_mesh) = background
+            .get("ground_plane_mesh")
+            .and_then(|v| v.as_bool())
+        {
+            settings.ground_plane_mesh = ground_plane_mesh;
+        }
+        if let Some(ground_plane_size) = background
+            .get("ground_plane_size")
+            .and_then(|v| v.as_float())
+        {
+            settings.ground_plane_size = (ground_plane_size as f32).max(0.01);
+        }
+        if let Some(ground_plane_texture) = background
+            .get("ground_plane_texture")
+            .and_then(|v| v.as_str())
+        {
+            settings.ground_plane_texture = Some(ground_plane_texture.to_string());
+        }
+        if let Some(ground_plane_texture_tiling) = background
+            .get("ground_plane_texture_tiling")
+            .and_then(|v| v.as_float())
+        {
+            settings.ground_plane_texture_tiling = (ground_plane_texture_tiling as f32).max(0.01);
+        }
+        Ok(())
+    }
+
+    fn parse_terrain_section(
+        settings: &mut RenderSettings,
+        terrain: &toml::Table,
+    ) -> Result<(), String> {
+        if let Some(enable) = terrain.get("enable").and_then(|v| v.as_bool()) {
+            settings.enable_terrain = enable;
+        }
+        if let Some(heightmap) = terrain.get("heightmap").and_then(|v| v.as_str()) {
+            settings.terrain_heightmap = Some(heightmap.to_string());
+        }
+        if let Some(texture) = terrain.get("texture").and_then(|v| v.as_str()) {
+            settings.terrain_texture = Some(texture.to_string());
+        }
+        if let Some(size) = terrain.get("size").and_then(|v| v.as_float()) {
+            settings.terrain_size = (size as f32).max(0.01);
+        }
+        if let Some(height_scale) = terrain.get("height_scale").and_then(|v| v.as_float()) {
+            settings.terrain_height_scale = height_scale as f32;
+        }
+        if let Some(height) = terrain.get("height").and_then(|v| v.as_float()) {
+            settings.terrain_height = height as f32;
+        }
+        if let Some(resolution) = terrain.get("resolution").and_then(|v| v.as_integer()) {
+            settings.terrain_resolution = (resolution as usize).clamp(8, 1024);
+        }
+        if let Some(texture_tiling) = terrain.get("texture_tiling").and_then(|v| v.as_float()) {
+            settings.terrain_texture_tiling = (texture_tiling as f32).max(0.01);
+        }
+        if let Some(color) = terrain.get("color").and_then(|v| v.as_str()) {
+            settings.terrain_color = color.to_string();
+        }
+        if let Some(lod) = terrain.get("lod").and_then(|v| v.as_bool()) {
+            settings.terrain_lod = lod;
+        }
+        if let Some(lod_distance) = terrain.get("lod_distance").and_then(|v| v.as_float()) {
+            settings.terrain_lod_distance = (lod_distance as f32).max(0.1);
         }
         Ok(())
     }
@@ -454,6 +1054,8 @@ impl TomlConfigLoader {
             settings.animation_type = match animation_type {
                 "CameraOrbit" => AnimationType::CameraOrbit,
                 "ObjectLocalRotation" => AnimationType::ObjectLocalRotation,
+                "Keyframes" => AnimationType::Keyframes,
+                "CameraPath" => AnimationType::CameraPath,
                 "None" => AnimationType::None,
                 _ => return Err(format!("未知的动画类型: {animation_type}")),
             };
@@ -473,9 +1075,223 @@ impl TomlConfigLoader {
         {
             settings.custom_rotation_axis = custom_rotation_axis.to_string();
         }
+        if let Some(video_format) = animation.get("video_format").and_then(|v| v.as_str()) {
+            settings.video_format = VideoFormat::parse(video_format)?;
+        }
+        if let Some(gif_colors) = animation.get("gif_colors").and_then(|v| v.as_integer()) {
+            settings.gif_colors = gif_colors.max(0) as usize;
+        }
+        if let Some(gif_dither) = animation.get("gif_dither").and_then(|v| v.as_bool()) {
+            settings.gif_dither = gif_dither;
+        }
+        if let Some(motion_blur) = animation.get("motion_blur").and_then(|v| v.as_bool()) {
+            settings.motion_blur = motion_blur;
+        }
+        if let Some(samples) = animation
+            .get("motion_blur_samples")
+            .and_then(|v| v.as_integer())
+        {
+            settings.motion_blur_samples = samples.max(0) as usize;
+        }
+        if let Some(shutter_angle) = animation
+            .get("shutter_angle")
+            .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|i| i as f64)))
+        {
+            settings.shutter_angle = shutter_angle as f32;
+        }
+        Ok(())
+    }
+
+    fn parse_camera_path_section(
+        settings: &mut RenderSettings,
+        camera_path: &toml::Table,
+    ) -> Result<(), String> {
+        let as_number = |value: &Value| {
+            value
+                .as_float()
+                .or_else(|| value.as_integer().map(|i| i as f64))
+                .map(|v| v as f32)
+        };
+        let parse_points = |key: &str| -> Result<Option<Vec<_>>, String> {
+            let Some(array) = camera_path.get(key).and_then(|v| v.as_array()) else {
+                return Ok(None);
+            };
+            array
+                .iter()
+                .map(|point| {
+                    point
+                        .as_str()
+                        .ok_or_else(|| format!("{key} 的元素应为 \"x,y,z\" 字符串"))
+                        .and_then(|text| {
+                            parse_point3(text).map_err(|e| format!("解析{key}失败: {e}"))
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Some)
+        };
+
+        let path = &mut settings.camera_path;
+        if let Some(spline) = camera_path.get("type").and_then(|v| v.as_str()) {
+            path.spline = SplineType::parse(spline)?;
+        }
+        if let Some(points) = parse_points("points")? {
+            path.points = points;
+        }
+        if let Some(closed) = camera_path.get("closed").and_then(|v| v.as_bool()) {
+            path.closed = closed;
+        }
+        if let Some(target) = camera_path.get("target").and_then(|v| v.as_str()) {
+            path.target = Some(parse_point3(target).map_err(|e| format!("解析target失败: {e}"))?);
+        }
+        if let Some(target_points) = parse_points("target_points")? {
+            path.target_points = target_points;
+        }
+        if let Some(duration) = camera_path.get("duration").and_then(as_number) {
+            path.duration = duration;
+        }
+        if let Some(constant_speed) = camera_path.get("constant_speed").and_then(|v| v.as_bool()) {
+            path.constant_speed = constant_speed;
+        }
+        // 滚转可以是单个角度或沿路径分布的角度数组
+        match camera_path.get("roll") {
+            Some(Value::Array(values)) => {
+                path.roll = values
+                    .iter()
+                    .map(|v| as_number(v).ok_or("roll 数组的元素应为数值".to_string()))
+                    .collect::<Result<_, _>>()?;
+            }
+            Some(value) => {
+                path.roll = vec![as_number(value).ok_or("roll 应为数值或数值数组")?];
+            }
+            None => {}
+        }
         Ok(())
     }
 
+    /// 单个关键帧，光源、材质属性和变形权重分别位于 `[[keyframe.light]]`、`[[keyframe.material]]` 与 `[[keyframe.morph]]` 子表
+    fn parse_single_keyframe(table: &toml::Table) -> Result<Keyframe, String> {
+        // 保存时整数值的浮点数会写成整数形式
+        let as_number = |value: &Value| {
+            value
+                .as_float()
+                .or_else(|| value.as_integer().map(|i| i as f64))
+                .map(|v| v as f32)
+        };
+        let parse_value = |value: &Value, channel: &Channel| -> Result<KeyValue, String> {
+            if channel.is_scalar() {
+                as_number(value)
+                    .map(KeyValue::Scalar)
+                    .ok_or_else(|| format!("{} 应为数值", channel.key()))
+            } else {
+                let text = value
+                    .as_str()
+                    .ok_or_else(|| format!("{} 应为 \"x,y,z\" 字符串", channel.key()))?;
+                parse_vec3(text)
+                    .map(KeyValue::Vector)
+                    .map_err(|e| format!("解析{}失败: {e}", channel.key()))
+            }
+        };
+
+        let time = table
+            .get("time")
+            .and_then(as_number)
+            .ok_or("缺少 time 字段")?;
+        if time < 0.0 {
+            return Err("time 不能为负数".to_string());
+        }
+
+        let control_points = match table.get("bezier").and_then(|v| v.as_str()) {
+            Some(text) => {
+                let values: Vec<f32> = text
+                    .split(',')
+                    .map(|part| part.trim().parse::<f32>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| format!("解析bezier失败: {e}"))?;
+                Some(
+                    <[f32; 4]>::try_from(values)
+                        .map_err(|_| "bezier 应为 \"x1,y1,x2,y2\"".to_string())?,
+                )
+            }
+            None => None,
+        };
+        let interpolation = Interpolation::parse(
+            table
+                .get("interpolation")
+                .and_then(|v| v.as_str())
+                .unwrap_or("linear"),
+            control_points,
+        )?;
+
+        let mut values = Vec::new();
+        for channel in Channel::SCENE_CHANNELS {
+            if let Some(value) = table.get(channel.key()) {
+                values.push((channel.clone(), parse_value(value, &channel)?));
+            }
+        }
+
+        for light in table
+            .get("light")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_table())
+        {
+            let index = light
+                .get("index")
+                .and_then(|v| v.as_integer())
+                .filter(|&i| i >= 0)
+                .ok_or("keyframe.light 缺少有效的 index 字段")? as usize;
+            for property in LightProperty::ALL {
+                let channel = Channel::Light(index, property);
+                if let Some(value) = light.get(channel.key()) {
+                    values.push((channel.clone(), parse_value(value, &channel)?));
+                }
+            }
+        }
+
+        for material in table
+            .get("material")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_table())
+        {
+            let target = material
+                .get("target")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            for property in MaterialProperty::ALL {
+                let channel = Channel::Material(target.clone(), property);
+                if let Some(value) = material.get(channel.key()) {
+                    values.push((channel.clone(), parse_value(value, &channel)?));
+                }
+            }
+        }
+
+        for morph in table
+            .get("morph")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_table())
+        {
+            let name = morph
+                .get("name")
+                .and_then(|v| v.as_str())
+                .ok_or("keyframe.morph 缺少 name 字段")?;
+            let channel = Channel::Morph(name.to_string());
+            if let Some(value) = morph.get(channel.key()) {
+                values.push((channel.clone(), parse_value(value, &channel)?));
+            }
+        }
+
+        Ok(Keyframe {
+            time,
+            interpolation,
+            values,
+        })
+    }
+
     /// 阴影配置解析
     fn parse_shadow_section(
         settings: &mut RenderSettings,
@@ -500,7 +1316,8 @@ impl TomlConfigLoader {
             settings.shadow_bias = (shadow_bias as f32).clamp(0.0001, 0.1);
         }
         if let Some(shadow_distance) = shadow.get("shadow_distance").and_then(|v| v.as_float()) {
-            settings.shadow_distance = (shadow_distance as f32).clamp(1.0, 100.0);
+            settings.shadow_distance = (shadow_distance as f32).max(1.0);
+            settings.shadow_distance_explicit = true;
         }
         if let Some(enable_pcf) = shadow.get("enable_pcf").and_then(|v| v.as_bool()) {
             settings.enable_pcf = enable_pcf;
@@ -545,6 +1362,18 @@ impl TomlConfigLoader {
         } else {
             content.push_str("# background_image = \"path/to/background.jpg\"  # 可选：背景图片\n");
         }
+        if let Some(toon_ramp) = &settings.toon_ramp_texture {
+            content.push_str(&format!("toon_ramp = \"{toon_ramp}\"\n"));
+        }
+        if let Some(matcap) = &settings.matcap_texture {
+            content.push_str(&format!("matcap = \"{matcap}\"\n"));
+        }
+        if settings.is_obj_sequence() {
+            content.push_str(&format!("sequence_start = {}\n", settings.sequence_start));
+            if let Some(end) = settings.sequence_end {
+                content.push_str(&format!("sequence_end = {end}\n"));
+            }
+        }
         content.push('\n');
 
         // [render] 部分
@@ -562,6 +1391,19 @@ impl TomlConfigLoader {
         ));
         content.push_str(&format!("enable_aces = {}\n", settings.enable_aces));
         content.push_str(&format!("wireframe = {}\n", settings.wireframe));
+        content.push_str(&format!(
+            "shaded_wireframe = {}\n",
+            settings.shaded_wireframe
+        ));
+        content.push_str(&format!(
+            "hidden_edge_style = \"{:?}\"\n",
+            settings.hidden_edge_style
+        ));
+        content.push_str(&format!(
+            "wireframe_color = \"{}\"\n",
+            settings.wireframe_color
+        ));
+        content.push_str(&format!("wireframe_width = {}\n", settings.wireframe_width));
         content.push_str(&format!(
             "cull_small_triangles = {}\n",
             settings.cull_small_triangles
@@ -571,6 +1413,55 @@ impl TomlConfigLoader {
             settings.min_triangle_area
         ));
         content.push_str(&format!("save_depth = {}\n", settings.save_depth));
+        content.push_str(&format!(
+            "primitive_line_width = {}\n",
+            settings.primitive_line_width
+        ));
+        content.push_str(&format!("point_size = {}\n", settings.point_size));
+        content.push('\n');
+
+        // [lines] 部分
+        content.push_str("[lines]\n");
+        content.push_str(&format!(
+            "enable_feature_lines = {}\n",
+            settings.enable_feature_lines
+        ));
+        content.push_str(&format!(
+            "feature_line_mode = \"{:?}\"\n",
+            settings.feature_line_mode
+        ));
+        content.push_str(&format!(
+            "silhouette_lines = {}\n",
+            settings.silhouette_lines
+        ));
+        content.push_str(&format!("crease_lines = {}\n", settings.crease_lines));
+        content.push_str(&format!("boundary_lines = {}\n", settings.boundary_lines));
+        content.push_str(&format!("crease_angle = {}\n", settings.crease_angle));
+        content.push_str(&format!("line_width = {}\n", settings.line_width));
+        content.push_str(&format!("line_color = \"{}\"\n", settings.line_color));
+        content.push('\n');
+
+        // [debug] 部分
+        content.push_str("[debug]\n");
+        content.push_str(&format!("normals = {}\n", settings.debug_normals));
+        content.push_str(&format!("tangents = {}\n", settings.debug_tangents));
+        content.push_str(&format!(
+            "normal_length = {}\n",
+            settings.debug_normal_length
+        ));
+        content.push_str(&format!("uv_checker = {}\n", settings.debug_uv_checker));
+        content.push_str(&format!(
+            "face_orientation = {}\n",
+            settings.debug_face_orientation
+        ));
+        content.push_str(&format!(
+            "bounding_boxes = {}\n",
+            settings.debug_bounding_boxes
+        ));
+        content.push_str(&format!(
+            "shadow_frustum = {}\n",
+            settings.debug_shadow_frustum
+        ));
         content.push('\n');
 
         // [camera] 部分
@@ -579,16 +1470,135 @@ impl TomlConfigLoader {
         content.push_str(&format!("at = \"{}\"\n", settings.camera_at));
         content.push_str(&format!("up = \"{}\"\n", settings.camera_up));
         content.push_str(&format!("fov = {}\n", settings.camera_fov));
+        content.push_str(&format!(
+            "depth_of_field = {}\n",
+            settings.enable_depth_of_field
+        ));
+        content.push_str(&format!("aperture = {}\n", settings.camera_aperture));
+        if let Some(focus_distance) = settings.camera_focus_distance {
+            content.push_str(&format!("focus_distance = {focus_distance}\n"));
+        }
+        content.push_str(&format!("sensor_size = {}\n", settings.camera_sensor_size));
         content.push('\n');
 
-        // [object] 部分
-        content.push_str("[object]\n");
+        // [object] 部分，有程序化图元时改用 [[object]] 数组
+        if settings.primitives.is_empty() {
+            content.push_str("[object]\n");
+        } else {
+            content.push_str("[[object]]\n");
+        }
         content.push_str(&format!("position = \"{}\"\n", settings.object_position));
         content.push_str(&format!("rotation = \"{}\"\n", settings.object_rotation));
         content.push_str(&format!("scale_xyz = \"{}\"\n", settings.object_scale_xyz));
         content.push_str(&format!("scale = {}\n", settings.object_scale));
+        content.push_str(&format!(
+            "normalize = \"{}\"\n",
+            settings.object_normalize.as_str()
+        ));
         content.push('\n');
 
+        for primitive in &settings.primitives {
+            content.push_str("[[object]]\n");
+            content.push_str(&format!("name = \"{}\"\n", primitive.name));
+            content.push_str(&format!("primitive = \"{}\"\n", primitive.shape.kind()));
+            match &primitive.shape {
+                PrimitiveShape::Sphere {
+                    radius,
+                    segments,
+                    rings,
+                } => {
+                    content.push_str(&format!("radius = {radius}\n"));
+                    content.push_str(&format!("segments = {segments}\n"));
+                    content.push_str(&format!("rings = {rings}\n"));
+                }
+                PrimitiveShape::Box {
+                    width,
+                    height,
+                    depth,
+                } => {
+                    content.push_str(&format!("width = {width}\n"));
+                    content.push_str(&format!("height = {height}\n"));
+                    content.push_str(&format!("depth = {depth}\n"));
+                }
+                PrimitiveShape::Cylinder {
+                    radius,
+                    height,
+                    segments,
+                    capped,
+                } => {
+                    content.push_str(&format!("radius = {radius}\n"));
+                    content.push_str(&format!("height = {height}\n"));
+                    content.push_str(&format!("segments = {segments}\n"));
+                    content.push_str(&format!("capped = {capped}\n"));
+                }
+                PrimitiveShape::Torus {
+                    major_radius,
+                    minor_radius,
+                    segments,
+                    sides,
+                } => {
+                    content.push_str(&format!("major_radius = {major_radius}\n"));
+                    content.push_str(&format!("minor_radius = {minor_radius}\n"));
+                    content.push_str(&format!("segments = {segments}\n"));
+                    content.push_str(&format!("sides = {sides}\n"));
+                }
+                PrimitiveShape::Plane {
+                    width,
+                    depth,
+                    subdivisions,
+                } => {
+                    content.push_str(&format!("width = {width}\n"));
+                    content.push_str(&format!("depth = {depth}\n"));
+                    content.push_str(&format!("subdivisions = {subdivisions}\n"));
+                }
+            }
+            content.push_str(&format!("position = \"{}\"\n", primitive.position));
+            content.push_str(&format!("rotation = \"{}\"\n", primitive.rotation));
+            content.push_str(&format!("scale = \"{}\"\n", primitive.scale));
+            content.push('\n');
+        }
+
+        // [mesh] 部分
+        content.push_str("[mesh]\n");
+        content.push_str(&format!("subdivision = {}\n", settings.subdivision_level));
+        content.push_str(&format!("decimate_target = {}\n", settings.decimate_target));
+        content.push_str(&format!(
+            "recompute_normals = {}\n",
+            settings.recompute_normals
+        ));
+        content.push_str(&format!("smoothing_angle = {}\n", settings.smoothing_angle));
+        content.push('\n');
+
+        // [lod] 部分
+        content.push_str("[lod]\n");
+        content.push_str(&format!("enabled = {}\n", settings.enable_lod));
+        content.push_str(&format!("levels = {}\n", settings.lod_levels));
+        content.push_str(&format!("ratio = {}\n", settings.lod_ratio));
+        content.push_str(&format!("switch_size = {}\n", settings.lod_switch_size));
+        content.push_str(&format!("hysteresis = {}\n", settings.lod_hysteresis));
+        content.push('\n');
+
+        // [skeleton] 部分，仅在指定了 BVH 时写出
+        if let Some(bvh) = &settings.skeleton_bvh {
+            content.push_str("[skeleton]\n");
+            content.push_str(&format!("bvh = \"{bvh}\"\n"));
+            content.push_str(&format!("scale = {}\n", settings.skeleton_scale));
+            content.push_str(&format!("offset = \"{}\"\n", settings.skeleton_offset));
+            content.push_str(&format!("speed = {}\n", settings.skeleton_speed));
+            content.push_str(&format!("loop = {}\n", settings.skeleton_loop));
+            content.push_str(&format!("time = {}\n", settings.skeleton_time));
+            content.push('\n');
+        }
+
+        // [[morph_target]] 数组
+        for target in &settings.morph_targets {
+            content.push_str("[[morph_target]]\n");
+            content.push_str(&format!("name = \"{}\"\n", target.name));
+            content.push_str(&format!("obj = \"{}\"\n", target.obj));
+            content.push_str(&format!("weight = {}\n", target.weight));
+            content.push('\n');
+        }
+
         // [lighting] 部分
         content.push_str("[lighting]\n");
         content.push_str(&format!("use_lighting = {}\n", settings.use_lighting));
@@ -644,14 +1654,22 @@ impl TomlConfigLoader {
 
         // [material] 部分
         content.push_str("[material]\n");
-        content.push_str(&format!("use_phong = {}\n", settings.use_phong));
-        content.push_str(&format!("use_pbr = {}\n", settings.use_pbr));
+        content.push_str(&format!(
+            "material_mode = \"{:?}\"\n",
+            settings.material_mode
+        ));
+        content.push_str(&format!(
+            "shading_model = \"{:?}\"\n",
+            settings.shading_model
+        ));
+        content.push_str(&format!("toon_bands = {}\n", settings.toon_bands));
         content.push_str(&format!("diffuse_color = \"{}\"\n", settings.diffuse_color));
         content.push_str(&format!(
             "diffuse_intensity = {}\n",
             settings.diffuse_intensity
         ));
         content.push_str(&format!("alpha = {}\n", settings.alpha));
+        content.push_str(&format!("alpha_cutoff = {}\n", settings.alpha_cutoff));
         content.push_str(&format!(
             "specular_color = \"{}\"\n",
             settings.specular_color
@@ -669,8 +1687,62 @@ impl TomlConfigLoader {
             settings.ambient_occlusion
         ));
         content.push_str(&format!("emissive = \"{}\"\n", settings.emissive));
+        content.push_str(&format!("clearcoat = {}\n", settings.clearcoat));
+        content.push_str(&format!(
+            "clearcoat_roughness = {}\n",
+            settings.clearcoat_roughness
+        ));
+        content.push_str(&format!("sheen_color = \"{}\"\n", settings.sheen_color));
+        content.push_str(&format!("sheen_roughness = {}\n", settings.sheen_roughness));
+        content.push_str(&format!("anisotropy = {}\n", settings.anisotropy));
+        content.push_str(&format!(
+            "anisotropy_rotation = {}\n",
+            settings.anisotropy_rotation
+        ));
+        content.push_str(&format!("transmission = {}\n", settings.transmission));
         content.push('\n');
 
+        // [[material_override]] 数组
+        if !settings.material_overrides.is_empty() {
+            content.push_str("# 逐材质覆盖（target 为材质名或网格名）\n");
+            for material_override in &settings.material_overrides {
+                content.push_str("[[material_override]]\n");
+                content.push_str(&format!("target = \"{}\"\n", material_override.target));
+                let colors = [
+                    ("base_color", &material_override.base_color),
+                    ("emissive", &material_override.emissive),
+                    ("specular_color", &material_override.specular_color),
+                    ("sheen_color", &material_override.sheen_color),
+                ];
+                for (key, value) in colors {
+                    if let Some(value) = value {
+                        content.push_str(&format!("{key} = \"{value}\"\n"));
+                    }
+                }
+                let floats = [
+                    ("alpha", material_override.alpha),
+                    ("metallic", material_override.metallic),
+                    ("roughness", material_override.roughness),
+                    ("ambient_occlusion", material_override.ambient_occlusion),
+                    ("clearcoat", material_override.clearcoat),
+                    ("clearcoat_roughness", material_override.clearcoat_roughness),
+                    ("sheen_roughness", material_override.sheen_roughness),
+                    ("anisotropy", material_override.anisotropy),
+                    ("anisotropy_rotation", material_override.anisotropy_rotation),
+                    ("transmission", material_override.transmission),
+                    ("shininess", material_override.shininess),
+                    ("diffuse_intensity", material_override.diffuse_intensity),
+                    ("specular_intensity", material_override.specular_intensity),
+                ];
+                for (key, value) in floats {
+                    if let Some(value) = value {
+                        content.push_str(&format!("{key} = {value}\n"));
+                    }
+                }
+                content.push('\n');
+            }
+        }
+
         // [background] 部分
         content.push_str("[background]\n");
         content.push_str(&format!(
@@ -697,9 +1769,61 @@ impl TomlConfigLoader {
             "ground_plane_color = \"{}\"\n",
             settings.ground_plane_color
         ));
+        // 随模型包围盒自适应的值不写入，重新加载时仍按包围盒计算
+        if settings.ground_plane_height_explicit {
+            content.push_str(&format!(
+                "ground_plane_height = {}\n",
+                settings.ground_plane_height
+            ));
+        } else {
+            content.push_str("# ground_plane_height = -1.0  # 未设置时按模型包围盒自适应\n");
+        }
+        content.push_str(&format!(
+            "ground_plane_mesh = {}\n",
+            settings.ground_plane_mesh
+        ));
+        content.push_str(&format!(
+            "ground_plane_size = {}\n",
+            settings.ground_plane_size
+        ));
+        if let Some(ground_plane_texture) = &settings.ground_plane_texture {
+            content.push_str(&format!(
+                "ground_plane_texture = \"{ground_plane_texture}\"\n"
+            ));
+        }
         content.push_str(&format!(
-            "ground_plane_height = {}\n",
-            settings.ground_plane_height
+            "ground_plane_texture_tiling = {}\n",
+            settings.ground_plane_texture_tiling
+        ));
+        content.push('\n');
+
+        // [terrain] 部分
+        content.push_str("[terrain]\n");
+        content.push_str(&format!("enable = {}\n", settings.enable_terrain));
+        if let Some(heightmap) = &settings.terrain_heightmap {
+            content.push_str(&format!("heightmap = \"{heightmap}\"\n"));
+        } else {
+            content.push_str("# heightmap = \"path/to/heightmap.png\"  # 灰度高度图\n");
+        }
+        if let Some(texture) = &settings.terrain_texture {
+            content.push_str(&format!("texture = \"{texture}\"\n"));
+        }
+        content.push_str(&format!("size = {}\n", settings.terrain_size));
+        content.push_str(&format!(
+            "height_scale = {}\n",
+            settings.terrain_height_scale
+        ));
+        content.push_str(&format!("height = {}\n", settings.terrain_height));
+        content.push_str(&format!("resolution = {}\n", settings.terrain_resolution));
+        content.push_str(&format!(
+            "texture_tiling = {}\n",
+            settings.terrain_texture_tiling
+        ));
+        content.push_str(&format!("color = \"{}\"\n", settings.terrain_color));
+        content.push_str(&format!("lod = {}\n", settings.terrain_lod));
+        content.push_str(&format!(
+            "lod_distance = {}\n",
+            settings.terrain_lod_distance
         ));
         content.push('\n');
 
@@ -721,8 +1845,55 @@ impl TomlConfigLoader {
             "custom_rotation_axis = \"{}\"\n",
             settings.custom_rotation_axis
         ));
+        content.push_str(&format!(
+            "video_format = \"{}\"\n",
+            settings.video_format.name()
+        ));
+        content.push_str(&format!("gif_colors = {}\n", settings.gif_colors));
+        content.push_str(&format!("gif_dither = {}\n", settings.gif_dither));
+        content.push_str(&format!("motion_blur = {}\n", settings.motion_blur));
+        content.push_str(&format!(
+            "motion_blur_samples = {}\n",
+            settings.motion_blur_samples
+        ));
+        content.push_str(&format!("shutter_angle = {}\n", settings.shutter_angle));
         content.push('\n');
 
+        // [camera_path] 部分，仅在设置了路径点时写出
+        let path = &settings.camera_path;
+        if !path.points.is_empty() {
+            let format_points = |points: &[nalgebra::Point3<f32>]| {
+                points
+                    .iter()
+                    .map(|p| format!("\"{},{},{}\"", p.x, p.y, p.z))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            content.push_str("[camera_path]\n");
+            content.push_str(&format!("type = \"{}\"\n", path.spline.name()));
+            content.push_str(&format!("points = [{}]\n", format_points(&path.points)));
+            content.push_str(&format!("closed = {}\n", path.closed));
+            if let Some(target) = path.target {
+                content.push_str(&format!(
+                    "target = \"{},{},{}\"\n",
+                    target.x, target.y, target.z
+                ));
+            }
+            if !path.target_points.is_empty() {
+                content.push_str(&format!(
+                    "target_points = [{}]\n",
+                    format_points(&path.target_points)
+                ));
+            }
+            content.push_str(&format!("duration = {}\n", path.duration));
+            content.push_str(&format!("constant_speed = {}\n", path.constant_speed));
+            if !path.roll.is_empty() {
+                let roll: Vec<String> = path.roll.iter().map(|r| r.to_string()).collect();
+                content.push_str(&format!("roll = [{}]\n", roll.join(", ")));
+            }
+            content.push('\n');
+        }
+
         // [shadow] 部分
         content.push_str("# 阴影配置\n");
         content.push_str("[shadow]\n");
@@ -735,9 +1906,13 @@ impl TomlConfigLoader {
         content.push_str("shadow_bias = ");
         content.push_str(&settings.shadow_bias.to_string());
         content.push('\n');
-        content.push_str("shadow_distance = ");
-        content.push_str(&settings.shadow_distance.to_string());
-        content.push('\n');
+        if settings.shadow_distance_explicit {
+            content.push_str("shadow_distance = ");
+            content.push_str(&settings.shadow_distance.to_string());
+            content.push('\n');
+        } else {
+            content.push_str("# shadow_distance = 20.0  # 未设置时按模型包围盒自适应\n");
+        }
         content.push_str("enable_pcf = ");
         content.push_str(&settings.enable_pcf.to_string());
         content.push('\n');
@@ -751,6 +1926,69 @@ impl TomlConfigLoader {
         content.push_str(&settings.pcf_sigma.to_string());
         content.push('\n');
 
+        // [[keyframe]] 数组，子表必须紧跟在所属关键帧之后
+        for keyframe in &settings.keyframes {
+            content.push_str("\n[[keyframe]]\n");
+            content.push_str(&format!("time = {}\n", keyframe.time));
+            content.push_str(&format!(
+                "interpolation = \"{}\"\n",
+                keyframe.interpolation.name()
+            ));
+            if let Interpolation::Bezier([x1, y1, x2, y2]) = keyframe.interpolation {
+                content.push_str(&format!("bezier = \"{x1},{y1},{x2},{y2}\"\n"));
+            }
+
+            let mut light_indices = Vec::new();
+            let mut material_targets = Vec::new();
+            for (channel, value) in &keyframe.values {
+                match channel {
+                    Channel::Light(index, _) => {
+                        if !light_indices.contains(index) {
+                            light_indices.push(*index);
+                        }
+                    }
+                    Channel::Material(target, _) => {
+                        if !material_targets.contains(target) {
+                            material_targets.push(target.clone());
+                        }
+                    }
+                    // 每个变形通道只有一个值，写在下面各自的子表中
+                    Channel::Morph(_) => {}
+                    _ => content.push_str(&format!("{} = {}\n", channel.key(), value.to_toml())),
+                }
+            }
+
+            for index in light_indices {
+                content.push_str("[[keyframe.light]]\n");
+                content.push_str(&format!("index = {index}\n"));
+                for (channel, value) in &keyframe.values {
+                    if matches!(channel, Channel::Light(i, _) if *i == index) {
+                        content.push_str(&format!("{} = {}\n", channel.key(), value.to_toml()));
+                    }
+                }
+            }
+
+            for target in material_targets {
+                content.push_str("[[keyframe.material]]\n");
+                if let Some(target) = &target {
+                    content.push_str(&format!("target = \"{target}\"\n"));
+                }
+                for (channel, value) in &keyframe.values {
+                    if matches!(channel, Channel::Material(t, _) if *t == target) {
+                        content.push_str(&format!("{} = {}\n", channel.key(), value.to_toml()));
+                    }
+                }
+            }
+
+            for (channel, value) in &keyframe.values {
+                if let Channel::Morph(name) = channel {
+                    content.push_str("[[keyframe.morph]]\n");
+                    content.push_str(&format!("name = \"{name}\"\n"));
+                    content.push_str(&format!("{} = {}\n", channel.key(), value.to_toml()));
+                }
+            }
+        }
+
         Ok(content)
     }
 }
@@ -1,5 +1,7 @@
+pub mod bvh_loader;
 pub mod config_loader;
 pub mod model_loader;
 pub mod obj_loader;
+pub mod obj_sequence;
 pub mod render_settings;
 pub mod simple_cli;
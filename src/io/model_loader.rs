@@ -1,37 +1,44 @@
-use crate::io::obj_loader::load_obj_model;
-use crate::io::render_settings::RenderSettings;
-use crate::material_system::materials::Model;
+use crate::geometry::mesh_ops::{apply_mesh_operations, generate_lods, triangle_count};
+use crate::geometry::morph::MorphTarget;
+use crate::io::bvh_loader::load_bvh;
+use crate::io::obj_loader::{SharedTextures, load_obj_model};
+use crate::io::render_settings::{MAX_LOD_LEVELS, NormalizeMode, RenderSettings, parse_vec3};
+use crate::material_system::materials::{Mesh, Model};
 use crate::scene::scene_utils::Scene;
 use crate::utils::model_utils::normalize_and_center_model;
-use log::{debug, info};
-use std::path::Path;
+use log::{debug, info, warn};
+use nalgebra::{Matrix4, Vector3};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 /// 模型加载器
 pub struct ModelLoader;
 
 impl ModelLoader {
-    /// 主要功能：加载OBJ模型并创建场景
-    pub fn load_and_create_scene(
-        obj_path: &str,
-        settings: &RenderSettings,
-    ) -> Result<(Scene, Model), String> {
-        info!("加载模型：{obj_path}");
-        let load_start = Instant::now();
-
-        // 检查文件存在
-        if !Path::new(obj_path).exists() {
-            return Err(format!("输入的 OBJ 文件未找到：{obj_path}"));
+    /// 主要功能：加载模型并创建场景
+    ///
+    /// 配置了 `[[object]]` 程序化图元时使用图元生成模型，否则加载 OBJ 文件。
+    /// 归一化方式不是 `Unit` 时，会按模型的真实包围盒调整 `settings` 中未显式设置的地面高度和阴影距离；
+    /// 是 `Unit` 时这两项恢复默认值。
+    pub fn load_and_create_scene(settings: &mut RenderSettings) -> Result<(Scene, Model), String> {
+        if !settings.primitives.is_empty()
+            && let Some(obj) = &settings.obj
+        {
+            warn!(
+                "同时指定了 OBJ 文件 '{obj}' 和 {} 个 [[object]] 图元，只渲染图元，OBJ 被忽略",
+                settings.primitives.len()
+            );
         }
 
-        // 加载模型数据
-        let mut model = load_obj_model(obj_path, settings)?;
-        debug!("模型加载耗时 {:?}", load_start.elapsed());
+        // OBJ 序列以首帧建立场景
+        let obj_path = settings.obj_frame_path(settings.sequence_start);
+        let mut model = Self::load_model(settings, obj_path.as_deref())?;
 
         // 归一化模型
         debug!("归一化模型...");
         let norm_start_time = Instant::now();
-        let (original_center, scale_factor) = normalize_and_center_model(&mut model);
+        let (original_center, scale_factor) =
+            normalize_and_center_model(&mut model, &settings.object_normalize);
         debug!(
             "模型归一化耗时 {:?}，原始中心：{:.3?}，缩放系数：{:.3}",
             norm_start_time.elapsed(),
@@ -39,20 +46,243 @@ impl ModelLoader {
             scale_factor
         );
 
+        if settings.object_normalize == NormalizeMode::Unit {
+            settings.reset_model_bounds_adaptation();
+        }
+
         // 创建场景
         debug!("创建场景...");
-        let scene = Scene::new(model.clone(), settings)?;
+        let mut scene = Scene::new(model.clone(), settings)?;
+
+        // 保留真实尺寸时，地面和阴影范围跟随模型包围盒
+        if settings.object_normalize != NormalizeMode::Unit
+            && let Some((min, max)) = scene.object.world_bounds()
+        {
+            settings.adapt_to_model_bounds(&min, &max);
+            debug!(
+                "按包围盒自适应: 地面高度 {:.3}，阴影距离 {:.3}",
+                settings.ground_plane_height, settings.shadow_distance
+            );
+            scene.sync_ground(settings)?;
+        }
 
         Ok((scene, model))
     }
 
+    /// 加载模型并执行网格处理、变形目标、LOD 和骨架绑定，不做归一化
+    ///
+    /// 配置了程序化图元时忽略 `obj_path`。
+    pub fn load_model(settings: &RenderSettings, obj_path: Option<&str>) -> Result<Model, String> {
+        let load_start = Instant::now();
+
+        let mut model = if !settings.primitives.is_empty() {
+            info!("生成 {} 个程序化图元", settings.primitives.len());
+            Self::build_primitive_model(settings)?
+        } else if let Some(obj_path) = obj_path {
+            info!("加载模型：{obj_path}");
+            // 检查文件存在
+            if !Path::new(obj_path).exists() {
+                return Err(format!("输入的 OBJ 文件未找到：{obj_path}"));
+            }
+            load_obj_model(obj_path, settings)?
+        } else {
+            return Err("未指定 OBJ 文件路径或 [[object]] 图元".to_string());
+        };
+        debug!("模型加载耗时 {:?}", load_start.elapsed());
+
+        // 细分、简化、重算法线
+        apply_mesh_operations(&mut model, settings);
+
+        // 变形目标经过同样的网格处理后与原始网格逐顶点相减
+        if !settings.morph_targets.is_empty() {
+            Self::attach_morph_targets(&mut model, settings)?;
+        }
+
+        // 细节层级，在归一化之前生成以便与原始网格使用同一变换
+        if settings.enable_lod {
+            Self::build_lod_levels(&mut model, settings, obj_path)?;
+        }
+
+        // 骨架绑定在网格处理和 LOD 之后进行，所有层级都获得蒙皮权重
+        if let Some(bvh_path) = &settings.skeleton_bvh {
+            Self::bind_skeleton(&mut model, bvh_path, settings)?;
+        }
+
+        Ok(model)
+    }
+
+    /// 由程序化图元组成模型，每个图元是一个独立网格并拥有同名材质
+    fn build_primitive_model(settings: &RenderSettings) -> Result<Model, String> {
+        let shared_textures = SharedTextures::load(settings);
+        let mut model = Model {
+            meshes: Vec::with_capacity(settings.primitives.len()),
+            materials: Vec::with_capacity(settings.primitives.len()),
+            name: "primitives".to_string(),
+            lods: Vec::new(),
+            skeleton: None,
+            normalization: (Vector3::zeros(), 1.0),
+        };
+        for primitive in &settings.primitives {
+            let mut mesh = primitive.build_mesh()?;
+            mesh.material_id = model.materials.len();
+            debug!(
+                "图元 '{}' ({}): {} 个顶点, {} 个三角形",
+                primitive.name,
+                primitive.shape.kind(),
+                mesh.vertices.len(),
+                mesh.indices.len() / 3
+            );
+            model.meshes.push(mesh);
+
+            let mut material = shared_textures.default_material(settings.shading_model);
+            material.name = primitive.name.clone();
+            model.materials.push(material);
+        }
+        Ok(model)
+    }
+
+    /// 加载 BVH 骨架，按缩放和偏移对齐到模型坐标后自动计算所有网格的蒙皮权重
+    fn bind_skeleton(
+        model: &mut Model,
+        bvh_path: &str,
+        settings: &RenderSettings,
+    ) -> Result<(), String> {
+        let bind_start = Instant::now();
+        let mut skeleton = load_bvh(bvh_path)?;
+        let offset =
+            parse_vec3(&settings.skeleton_offset).map_err(|e| format!("无效的骨架偏移: {e}"))?;
+        skeleton.to_model =
+            Matrix4::new_translation(&offset) * Matrix4::new_scaling(settings.skeleton_scale);
+
+        for mesh in model
+            .all_meshes_mut()
+            .filter(|mesh| !mesh.vertices.is_empty())
+        {
+            skeleton.bind_mesh(mesh);
+        }
+        debug!(
+            "骨架绑定耗时 {:?}，{} 个关节",
+            bind_start.elapsed(),
+            skeleton.joints.len()
+        );
+        model.skeleton = Some(skeleton);
+        Ok(())
+    }
+
+    /// 加载 `[[morph_target]]` 指定的 OBJ，按网格顺序与主模型逐顶点配对得到偏移
+    ///
+    /// 细分和重算法线只取决于拓扑，对目标执行同样的处理后顶点仍一一对应；
+    /// 简化和 LOD 层级会改变拓扑，因此不能同时使用。
+    fn attach_morph_targets(model: &mut Model, settings: &RenderSettings) -> Result<(), String> {
+        if settings.decimate_target > 0 {
+            return Err("变形目标不能与网格简化同时使用".to_string());
+        }
+        if settings.enable_lod {
+            return Err("变形目标不能与 LOD 同时使用".to_string());
+        }
+        for source in &settings.morph_targets {
+            let mut target_model = load_obj_model(&source.obj, settings)
+                .map_err(|e| format!("加载变形目标 '{}' 失败: {e}", source.name))?;
+            apply_mesh_operations(&mut target_model, settings);
+            if target_model.meshes.len() != model.meshes.len() {
+                return Err(format!(
+                    "变形目标 '{}' 有 {} 个网格，主模型有 {} 个",
+                    source.name,
+                    target_model.meshes.len(),
+                    model.meshes.len()
+                ));
+            }
+            for (mesh, target_mesh) in model.meshes.iter_mut().zip(&target_model.meshes) {
+                let target = MorphTarget::from_meshes(&source.name, mesh, target_mesh)?;
+                mesh.morph_targets.push(target);
+            }
+            info!("加载变形目标 '{}': {}", source.name, source.obj);
+        }
+        Ok(())
+    }
+
+    /// 优先加载 OBJ 同目录下的 `<名称>_lod1.obj`、`<名称>_lod2.obj`…，没有时逐级简化生成
+    ///
+    /// LOD 文件中的网格按材质名（其次按网格名）绑定到原始模型的材质，不执行网格处理。
+    fn build_lod_levels(
+        model: &mut Model,
+        settings: &RenderSettings,
+        obj_path: Option<&str>,
+    ) -> Result<(), String> {
+        let lod_files = match obj_path {
+            Some(obj_path) if settings.primitives.is_empty() => Self::find_lod_files(obj_path),
+            _ => Vec::new(),
+        };
+        if lod_files.is_empty() {
+            generate_lods(model, settings.lod_levels, settings.lod_ratio);
+            return Ok(());
+        }
+
+        model.lods.clear();
+        for path in lod_files {
+            let lod_model = load_obj_model(&path, settings)?;
+            let meshes: Vec<Mesh> = lod_model
+                .meshes
+                .into_iter()
+                .map(|mut mesh| {
+                    let material_name = lod_model
+                        .materials
+                        .get(mesh.material_id)
+                        .map(|material| material.name.as_str());
+                    mesh.material_id = model
+                        .materials
+                        .iter()
+                        .position(|material| Some(material.name.as_str()) == material_name)
+                        .or_else(|| {
+                            model
+                                .meshes
+                                .iter()
+                                .find(|base| base.name == mesh.name)
+                                .map(|base| base.material_id)
+                        })
+                        .unwrap_or(0);
+                    mesh
+                })
+                .collect();
+            info!(
+                "加载 LOD{}: {:?}，{} 个三角形",
+                model.lods.len() + 1,
+                path,
+                meshes
+                    .iter()
+                    .map(|mesh| mesh.indices.len() / 3)
+                    .sum::<usize>()
+            );
+            model.lods.push(meshes);
+        }
+        debug!(
+            "LOD0: {} 个三角形，共 {} 个低细节层级",
+            triangle_count(model),
+            model.lods.len()
+        );
+        Ok(())
+    }
+
+    /// 从 1 开始连续查找存在的 LOD 文件
+    fn find_lod_files(obj_path: &str) -> Vec<PathBuf> {
+        let path = Path::new(obj_path);
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            return Vec::new();
+        };
+        let directory = path.parent().unwrap_or_else(|| Path::new("."));
+        (1..=MAX_LOD_LEVELS)
+            .map(|level| directory.join(format!("{stem}_lod{level}.obj")))
+            .take_while(|candidate| candidate.exists())
+            .collect()
+    }
+
     /// 验证资源
     pub fn validate_resources(settings: &RenderSettings) -> Result<(), String> {
         // 验证 OBJ 文件
-        if let Some(obj_path) = &settings.obj {
-            if !Path::new(obj_path).exists() {
-                return Err(format!("OBJ 文件不存在: {obj_path}"));
-            }
+        if let Some(obj_path) = settings.obj_frame_path(settings.sequence_start)
+            && !Path::new(&obj_path).exists()
+        {
+            return Err(format!("OBJ 文件不存在: {obj_path}"));
         }
 
         // 验证背景图片（如果启用）
@@ -66,13 +296,38 @@ impl ModelLoader {
             }
         }
 
-        // 验证纹理文件（如果指定）
-        if let Some(texture_path) = &settings.texture {
-            if !Path::new(texture_path).exists() {
-                return Err(format!("纹理文件不存在: {texture_path}"));
+        // 验证地形高度图（如果启用）
+        if settings.enable_terrain {
+            match &settings.terrain_heightmap {
+                Some(heightmap) if !Path::new(heightmap).exists() => {
+                    return Err(format!("地形高度图不存在: {heightmap}"));
+                }
+                None => return Err("启用了地形但未指定高度图".to_string()),
+                _ => {}
             }
         }
 
+        // 验证 BVH 动作文件（如果指定）
+        if let Some(bvh_path) = &settings.skeleton_bvh
+            && !Path::new(bvh_path).exists()
+        {
+            return Err(format!("BVH 文件不存在: {bvh_path}"));
+        }
+
+        // 验证变形目标文件
+        for target in &settings.morph_targets {
+            if !Path::new(&target.obj).exists() {
+                return Err(format!("变形目标 OBJ 文件不存在: {}", target.obj));
+            }
+        }
+
+        // 验证纹理文件（如果指定）
+        if let Some(texture_path) = &settings.texture
+            && !Path::new(texture_path).exists()
+        {
+            return Err(format!("纹理文件不存在: {texture_path}"));
+        }
+
         info!("所有资源验证通过");
         Ok(())
     }
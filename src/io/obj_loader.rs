@@ -1,19 +1,17 @@
 use crate::io::render_settings::RenderSettings;
 use crate::material_system::materials::{Material, MaterialType, Mesh, Model, Vertex};
-use crate::material_system::texture::Texture;
-use image::DynamicImage;
+use crate::material_system::texture::{ColorSpace, Texture, TextureOptions, WrapMode};
 use log::{debug, info, warn};
 use nalgebra::{Point3, Vector2, Vector3};
 use std::collections::HashMap;
 use std::path::Path;
-use std::sync::Arc;
 
 /// 生成平滑的顶点法线，通过平均面法线实现
 fn generate_smooth_vertex_normals(
     vertices: &[Point3<f32>],
     indices: &[u32],
 ) -> Result<Vec<Vector3<f32>>, String> {
-    if indices.len() % 3 != 0 {
+    if !indices.len().is_multiple_of(3) {
         return Err("三角形索引数量必须是3的倍数".to_string());
     }
     if vertices.is_empty() {
@@ -65,6 +63,386 @@ fn generate_smooth_vertex_normals(
     Ok(vertex_normals)
 }
 
+/// 加载 MTL 纹理语句（可带 `-o`/`-s`/`-clamp` 等选项）
+fn load_mtl_texture(
+    base_path: &Path,
+    statement: &str,
+    default_color_space: ColorSpace,
+) -> Option<Texture> {
+    let (tex_name, options) = TextureOptions::parse_statement(statement);
+    if tex_name.is_empty() {
+        warn!("纹理语句缺少文件名: '{statement}'");
+        return None;
+    }
+    let texture_path = base_path.join(&tex_name);
+    match Texture::from_file(&texture_path) {
+        Some(texture) => {
+            debug!("加载纹理 {texture_path:?}，选项: {options:?}");
+            Some(
+                texture
+                    .with_color_space(default_color_space)
+                    .with_options(&options),
+            )
+        }
+        None => {
+            warn!("无法加载纹理 '{texture_path:?}'，使用默认颜色");
+            None
+        }
+    }
+}
+
+/// 读取 MTL 中 tobj 未识别的参数（如 `Pr`、`Pm`、`Ke`、`Tr`），忽略行尾注释
+fn unknown_mtl_floats(mat: &tobj::Material, key: &str) -> Option<Vec<f32>> {
+    let raw = mat.unknown_param.get(key)?;
+    let values: Vec<f32> = raw
+        .split('#')
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map_while(|t| t.parse::<f32>().ok())
+        .collect();
+    if values.is_empty() {
+        warn!("材质 '{}' 的参数 {key} 无法解析: '{raw}'", mat.name);
+        None
+    } else {
+        Some(values)
+    }
+}
+
+fn unknown_mtl_float(mat: &tobj::Material, key: &str) -> Option<f32> {
+    unknown_mtl_floats(mat, key).map(|v| v[0])
+}
+
+fn unknown_mtl_color(mat: &tobj::Material, key: &str) -> Option<Vector3<f32>> {
+    unknown_mtl_floats(mat, key).map(|v| match v.as_slice() {
+        [r, g, b, ..] => Vector3::new(*r, *g, *b),
+        [gray, ..] => Vector3::new(*gray, *gray, *gray),
+        [] => Vector3::zeros(),
+    })
+}
+
+/// 将 MTL 材质映射为统一的 `Material`
+///
+/// | MTL            | Material 字段                | 说明                                           |
+/// |----------------|------------------------------|------------------------------------------------|
+/// | `Kd`           | `base_color`                 | PBR 中作为 albedo                              |
+/// | `Ka`           | `ambient_factor`             | 全零（Blender 默认导出）视为未设置，回退 Kd*0.3 |
+/// | `Ks`           | `specular`                   | 仅 Phong 使用                                  |
+/// | `Ns`           | `shininess` / `roughness`    | 无 `Pr` 时仅在保留 MTL 参数模式下推导 roughness |
+/// | `d` / `Tr`     | `alpha`                      | `d` 优先，否则 alpha = 1 - Tr                  |
+/// | `Ni`           | `ior`                        | 决定 PBR 电介质 F0，<= 1.0 视为未设置          |
+/// | `illum`        | `illumination_model`         | 0 无光照明暗，1 仅漫反射，>= 2 含高光          |
+/// | `Ke`           | `emissive`                   |                                                |
+/// | `Pr` / `Pm`    | `roughness` / `metallic`     | PBR 扩展参数                                   |
+/// | `Pc` / `Pcr`   | `clearcoat` / `clearcoat_roughness` | 清漆层                                  |
+/// | `Ps`           | `sheen_color`                | 单值时为灰度                                   |
+/// | `aniso` / `anisor` | `anisotropy` / `anisotropy_rotation` | anisor 取值 0-1 对应 0-360 度       |
+fn material_from_mtl(
+    mat: &tobj::Material,
+    material_type: MaterialType,
+    texture: Option<Texture>,
+    alpha_texture: Option<Texture>,
+) -> Material {
+    let defaults = Material::default(material_type);
+    let base_color = mat
+        .diffuse
+        .map(Vector3::from)
+        .unwrap_or(defaults.base_color);
+    let shininess = mat.shininess.unwrap_or(defaults.shininess);
+
+    let explicit_ambient = mat.ambient.map(Vector3::from).filter(|ka| ka.max() > 0.0);
+
+    let alpha = mat
+        .dissolve
+        .or_else(|| unknown_mtl_float(mat, "Tr").map(|tr| 1.0 - tr))
+        .unwrap_or(1.0)
+        .clamp(0.0, 1.0);
+
+    let explicit_roughness = unknown_mtl_float(mat, "Pr");
+    let roughness = explicit_roughness
+        .unwrap_or(defaults.roughness)
+        .clamp(0.0, 1.0);
+    let metallic = unknown_mtl_float(mat, "Pm")
+        .unwrap_or(defaults.metallic)
+        .clamp(0.0, 1.0);
+
+    let clearcoat = unknown_mtl_float(mat, "Pc")
+        .unwrap_or(defaults.clearcoat)
+        .clamp(0.0, 1.0);
+    let clearcoat_roughness = unknown_mtl_float(mat, "Pcr")
+        .unwrap_or(defaults.clearcoat_roughness)
+        .clamp(0.0, 1.0);
+    let sheen_color = unknown_mtl_color(mat, "Ps")
+        .map(|ps| ps.map(|c| c.max(0.0)))
+        .unwrap_or(defaults.sheen_color);
+    let anisotropy = unknown_mtl_float(mat, "aniso")
+        .unwrap_or(defaults.anisotropy)
+        .clamp(-1.0, 1.0);
+    // anisor 以 0-1 表示一整圈
+    let anisotropy_rotation = unknown_mtl_float(mat, "anisor")
+        .map(|rotation| rotation * 360.0)
+        .unwrap_or(defaults.anisotropy_rotation);
+
+    let ior = match mat.optical_density {
+        Some(ni) if ni > 1.0 => ni,
+        _ => defaults.ior,
+    };
+
+    Material {
+        name: mat.name.clone(),
+        base_color,
+        alpha,
+        texture,
+        alpha_texture,
+        metallic,
+        roughness,
+        clearcoat,
+        clearcoat_roughness,
+        sheen_color,
+        anisotropy,
+        anisotropy_rotation,
+        specular: mat.specular.map(Vector3::from).unwrap_or(defaults.specular),
+        shininess,
+        emissive: unknown_mtl_color(mat, "Ke").unwrap_or(defaults.emissive),
+        ambient_factor: explicit_ambient.unwrap_or(base_color * 0.3),
+        ambient_from_mtl: explicit_ambient.is_some(),
+        roughness_from_shininess: explicit_roughness.is_none() && mat.shininess.is_some(),
+        ior,
+        illumination_model: mat
+            .illumination_model
+            .unwrap_or(defaults.illumination_model),
+        ..defaults
+    }
+}
+
+/// 同一材质下的线段与点图元（索引指向 `ObjPrimitives::positions`）
+#[derive(Debug, Default)]
+struct PrimitiveGroup {
+    material_name: Option<String>,
+    lines: Vec<[u32; 2]>,
+    points: Vec<u32>,
+}
+
+/// OBJ 中的 `l`/`p` 图元
+///
+/// tobj 会把多于两个顶点的 `l` 当作多边形三角化，并且不解析 `p`，
+/// 因此在交给 tobj 之前先把这两类语句单独取出。
+#[derive(Debug, Default)]
+struct ObjPrimitives {
+    positions: Vec<Point3<f32>>,
+    groups: Vec<PrimitiveGroup>,
+    has_faces: bool,
+}
+
+impl ObjPrimitives {
+    /// 拆分 OBJ 文本，返回去掉 `l`/`p` 语句后的内容与提取出的图元
+    fn split_from(content: &str) -> (String, Self) {
+        let mut filtered = String::with_capacity(content.len());
+        let mut primitives = Self::default();
+        let mut current_material: Option<String> = None;
+
+        for line in content.lines() {
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("v") => {
+                    let coords: Vec<f32> = tokens.take(3).filter_map(|t| t.parse().ok()).collect();
+                    if let [x, y, z] = coords[..] {
+                        primitives.positions.push(Point3::new(x, y, z));
+                    }
+                }
+                Some("f") => primitives.has_faces = true,
+                Some("usemtl") => current_material = tokens.next().map(str::to_string),
+                Some(keyword @ ("l" | "p")) => {
+                    let indices: Vec<u32> = tokens
+                        .filter_map(|t| Self::resolve_index(t, primitives.positions.len()))
+                        .collect();
+                    let group = primitives.group_for(&current_material);
+                    if keyword == "l" {
+                        // 折线拆分为相邻顶点组成的线段
+                        group
+                            .lines
+                            .extend(indices.windows(2).map(|pair| [pair[0], pair[1]]));
+                    } else {
+                        group.points.extend(indices);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            filtered.push_str(line);
+            filtered.push('\n');
+        }
+
+        // 只有顶点的文件（如激光扫描点云）视为点云
+        let has_primitives = primitives
+            .groups
+            .iter()
+            .any(|g| !g.lines.is_empty() || !g.points.is_empty());
+        if !primitives.has_faces && !has_primitives && !primitives.positions.is_empty() {
+            let count = primitives.positions.len() as u32;
+            primitives.group_for(&None).points.extend(0..count);
+        }
+
+        (filtered, primitives)
+    }
+
+    /// 解析 `v/vt` 形式的索引，支持负数相对索引，返回从 0 开始的位置索引
+    fn resolve_index(token: &str, position_count: usize) -> Option<u32> {
+        let index: i64 = token.split('/').next()?.parse().ok()?;
+        let resolved = if index < 0 {
+            position_count as i64 + index
+        } else {
+            index - 1
+        };
+        (0..position_count as i64)
+            .contains(&resolved)
+            .then_some(resolved as u32)
+    }
+
+    fn group_for(&mut self, material_name: &Option<String>) -> &mut PrimitiveGroup {
+        let index = match self
+            .groups
+            .iter()
+            .position(|g| &g.material_name == material_name)
+        {
+            Some(index) => index,
+            None => {
+                self.groups.push(PrimitiveGroup {
+                    material_name: material_name.clone(),
+                    ..Default::default()
+                });
+                self.groups.len() - 1
+            }
+        };
+        &mut self.groups[index]
+    }
+
+    /// 为每个材质分组生成只含线段/点的网格，顶点按引用重新编号
+    fn into_meshes(self, materials: &[Material], basename: &str) -> Vec<Mesh> {
+        let mut meshes = Vec::new();
+        for group in self.groups {
+            if group.lines.is_empty() && group.points.is_empty() {
+                continue;
+            }
+            let mut remap: HashMap<u32, u32> = HashMap::new();
+            let mut vertices = Vec::new();
+            let mut local_index = |index: u32| {
+                *remap.entry(index).or_insert_with(|| {
+                    vertices.push(Vertex {
+                        position: self.positions[index as usize],
+                        normal: Vector3::y(),
+                        texcoord: Vector2::zeros(),
+                    });
+                    (vertices.len() - 1) as u32
+                })
+            };
+            let lines: Vec<u32> = group
+                .lines
+                .iter()
+                .flat_map(|&[a, b]| [local_index(a), local_index(b)])
+                .collect();
+            let points: Vec<u32> = group.points.iter().map(|&i| local_index(i)).collect();
+
+            let material_id = group
+                .material_name
+                .as_ref()
+                .and_then(|name| materials.iter().position(|m| &m.name == name))
+                .unwrap_or(0);
+            let suffix = if lines.is_empty() { "points" } else { "lines" };
+            debug!(
+                "图元网格: {} 条线段, {} 个点, 材质 ID: {material_id}",
+                lines.len() / 2,
+                points.len()
+            );
+            meshes.push(Mesh {
+                vertices,
+                indices: Vec::new(),
+                lines,
+                points,
+                material_id,
+                name: format!("{basename}_{suffix}"),
+                skin_weights: Vec::new(),
+                morph_targets: Vec::new(),
+            });
+        }
+        meshes
+    }
+}
+
+/// 与 MTL 无关、所有材质共用的纹理
+#[derive(Debug, Clone)]
+pub struct SharedTextures {
+    /// 命令行/配置指定的纹理，覆盖 MTL 设置
+    pub diffuse: Option<Texture>,
+    /// 卡通着色渐变纹理
+    pub ramp: Option<Texture>,
+    /// Matcap 纹理
+    pub matcap: Option<Texture>,
+    /// 已加载的渐变纹理和 matcap 纹理路径
+    lookup_paths: (Option<String>, Option<String>),
+}
+
+impl SharedTextures {
+    pub fn load(settings: &RenderSettings) -> Self {
+        let diffuse = settings.texture.as_ref().map(|tex_path_str| {
+            let tex_path = Path::new(tex_path_str);
+            debug!("使用命令行指定的纹理: {tex_path:?}");
+            Texture::from_file(tex_path).unwrap_or_else(|| {
+                warn!("无法加载命令行指定的纹理，使用默认颜色");
+                Texture::fallback()
+            })
+        });
+        Self {
+            diffuse,
+            ..Self::load_lookups(settings)
+        }
+    }
+
+    /// 只加载风格化着色的查找纹理，供不使用全局漫反射纹理的地形和地面
+    pub fn load_lookups(settings: &RenderSettings) -> Self {
+        // 风格化着色使用的查找纹理，数据均视为颜色（sRGB）
+        let load_lookup = |path: &Option<String>| {
+            path.as_deref().and_then(|path| {
+                Texture::from_file(path).map(|tex| Texture {
+                    wrap_mode: WrapMode::Clamp,
+                    ..tex
+                })
+            })
+        };
+
+        Self {
+            diffuse: None,
+            ramp: load_lookup(&settings.toon_ramp_texture),
+            matcap: load_lookup(&settings.matcap_texture),
+            lookup_paths: (
+                settings.toon_ramp_texture.clone(),
+                settings.matcap_texture.clone(),
+            ),
+        }
+    }
+
+    /// 查找纹理路径变化时重新加载
+    pub fn sync_lookups(&mut self, settings: &RenderSettings) {
+        if self.lookup_paths.0 != settings.toon_ramp_texture
+            || self.lookup_paths.1 != settings.matcap_texture
+        {
+            *self = Self {
+                diffuse: self.diffuse.take(),
+                ..Self::load_lookups(settings)
+            };
+        }
+    }
+
+    /// 没有 MTL 材质时使用的默认材质
+    pub fn default_material(&self, material_type: MaterialType) -> Material {
+        let mut material = Material::default(material_type);
+        material.texture = self.diffuse.clone();
+        material.ramp_texture = self.ramp.clone();
+        material.matcap_texture = self.matcap.clone();
+        material
+    }
+}
+
 fn get_basename_from_path(path: &Path) -> String {
     path.file_stem()
         .map(|s| s.to_string_lossy().into_owned())
@@ -82,20 +460,7 @@ pub fn load_obj_model<P: AsRef<Path>>(
     let obj_basename = get_basename_from_path(obj_path_ref);
     let base_path = obj_path_ref.parent().unwrap_or_else(|| Path::new("."));
 
-    let cli_texture: Option<Texture> = if let Some(tex_path_str) = &settings.texture {
-        let tex_path = Path::new(tex_path_str);
-        debug!("使用命令行指定的纹理: {tex_path:?}");
-        Some(Texture::from_file(tex_path).unwrap_or_else(|| {
-            warn!("无法加载命令行指定的纹理，使用默认颜色");
-            Texture {
-                image: Arc::new(DynamicImage::new_rgb8(1, 1)),
-                width: 1,
-                height: 1,
-            }
-        }))
-    } else {
-        None
-    };
+    let shared_textures = SharedTextures::load(settings);
 
     let load_options = tobj::LoadOptions {
         triangulate: true,
@@ -104,8 +469,17 @@ pub fn load_obj_model<P: AsRef<Path>>(
         ignore_lines: true,
     };
 
-    let (models, materials_result) =
-        tobj::load_obj(obj_path_ref, &load_options).map_err(|e| format!("加载 OBJ 失败: {e}"))?;
+    // 旧导出器的注释和组名可能不是 UTF-8，无效字节替换后不影响几何数据
+    let bytes = std::fs::read(obj_path_ref).map_err(|e| format!("读取 OBJ 失败: {e}"))?;
+    let content = String::from_utf8_lossy(&bytes);
+    let (filtered_content, primitives) = ObjPrimitives::split_from(&content);
+
+    let (models, materials_result) = tobj::load_obj_buf(
+        &mut filtered_content.as_bytes(),
+        &load_options,
+        |mtl_path| tobj::load_mtl(base_path.join(mtl_path)),
+    )
+    .map_err(|e| format!("加载 OBJ 失败: {e}"))?;
 
     let mut loaded_materials: Vec<Material> = match materials_result {
         Ok(mats) => {
@@ -114,42 +488,25 @@ pub fn load_obj_model<P: AsRef<Path>>(
                 mats.into_iter()
                     .map(|mat| {
                         // 只加载图片纹理
-                        let texture = if let Some(cli_tex) = &cli_texture {
+                        let texture = if let Some(cli_tex) = &shared_textures.diffuse {
                             Some(cli_tex.clone())
-                        } else if let Some(tex_name) = mat.diffuse_texture {
-                            let texture_path = base_path.join(&tex_name);
-                            Some(Texture::from_file(&texture_path).unwrap_or_else(|| {
-                                warn!("无法加载纹理 '{texture_path:?}'，使用默认颜色");
-                                Texture {
-                                    image: Arc::new(DynamicImage::new_rgb8(1, 1)),
-                                    width: 1,
-                                    height: 1,
-                                }
-                            }))
                         } else {
-                            None
+                            mat.diffuse_texture.as_deref().map(|statement| {
+                                load_mtl_texture(base_path, statement, ColorSpace::Srgb)
+                                    .unwrap_or_else(Texture::fallback)
+                            })
                         };
 
-                        Material {
-                            material_type: if settings.use_pbr {
-                                MaterialType::PBR
-                            } else {
-                                MaterialType::Phong
-                            },
-                            base_color: Vector3::from(mat.diffuse.unwrap_or([0.8, 0.8, 0.8])),
-                            alpha: 1.0,
-                            texture,
-                            metallic: 0.0,
-                            roughness: 0.5,
-                            ambient_occlusion: 1.0,
-                            specular: Vector3::from(mat.specular.unwrap_or([0.5, 0.5, 0.5])),
-                            shininess: mat.shininess.unwrap_or(32.0),
-                            diffuse_intensity: 1.0,
-                            specular_intensity: 1.0,
-                            emissive: Vector3::zeros(),
-                            ambient_factor: Vector3::from(mat.diffuse.unwrap_or([0.8, 0.8, 0.8]))
-                                * 0.3,
-                        }
+                        // 透明度遮罩（map_d）是数据贴图，不做 gamma 解码
+                        let alpha_texture = mat.dissolve_texture.as_deref().and_then(|statement| {
+                            load_mtl_texture(base_path, statement, ColorSpace::Linear)
+                        });
+
+                        let mut material =
+                            material_from_mtl(&mat, settings.shading_model, texture, alpha_texture);
+                        material.ramp_texture = shared_textures.ramp.clone();
+                        material.matcap_texture = shared_textures.matcap.clone();
+                        material
                     })
                     .collect()
             } else {
@@ -164,19 +521,7 @@ pub fn load_obj_model<P: AsRef<Path>>(
     };
 
     if loaded_materials.is_empty() {
-        let default_type = if settings.use_pbr {
-            MaterialType::PBR
-        } else {
-            MaterialType::Phong
-        };
-        let mut default_mat = Material::default(default_type);
-
-        if let Some(texture) = cli_texture {
-            default_mat.texture = Some(texture);
-        } else {
-            default_mat.texture = None;
-        }
-        loaded_materials.push(default_mat);
+        loaded_materials.push(shared_textures.default_material(settings.shading_model));
     }
 
     let mut loaded_meshes: Vec<Mesh> = Vec::with_capacity(models.len());
@@ -327,8 +672,12 @@ pub fn load_obj_model<P: AsRef<Path>>(
         loaded_meshes.push(Mesh {
             vertices,
             indices: final_indices,
+            lines: Vec::new(),
+            points: Vec::new(),
             material_id: final_material_id,
             name: mesh_name.clone(),
+            skin_weights: Vec::new(),
+            morph_targets: Vec::new(),
         });
 
         debug!(
@@ -340,6 +689,8 @@ pub fn load_obj_model<P: AsRef<Path>>(
         );
     }
 
+    loaded_meshes.extend(primitives.into_meshes(&loaded_materials, &obj_basename));
+
     if loaded_meshes.is_empty() {
         return Err("OBJ 文件中没有可处理的网格".to_string());
     }
@@ -348,6 +699,9 @@ pub fn load_obj_model<P: AsRef<Path>>(
         meshes: loaded_meshes,
         materials: loaded_materials,
         name: obj_basename,
+        lods: Vec::new(),
+        skeleton: None,
+        normalization: (Vector3::zeros(), 1.0),
     };
 
     info!("创建模型 '{}' 成功", model.name);
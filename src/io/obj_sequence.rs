@@ -0,0 +1,187 @@
+use crate::io::model_loader::ModelLoader;
+use crate::io::render_settings::RenderSettings;
+use crate::material_system::materials::Model;
+use crate::utils::model_utils::apply_normalization;
+use log::{debug, info};
+use nalgebra::Vector3;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// 当前帧之后在后台预读的帧数
+const PREFETCH_FRAMES: usize = 2;
+/// 缓存中保留的已加载帧数
+const CACHE_FRAMES: usize = 4;
+
+/// 帧号占位符的位置和宽度：`%d` 宽度为 0，`%04d` 宽度为 4
+fn find_placeholder(pattern: &str) -> Option<(usize, usize, usize)> {
+    pattern.match_indices('%').find_map(|(start, _)| {
+        let rest = &pattern[start + 1..];
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if !rest[digits..].starts_with('d') {
+            return None;
+        }
+        let width = rest[..digits].parse().unwrap_or(0);
+        Some((start, start + 1 + digits + 1, width))
+    })
+}
+
+/// 路径中含有 printf 风格的帧号占位符（`%d`、`%04d`）
+pub fn is_sequence_pattern(path: &str) -> bool {
+    find_placeholder(path).is_some()
+}
+
+/// 用帧号替换占位符，`%04d` 补零到 4 位；不含占位符时原样返回
+pub fn format_frame_path(pattern: &str, frame: usize) -> String {
+    match find_placeholder(pattern) {
+        Some((start, end, width)) => {
+            format!("{}{frame:0width$}{}", &pattern[..start], &pattern[end..])
+        }
+        None => pattern.to_string(),
+    }
+}
+
+/// 序列包含的帧号：从 `sequence_start` 到 `sequence_end`，未指定末帧时读到第一个缺失的文件为止
+pub fn sequence_frames(settings: &RenderSettings) -> Result<Vec<usize>, String> {
+    let pattern = settings.obj.as_deref().ok_or("未指定 OBJ 序列路径")?;
+    let frames: Vec<usize> = match settings.sequence_end {
+        Some(end) => (settings.sequence_start..=end).collect(),
+        None => (settings.sequence_start..)
+            .take_while(|&frame| Path::new(&format_frame_path(pattern, frame)).exists())
+            .collect(),
+    };
+    if let Some(missing) = frames
+        .iter()
+        .map(|&frame| format_frame_path(pattern, frame))
+        .find(|path| !Path::new(path).exists())
+    {
+        return Err(format!("OBJ 序列缺少文件: {missing}"));
+    }
+    if frames.is_empty() {
+        return Err(format!(
+            "OBJ 序列 '{pattern}' 从第 {} 帧起没有文件",
+            settings.sequence_start
+        ));
+    }
+    Ok(frames)
+}
+
+/// 按帧加载 OBJ 序列：最近用过的帧留在缓存中，并在后台线程预读后续帧
+///
+/// 所有帧使用首帧的归一化中心和缩放，相机、光照和地面在整个序列中保持一致。
+pub struct SequenceLoader {
+    settings: Arc<RenderSettings>,
+    frames: Vec<usize>,
+    center: Vector3<f32>,
+    scale_factor: f32,
+    cache: VecDeque<(usize, Model)>,
+    pending: HashMap<usize, JoinHandle<Result<Model, String>>>,
+}
+
+impl SequenceLoader {
+    /// 以已加载并归一化的首帧建立序列，后续帧沿用首帧的归一化参数
+    pub fn new(settings: &RenderSettings, first_frame: &Model) -> Result<Self, String> {
+        let frames = sequence_frames(settings)?;
+        info!(
+            "OBJ 序列 '{}': 第 {} - {} 帧，共 {} 帧",
+            settings.obj.as_deref().unwrap_or_default(),
+            frames[0],
+            frames[frames.len() - 1],
+            frames.len()
+        );
+
+        let (center, scale_factor) = first_frame.normalization;
+        let mut cache = VecDeque::with_capacity(CACHE_FRAMES);
+        cache.push_back((0, first_frame.clone()));
+        Ok(Self {
+            settings: Arc::new(settings.clone()),
+            frames,
+            center,
+            scale_factor,
+            cache,
+            pending: HashMap::new(),
+        })
+    }
+
+    /// 序列的帧数
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// 第 `index` 帧对应的文件帧号
+    pub fn frame_number(&self, index: usize) -> usize {
+        self.frames[index]
+    }
+
+    /// 取得第 `index` 帧（从 0 开始）的模型，并在后台预读 `upcoming` 中最近的几帧
+    ///
+    /// `upcoming` 是之后按顺序要渲染的帧索引，只预读真正会用到的帧。
+    pub fn load(&mut self, index: usize, upcoming: &[usize]) -> Result<&Model, String> {
+        if index >= self.frames.len() {
+            return Err(format!(
+                "序列帧索引 {index} 超出范围（共 {} 帧）",
+                self.frames.len()
+            ));
+        }
+
+        if !self.cache.iter().any(|(cached, _)| *cached == index) {
+            let model = match self.pending.remove(&index) {
+                Some(handle) => {
+                    let frame = self.frames[index];
+                    handle
+                        .join()
+                        .map_err(|_| format!("第 {frame} 帧的加载线程异常退出"))??
+                }
+                None => {
+                    debug!("序列帧 {} 未预读，同步加载", self.frames[index]);
+                    self.load_frame(index)()?
+                }
+            };
+            if self.cache.len() >= CACHE_FRAMES {
+                self.cache.pop_front();
+            }
+            self.cache.push_back((index, model));
+        }
+
+        for &next in upcoming.iter().take(PREFETCH_FRAMES) {
+            if next < self.frames.len()
+                && !self.pending.contains_key(&next)
+                && !self.cache.iter().any(|(cached, _)| *cached == next)
+            {
+                self.pending
+                    .insert(next, thread::spawn(self.load_frame(next)));
+            }
+        }
+
+        self.cache
+            .iter()
+            .find(|(cached, _)| *cached == index)
+            .map(|(_, model)| model)
+            .ok_or_else(|| format!("第 {} 帧不在缓存中", self.frames[index]))
+    }
+
+    /// 加载并归一化一帧的闭包，可在后台线程中执行
+    fn load_frame(&self, index: usize) -> impl FnOnce() -> Result<Model, String> + Send + 'static {
+        let settings = Arc::clone(&self.settings);
+        let path = format_frame_path(
+            settings.obj.as_deref().unwrap_or_default(),
+            self.frames[index],
+        );
+        let (center, scale_factor) = (self.center, self.scale_factor);
+        move || {
+            let mut model = ModelLoader::load_model(&settings, Some(&path))?;
+            apply_normalization(&mut model, &center, scale_factor);
+            Ok(model)
+        }
+    }
+}
+
+impl Drop for SequenceLoader {
+    /// 等待尚未取用的预读结束，后台加载线程不会比加载器存活得更久
+    fn drop(&mut self) {
+        for (_, handle) in self.pending.drain() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -1,4 +1,11 @@
+use crate::geometry::camera::LensParams;
+use crate::geometry::morph::MorphTargetSource;
+use crate::geometry::primitives::PrimitiveObject;
+use crate::io::obj_sequence::{format_frame_path, is_sequence_pattern};
 use crate::material_system::light::Light;
+use crate::material_system::materials::{MaterialOverride, MaterialType};
+use crate::scene::camera_path::CameraPath;
+use crate::scene::timeline::{Keyframe, Timeline};
 use log::warn;
 use nalgebra::{Point3, Vector3};
 
@@ -8,9 +15,72 @@ pub enum AnimationType {
     #[default]
     CameraOrbit,
     ObjectLocalRotation,
+    /// 按 `[[keyframe]]` 时间线播放
+    Keyframes,
+    /// 相机沿 `[camera_path]` 样条移动，同时播放已有的关键帧
+    CameraPath,
     None,
 }
 
+impl AnimationType {
+    /// 按绝对时间采样的动画（时间线），而非逐帧累加旋转
+    pub fn is_timeline(&self) -> bool {
+        matches!(self, Self::Keyframes | Self::CameraPath)
+    }
+}
+
+/// 动画导出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VideoFormat {
+    /// H.264 视频，需要系统安装 ffmpeg
+    #[default]
+    Mp4,
+    /// 调色板量化的 GIF 动画
+    Gif,
+    /// 无损的动画 PNG
+    Apng,
+    /// 无损的动画 WebP
+    WebP,
+}
+
+impl VideoFormat {
+    pub const ALL: [Self; 4] = [Self::Mp4, Self::Gif, Self::Apng, Self::WebP];
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "mp4" => Ok(Self::Mp4),
+            "gif" => Ok(Self::Gif),
+            "apng" => Ok(Self::Apng),
+            "webp" => Ok(Self::WebP),
+            _ => Err(format!(
+                "未知的视频格式: {name}（可选 mp4、gif、apng、webp）"
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Mp4 => "mp4",
+            Self::Gif => "gif",
+            Self::Apng => "apng",
+            Self::WebP => "webp",
+        }
+    }
+
+    /// 输出文件扩展名，APNG 使用 `.png`
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Apng => "png",
+            other => other.name(),
+        }
+    }
+
+    /// 由外部 ffmpeg 编码，其余格式在进程内编码
+    pub fn needs_ffmpeg(self) -> bool {
+        self == Self::Mp4
+    }
+}
+
 /// 旋转轴枚举
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum RotationAxis {
@@ -21,6 +91,67 @@ pub enum RotationAxis {
     Custom,
 }
 
+/// 材质参数来源
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MaterialMode {
+    /// 使用 [material] 中的全局参数覆盖所有材质
+    #[default]
+    Global,
+    /// 保留 MTL 文件中的材质参数
+    FromFile,
+}
+
+/// 加载模型时的归一化方式
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum NormalizeMode {
+    /// 移到原点并缩放到单位尺寸
+    #[default]
+    Unit,
+    /// 只移到原点，保留真实尺寸
+    CenterOnly,
+    /// 保留原始坐标和尺寸
+    None,
+}
+
+impl NormalizeMode {
+    /// 配置文件中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unit => "unit",
+            Self::CenterOnly => "center_only",
+            Self::None => "none",
+        }
+    }
+}
+
+/// 特征线绘制方式
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FeatureLineMode {
+    /// 叠加在着色结果之上
+    #[default]
+    Overlay,
+    /// 仅绘制线条（线稿），表面只参与遮挡
+    Standalone,
+}
+
+/// 着色+线框模式下被遮挡边的绘制方式
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum HiddenEdgeStyle {
+    /// 不绘制
+    #[default]
+    Culled,
+    /// 虚线
+    Dashed,
+    /// 半透明淡化
+    Faded,
+}
+
+/// 允许的最大细分级别
+pub const MAX_SUBDIVISION_LEVEL: usize = 4;
+
+/// 允许的最大细节层级数（不含原始网格）
+pub const MAX_LOD_LEVELS: usize = 6;
+
 /// 纯数据结构
 #[derive(Debug, Clone)]
 pub struct RenderSettings {
@@ -35,6 +166,14 @@ pub struct RenderSettings {
     pub texture: Option<String>,
     /// 背景图片路径
     pub background_image_path: Option<String>,
+    /// 卡通着色渐变纹理路径（横向对应 N·L 从暗到亮）
+    pub toon_ramp_texture: Option<String>,
+    /// Matcap 纹理路径
+    pub matcap_texture: Option<String>,
+    /// `obj` 含帧号占位符（如 `frame_%04d.obj`）时，序列的首帧编号
+    pub sequence_start: usize,
+    /// 序列的末帧编号（含），未指定时读到第一个缺失的文件为止
+    pub sequence_end: Option<usize>,
 
     // ===== 渲染基础设置 =====
     /// 输出图像的宽度
@@ -57,12 +196,58 @@ pub struct RenderSettings {
     pub backface_culling: bool,
     /// 以线框模式渲染
     pub wireframe: bool,
+    /// 在着色表面上叠加抗锯齿线框
+    pub shaded_wireframe: bool,
+    /// 着色+线框模式下被遮挡边的绘制方式
+    pub hidden_edge_style: HiddenEdgeStyle,
+    /// 叠加线框的颜色，格式为"r,g,b"
+    pub wireframe_color: String,
+    /// 叠加线框的线宽（像素）
+    pub wireframe_width: f32,
     /// 启用小三角形剔除
     pub cull_small_triangles: bool,
     /// 小三角形剔除的最小面积阈值
     pub min_triangle_area: f32,
     /// 启用渲染和保存深度图
     pub save_depth: bool,
+    /// OBJ 线段图元（`l`）的线宽（像素）
+    pub primitive_line_width: f32,
+    /// OBJ 点图元（`p`）的直径（世界单位，随距离缩放）
+    pub point_size: f32,
+
+    // ===== 特征线（线稿）设置 =====
+    /// 启用特征线绘制
+    pub enable_feature_lines: bool,
+    /// 特征线绘制方式：叠加或独立线稿
+    pub feature_line_mode: FeatureLineMode,
+    /// 绘制轮廓线（正面与背面交界）
+    pub silhouette_lines: bool,
+    /// 绘制折痕线（二面角超过阈值）
+    pub crease_lines: bool,
+    /// 绘制边界线（开放网格边缘）
+    pub boundary_lines: bool,
+    /// 折痕判定的二面角阈值（度）
+    pub crease_angle: f32,
+    /// 线宽（像素）
+    pub line_width: f32,
+    /// 线条颜色，格式为"r,g,b"
+    pub line_color: String,
+
+    // ===== 调试可视化 =====
+    /// 以线段显示顶点法线
+    pub debug_normals: bool,
+    /// 以线段显示三角形切线（由UV推导）
+    pub debug_tangents: bool,
+    /// 法线/切线线段长度（视图空间单位）
+    pub debug_normal_length: f32,
+    /// 用棋盘格替换表面颜色以检查UV
+    pub debug_uv_checker: bool,
+    /// 面朝向热力图（正面蓝色，背面红色）
+    pub debug_face_orientation: bool,
+    /// 显示每个网格的包围盒
+    pub debug_bounding_boxes: bool,
+    /// 显示阴影贴图的光源视锥
+    pub debug_shadow_frustum: bool,
 
     // ===== 物体变换控制（字符串格式，用于TOML序列化） =====
     /// 物体位置 (x,y,z)
@@ -73,6 +258,50 @@ pub struct RenderSettings {
     pub object_scale_xyz: String,
     /// 物体的全局均匀缩放因子
     pub object_scale: f32,
+    /// 加载时的归一化方式，非 Unit 时相机裁剪面、阴影距离和地面高度按真实包围盒自适应
+    pub object_normalize: NormalizeMode,
+    /// 程序化图元（`[[object]]` 数组），非空时代替 OBJ 文件作为模型
+    pub primitives: Vec<PrimitiveObject>,
+
+    // ===== 网格处理（加载模型时执行） =====
+    /// Loop 细分次数，每次三角形数量变为 4 倍
+    pub subdivision_level: usize,
+    /// 二次误差简化的目标三角形数量，0 表示不简化
+    pub decimate_target: usize,
+    /// 按平滑角重新计算顶点法线
+    pub recompute_normals: bool,
+    /// 平滑角（度），相邻面夹角超过该值的边保持为硬边
+    pub smoothing_angle: f32,
+
+    // ===== 细节层级（LOD） =====
+    /// 按包围球的屏幕尺寸为对象切换细节层级
+    pub enable_lod: bool,
+    /// 没有 `<名称>_lod1.obj` 等文件时，由简化生成的层级数
+    pub lod_levels: usize,
+    /// 简化生成时每级相对上一级保留的三角形比例
+    pub lod_ratio: f32,
+    /// 投影直径小于该值（像素）时切换到 LOD1，之后每缩小一半再降一级
+    pub lod_switch_size: f32,
+    /// 切换阈值两侧缓冲带的比例，防止尺寸在阈值附近时反复切换
+    pub lod_hysteresis: f32,
+
+    // ===== 骨骼动画 =====
+    /// BVH 动作文件，加载后网格按到骨骼的距离自动绑定
+    pub skeleton_bvh: Option<String>,
+    /// BVH 坐标到模型坐标的缩放（BVH 常以厘米为单位）
+    pub skeleton_scale: f32,
+    /// 缩放之后的平移，格式为"x,y,z"
+    pub skeleton_offset: String,
+    /// 动画片段的播放速度倍率
+    pub skeleton_speed: f32,
+    /// 片段播完后从头循环，否则停在最后一帧
+    pub skeleton_loop: bool,
+    /// 未播放动画时（单帧渲染、界面静止）使用的姿态时刻（秒）
+    pub skeleton_time: f32,
+
+    // ===== 变形目标 =====
+    /// `[[morph_target]]`：与主模型拓扑相同的 OBJ 及其权重，在蒙皮之前叠加
+    pub morph_targets: Vec<MorphTargetSource>,
 
     // ===== 相机参数 =====
     /// 相机位置（视点），格式为"x,y,z"
@@ -83,6 +312,14 @@ pub struct RenderSettings {
     pub camera_up: String,
     /// 相机垂直视场角（度，用于透视投影）
     pub camera_fov: f32,
+    /// 启用景深：按深度缓冲和镜头参数做散景模糊（仅透视投影）
+    pub enable_depth_of_field: bool,
+    /// 光圈 f 值，越小景深越浅
+    pub camera_aperture: f32,
+    /// 对焦距离（场景单位），未设置时对焦到相机目标
+    pub camera_focus_distance: Option<f32>,
+    /// 传感器高度（毫米），24 为 35mm 全画幅
+    pub camera_sensor_size: f32,
 
     // ===== 光照基础参数 =====
     /// 启用光照计算
@@ -93,10 +330,14 @@ pub struct RenderSettings {
     pub ambient_color: String,
 
     // ===== 着色模型选择 =====
-    /// 使用Phong着色（逐像素光照）
-    pub use_phong: bool,
-    /// 使用基于物理的渲染(PBR)
-    pub use_pbr: bool,
+    /// 材质参数来源：全局覆盖或保留MTL值
+    pub material_mode: MaterialMode,
+    /// 逐材质/逐网格的参数覆盖
+    pub material_overrides: Vec<MaterialOverride>,
+    /// 着色模型：Phong、PBR、Toon、Lambert、OrenNayar、Unlit、Matcap
+    pub shading_model: MaterialType,
+    /// 卡通着色的色阶数（未指定渐变纹理时使用）
+    pub toon_bands: u32,
 
     // ===== Phong着色模型参数 =====
     /// 漫反射颜色，格式为"r,g,b"
@@ -119,8 +360,24 @@ pub struct RenderSettings {
     pub roughness: f32,
     /// 环境光遮蔽系数(0.0-1.0)
     pub ambient_occlusion: f32,
+    /// 清漆层强度(0.0-1.0)
+    pub clearcoat: f32,
+    /// 清漆层粗糙度(0.0-1.0)
+    pub clearcoat_roughness: f32,
+    /// 布料光泽(Sheen)颜色，格式为"r,g,b"，全零时关闭
+    pub sheen_color: String,
+    /// 布料光泽粗糙度(0.0-1.0)
+    pub sheen_roughness: f32,
+    /// 各向异性强度(-1.0-1.0)
+    pub anisotropy: f32,
+    /// 各向异性切线旋转角度（度）
+    pub anisotropy_rotation: f32,
+    /// 薄表面透射比例(0.0-1.0)
+    pub transmission: f32,
     /// 材质透明度(0.0-1.0)，1.0为完全不透明
     pub alpha: f32,
+    /// Alpha 测试阈值(0.0-1.0)，遮罩纹理覆盖率低于该值的片元被丢弃
+    pub alpha_cutoff: f32,
     /// 材质的自发光颜色，格式为"r,g,b"
     pub emissive: String,
 
@@ -133,6 +390,8 @@ pub struct RenderSettings {
     pub shadow_bias: f32,
     /// 阴影渲染距离
     pub shadow_distance: f32,
+    /// 阴影距离由配置或界面显式设置，不随模型包围盒自适应
+    pub shadow_distance_explicit: bool,
     /// 是否启用PCF软阴影
     pub enable_pcf: bool,
     /// PCF类型
@@ -155,9 +414,43 @@ pub struct RenderSettings {
     pub ground_plane_color: String,
     /// 地面平面在Y轴上的高度
     pub ground_plane_height: f32,
+    /// 地面高度由配置或界面显式设置，不随模型包围盒自适应
+    pub ground_plane_height_explicit: bool,
+    /// 使用真实几何地面（参与深度测试、光照和阴影）代替逐像素合成的程序化地面
+    pub ground_plane_mesh: bool,
+    /// 几何地面的边长（世界单位），以原点为中心
+    pub ground_plane_size: f32,
+    /// 几何地面的纹理路径（可选）
+    pub ground_plane_texture: Option<String>,
+    /// 纹理在几何地面上的平铺次数
+    pub ground_plane_texture_tiling: f32,
     /// 使用背景图片
     pub use_background_image: bool,
 
+    // ===== 地形设置 =====
+    /// 启用高度图地形
+    pub enable_terrain: bool,
+    /// 灰度高度图路径，白色最高
+    pub terrain_heightmap: Option<String>,
+    /// 地形纹理路径（可选）
+    pub terrain_texture: Option<String>,
+    /// 地形边长（世界单位），以原点为中心
+    pub terrain_size: f32,
+    /// 高度缩放，高度图白色对应的抬升量
+    pub terrain_height_scale: f32,
+    /// 高度图黑色处的 Y 坐标
+    pub terrain_height: f32,
+    /// 最高精度下每边的网格单元数
+    pub terrain_resolution: usize,
+    /// 纹理在地形上的平铺次数
+    pub terrain_texture_tiling: f32,
+    /// 地形颜色，格式为"r,g,b"（有纹理时与纹理相乘）
+    pub terrain_color: String,
+    /// 启用分块 LOD
+    pub terrain_lod: bool,
+    /// LOD 首次降级的距离，此后距离每翻倍降低一级
+    pub terrain_lod_distance: f32,
+
     // ===== 动画设置 =====
     /// 运行完整动画循环而非单帧渲染
     pub animate: bool,
@@ -173,6 +466,30 @@ pub struct RenderSettings {
     pub rotation_axis: RotationAxis,
     /// 自定义旋转轴 (当 rotation_axis 为 Custom 时使用)，格式 "x,y,z"
     pub custom_rotation_axis: String,
+    /// 动画导出格式，无头模式下内置格式在渲染完所有帧后额外写出动画文件
+    pub video_format: VideoFormat,
+    /// GIF 调色板的颜色数 (2-256)
+    pub gif_colors: usize,
+    /// GIF 量化时使用 Floyd-Steinberg 误差扩散抖动
+    pub gif_dither: bool,
+    /// 动画渲染时启用运动模糊：每帧在快门开启期间取多个子帧平均
+    pub motion_blur: bool,
+    /// 运动模糊每帧的子帧采样数
+    pub motion_blur_samples: usize,
+    /// 快门角（度），快门开启时长占一帧的 `shutter_angle / 360`
+    pub shutter_angle: f32,
+    /// 只渲染从该帧起的部分（完整动画中的帧号，从 0 开始），None 表示从第一帧开始；由命令行 `--frames` 设置
+    pub frame_start: Option<usize>,
+    /// 渲染到该帧为止（含），None 表示到最后一帧
+    pub frame_end: Option<usize>,
+    /// 每隔多少帧渲染一帧，输出文件仍按完整动画的帧号命名
+    pub frame_step: usize,
+    /// 输出目录中已有的帧不再重新渲染，用于中断后继续
+    pub skip_existing: bool,
+    /// 关键帧动画（`[[keyframe]]` 数组），动画类型为 Keyframes 时播放
+    pub keyframes: Vec<Keyframe>,
+    /// 相机路径（`[camera_path]`），动画类型为 CameraPath 时播放
+    pub camera_path: CameraPath,
 
     // ===== 光源数组（运行时字段） =====
     /// 场景中的所有光源
@@ -231,6 +548,10 @@ impl Default for RenderSettings {
             output_dir: "output_rust".to_string(),
             texture: None,
             background_image_path: None,
+            toon_ramp_texture: None,
+            matcap_texture: None,
+            sequence_start: 1,
+            sequence_end: None,
 
             // ===== 渲染基础设置 =====
             width: 1024,
@@ -243,21 +564,74 @@ impl Default for RenderSettings {
             enable_aces: false,
             backface_culling: false,
             wireframe: false,
+            shaded_wireframe: false,
+            hidden_edge_style: HiddenEdgeStyle::Culled,
+            wireframe_color: "0.1,0.1,0.1".to_string(),
+            wireframe_width: 1.0,
             cull_small_triangles: false,
             min_triangle_area: 1e-3,
             save_depth: true,
+            primitive_line_width: 1.5,
+            point_size: 0.01,
+
+            // ===== 特征线（线稿）设置 =====
+            enable_feature_lines: false,
+            feature_line_mode: FeatureLineMode::Overlay,
+            silhouette_lines: true,
+            crease_lines: true,
+            boundary_lines: true,
+            crease_angle: 40.0,
+            line_width: 1.5,
+            line_color: "0.05,0.05,0.05".to_string(),
+
+            // ===== 调试可视化 =====
+            debug_normals: false,
+            debug_tangents: false,
+            debug_normal_length: 0.05,
+            debug_uv_checker: false,
+            debug_face_orientation: false,
+            debug_bounding_boxes: false,
+            debug_shadow_frustum: false,
 
             // ===== 物体变换控制 =====
             object_position: "0,0,0".to_string(),
             object_rotation: "0,0,0".to_string(),
             object_scale_xyz: "1,1,1".to_string(),
             object_scale: 1.0,
+            object_normalize: NormalizeMode::Unit,
+            primitives: Vec::new(),
+
+            // ===== 网格处理 =====
+            subdivision_level: 0,
+            decimate_target: 0,
+            recompute_normals: false,
+            smoothing_angle: 60.0,
+
+            // ===== 细节层级 =====
+            enable_lod: false,
+            lod_levels: 2,
+            lod_ratio: 0.25,
+            lod_switch_size: 200.0,
+            lod_hysteresis: 0.15,
+
+            // ===== 骨骼动画 =====
+            skeleton_bvh: None,
+            skeleton_scale: 1.0,
+            skeleton_offset: "0,0,0".to_string(),
+            skeleton_speed: 1.0,
+            skeleton_loop: true,
+            skeleton_time: 0.0,
+            morph_targets: Vec::new(),
 
             // ===== 相机参数 =====
             camera_from: "0,0,3".to_string(),
             camera_at: "0,0,0".to_string(),
             camera_up: "0,1,0".to_string(),
             camera_fov: 45.0,
+            enable_depth_of_field: false,
+            camera_aperture: 2.8,
+            camera_focus_distance: None,
+            camera_sensor_size: 24.0,
 
             // ===== 光照基础参数 =====
             use_lighting: true,
@@ -265,8 +639,10 @@ impl Default for RenderSettings {
             ambient_color: "0.3,0.4,0.5".to_string(),
 
             // ===== 着色模型选择 =====
-            use_phong: true,
-            use_pbr: false,
+            material_mode: MaterialMode::Global,
+            material_overrides: Vec::new(),
+            shading_model: MaterialType::Phong,
+            toon_bands: 4,
 
             // ===== Phong着色模型参数 =====
             diffuse_color: "0.8,0.8,0.8".to_string(),
@@ -280,7 +656,15 @@ impl Default for RenderSettings {
             metallic: 0.0,
             roughness: 0.5,
             ambient_occlusion: 1.0,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.1,
+            sheen_color: "0.0,0.0,0.0".to_string(),
+            sheen_roughness: 0.5,
+            anisotropy: 0.0,
+            anisotropy_rotation: 0.0,
+            transmission: 0.0,
             alpha: 1.0, // 默认完全不透明
+            alpha_cutoff: 0.5,
             emissive: "0.0,0.0,0.0".to_string(),
 
             // ===== 阴影设置 =====
@@ -290,6 +674,7 @@ impl Default for RenderSettings {
             shadow_map_size: 256,         // 阴影贴图尺寸（较小，只用于地面）
             shadow_bias: 0.001,           // 阴影偏移
             shadow_distance: 20.0,
+            shadow_distance_explicit: false,
 
             // 新增：PCF相关参数
             enable_pcf: false,           // 是否启用PCF软阴影
@@ -304,7 +689,23 @@ impl Default for RenderSettings {
             enable_ground_plane: false,
             ground_plane_color: "0.3,0.5,0.2".to_string(),
             ground_plane_height: -1.0,
+            ground_plane_height_explicit: false,
+            ground_plane_mesh: false,
+            ground_plane_size: 20.0,
+            ground_plane_texture: None,
+            ground_plane_texture_tiling: 10.0,
             use_background_image: false,
+            enable_terrain: false,
+            terrain_heightmap: None,
+            terrain_texture: None,
+            terrain_size: 10.0,
+            terrain_height_scale: 1.0,
+            terrain_height: -1.0,
+            terrain_resolution: 128,
+            terrain_texture_tiling: 1.0,
+            terrain_color: "0.45,0.5,0.35".to_string(),
+            terrain_lod: false,
+            terrain_lod_distance: 4.0,
 
             // ===== 动画设置 =====
             animate: false,
@@ -314,6 +715,18 @@ impl Default for RenderSettings {
             animation_type: AnimationType::CameraOrbit,
             rotation_axis: RotationAxis::Y,
             custom_rotation_axis: "0,1,0".to_string(),
+            video_format: VideoFormat::default(),
+            gif_colors: 256,
+            gif_dither: true,
+            motion_blur: false,
+            motion_blur_samples: 8,
+            shutter_angle: 180.0,
+            frame_start: None,
+            frame_end: None,
+            frame_step: 1,
+            skip_existing: false,
+            keyframes: Vec::new(),
+            camera_path: CameraPath::default(),
 
             // ===== 光源数组 =====
             lights: Vec::new(),
@@ -327,6 +740,11 @@ impl Default for RenderSettings {
 }
 
 impl RenderSettings {
+    /// 第 `animation_time` 秒（动画开始后经过的时间）对应的骨骼动画时刻
+    pub fn skeleton_pose_time(&self, animation_time: f32) -> f32 {
+        self.skeleton_time + animation_time * self.skeleton_speed
+    }
+
     /// 初始化默认光源
     pub fn initialize_lights(&mut self) {
         if self.use_lighting && self.lights.is_empty() {
@@ -355,11 +773,36 @@ impl RenderSettings {
         parse_vec3(&self.gradient_bottom_color).unwrap_or_else(|_| Vector3::new(0.1, 0.2, 0.4))
     }
 
+    /// 获取地形颜色向量（按需计算）
+    pub fn get_terrain_color_vec(&self) -> Vector3<f32> {
+        parse_vec3(&self.terrain_color).unwrap_or_else(|_| Vector3::new(0.45, 0.5, 0.35))
+    }
+
+    /// 是否由帧缓冲区合成程序化地面
+    pub fn uses_procedural_ground(&self) -> bool {
+        self.enable_ground_plane && !self.ground_plane_mesh
+    }
+
+    /// 是否使用真实几何地面
+    pub fn uses_ground_mesh(&self) -> bool {
+        self.enable_ground_plane && self.ground_plane_mesh
+    }
+
     /// 获取地面平面颜色向量（按需计算）
     pub fn get_ground_plane_color_vec(&self) -> Vector3<f32> {
         parse_vec3(&self.ground_plane_color).unwrap_or_else(|_| Vector3::new(0.3, 0.5, 0.2))
     }
 
+    /// 获取叠加线框颜色向量（按需计算）
+    pub fn get_wireframe_color_vec(&self) -> Vector3<f32> {
+        parse_vec3(&self.wireframe_color).unwrap_or_else(|_| Vector3::new(0.1, 0.1, 0.1))
+    }
+
+    /// 获取线条颜色向量（按需计算）
+    pub fn get_line_color_vec(&self) -> Vector3<f32> {
+        parse_vec3(&self.line_color).unwrap_or_else(|_| Vector3::new(0.05, 0.05, 0.05))
+    }
+
     /// 解析物体变换参数为向量（统一接口）
     pub fn get_object_transform_components(&self) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>) {
         // 解析位置
@@ -382,19 +825,88 @@ impl RenderSettings {
         (position, rotation_rad, scale)
     }
 
+    /// 模型来源的简短描述（图元数量或 OBJ 路径），未指定时返回 None
+    /// `obj` 是带帧号占位符的 OBJ 序列
+    pub fn is_obj_sequence(&self) -> bool {
+        self.primitives.is_empty() && self.obj.as_deref().is_some_and(is_sequence_pattern)
+    }
+
+    /// 第 `frame` 帧的 OBJ 路径，不是序列时即 `obj` 本身
+    pub fn obj_frame_path(&self, frame: usize) -> Option<String> {
+        self.obj.as_deref().map(|obj| format_frame_path(obj, frame))
+    }
+
+    pub fn model_source(&self) -> Option<String> {
+        if !self.primitives.is_empty() {
+            Some(format!("{} 个程序化图元", self.primitives.len()))
+        } else {
+            self.obj.clone()
+        }
+    }
+
+    /// 按模型真实包围盒（世界空间）调整未显式设置的地面高度和阴影距离
+    ///
+    /// 默认值是按单位尺寸模型标定的，配置文件或界面中显式设置过的参数保持不变。
+    pub fn adapt_to_model_bounds(&mut self, min: &Point3<f32>, max: &Point3<f32>) {
+        let extent = (max - min).max();
+        if !self.ground_plane_height_explicit {
+            self.ground_plane_height = min.y;
+        }
+        if !self.shadow_distance_explicit && extent > 0.0 {
+            self.shadow_distance = (extent * 2.0).max(1.0);
+        }
+    }
+
+    /// 撤销包围盒自适应，未显式设置的地面高度和阴影距离恢复默认值
+    ///
+    /// 归一化模式切回单位尺寸后重新加载模型时调用。
+    pub fn reset_model_bounds_adaptation(&mut self) {
+        let defaults = Self::default();
+        if !self.ground_plane_height_explicit {
+            self.ground_plane_height = defaults.ground_plane_height;
+        }
+        if !self.shadow_distance_explicit {
+            self.shadow_distance = defaults.shadow_distance;
+        }
+    }
+
+    /// 是否需要在加载时处理网格
+    pub fn has_mesh_operations(&self) -> bool {
+        self.subdivision_level > 0 || self.decimate_target > 0 || self.recompute_normals
+    }
+
+    /// 是否启用了需要线段叠加层的调试可视化
+    pub fn has_debug_line_overlays(&self) -> bool {
+        self.debug_normals
+            || self.debug_tangents
+            || self.debug_bounding_boxes
+            || self.debug_shadow_frustum
+    }
+
     /// 判断是否使用透视投影
     pub fn is_perspective(&self) -> bool {
         self.projection == "perspective"
     }
 
+    /// 相机的物理镜头参数
+    pub fn lens_params(&self) -> LensParams {
+        LensParams {
+            f_stop: self.camera_aperture,
+            focus_distance: self.camera_focus_distance,
+            sensor_height_mm: self.camera_sensor_size,
+        }
+    }
+
     /// 获取着色模型的描述字符串
     pub fn get_lighting_description(&self) -> String {
-        if self.use_pbr {
-            "基于物理的渲染(PBR)".to_string()
-        } else if self.use_phong {
-            "Phong着色模型".to_string()
-        } else {
-            "平面着色模型".to_string()
+        match self.shading_model {
+            MaterialType::PBR => "基于物理的渲染(PBR)".to_string(),
+            MaterialType::Phong => "Phong着色模型".to_string(),
+            MaterialType::Toon => "卡通着色模型".to_string(),
+            MaterialType::Lambert => "Lambert漫反射模型".to_string(),
+            MaterialType::OrenNayar => "Oren-Nayar漫反射模型".to_string(),
+            MaterialType::Unlit => "无光照模型".to_string(),
+            MaterialType::Matcap => "Matcap材质捕获".to_string(),
         }
     }
 
@@ -433,6 +945,20 @@ impl RenderSettings {
             return Err("错误: 相机上方向格式不正确，应为 x,y,z 格式".to_string());
         }
 
+        if self.camera_aperture <= 0.0 {
+            return Err("错误: 光圈 f 值必须大于 0".to_string());
+        }
+
+        if self.camera_sensor_size <= 0.0 {
+            return Err("错误: 传感器尺寸必须大于 0".to_string());
+        }
+
+        if let Some(focus_distance) = self.camera_focus_distance
+            && focus_distance <= 0.0
+        {
+            return Err("错误: 对焦距离必须大于 0".to_string());
+        }
+
         // 验证物体变换参数
         if parse_vec3(&self.object_position).is_err() {
             return Err("错误: 物体位置格式不正确，应为 x,y,z 格式".to_string());
@@ -446,6 +972,85 @@ impl RenderSettings {
             return Err("错误: 物体缩放格式不正确，应为 x,y,z 格式".to_string());
         }
 
+        // 每级细分使三角形数量变为 4 倍
+        if self.subdivision_level > MAX_SUBDIVISION_LEVEL {
+            return Err(format!("错误: 细分级别不能超过 {MAX_SUBDIVISION_LEVEL}"));
+        }
+
+        if self.animation_type == AnimationType::Keyframes && self.keyframes.is_empty() {
+            return Err("错误: 关键帧动画需要至少一个 [[keyframe]]".to_string());
+        }
+        if !self.keyframes.is_empty() {
+            // 在第一个关键帧处试应用一次，检查光源编号和属性类型
+            Timeline::new(&self.keyframes)
+                .apply(0.0, &mut self.clone())
+                .map_err(|e| format!("错误: 关键帧无效: {e}"))?;
+        }
+        if self.animation_type == AnimationType::CameraPath {
+            self.camera_path
+                .validate()
+                .map_err(|e| format!("错误: 相机路径无效: {e}"))?;
+        }
+
+        if self.enable_lod {
+            if self.lod_levels > MAX_LOD_LEVELS {
+                return Err(format!("错误: LOD 层级数不能超过 {MAX_LOD_LEVELS}"));
+            }
+            if !(self.lod_ratio > 0.0 && self.lod_ratio < 1.0) {
+                return Err("错误: LOD 简化比例必须在 0 到 1 之间".to_string());
+            }
+            if self.lod_switch_size <= 0.0 {
+                return Err("错误: LOD 切换尺寸必须大于 0".to_string());
+            }
+            if !(0.0..0.5).contains(&self.lod_hysteresis) {
+                return Err("错误: LOD 缓冲比例必须在 [0, 0.5) 范围内".to_string());
+            }
+        }
+
+        if self.skeleton_bvh.is_some() {
+            if self.skeleton_scale <= 0.0 {
+                return Err("错误: 骨架缩放必须大于 0".to_string());
+            }
+            if parse_vec3(&self.skeleton_offset).is_err() {
+                return Err("错误: 骨架偏移格式不正确，应为 x,y,z 格式".to_string());
+            }
+        }
+
+        if !(2..=256).contains(&self.gif_colors) {
+            return Err("错误: GIF 颜色数必须在 2 到 256 之间".to_string());
+        }
+
+        if self.motion_blur {
+            if !(1..=64).contains(&self.motion_blur_samples) {
+                return Err("错误: 运动模糊采样数必须在 1 到 64 之间".to_string());
+            }
+            if self.shutter_angle <= 0.0 || self.shutter_angle > 360.0 {
+                return Err("错误: 快门角必须在 0 到 360 度之间（不含 0）".to_string());
+            }
+        }
+
+        if self.is_obj_sequence()
+            && let Some(end) = self.sequence_end
+            && end < self.sequence_start
+        {
+            return Err("错误: OBJ 序列的末帧不能小于首帧".to_string());
+        }
+
+        for (index, target) in self.morph_targets.iter().enumerate() {
+            if target.name.is_empty() {
+                return Err(format!("错误: 第{}个变形目标缺少名称", index + 1));
+            }
+            if self.morph_targets[..index]
+                .iter()
+                .any(|other| other.name == target.name)
+            {
+                return Err(format!("错误: 变形目标名称 '{}' 重复", target.name));
+            }
+            if !target.weight.is_finite() {
+                return Err(format!("错误: 变形目标 '{}' 的权重无效", target.name));
+            }
+        }
+
         Ok(())
     }
 }
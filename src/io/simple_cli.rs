@@ -1,5 +1,5 @@
 use crate::io::config_loader::TomlConfigLoader;
-use crate::io::render_settings::RenderSettings;
+use crate::io::render_settings::{RenderSettings, VideoFormat};
 use clap::Parser;
 use log::info;
 
@@ -19,6 +19,22 @@ pub struct SimpleCli {
     /// 使用示例配置（临时创建并加载）
     #[arg(long)]
     pub use_example_config: bool,
+
+    /// 动画导出格式，覆盖配置中的 video_format（mp4、gif、apng、webp）
+    #[arg(long, value_name = "FORMAT")]
+    pub video_format: Option<String>,
+
+    /// 只渲染动画的一段帧，如 120-240（含两端）、120-（到结尾）或 -240（从开头）
+    #[arg(long, value_name = "RANGE")]
+    pub frames: Option<String>,
+
+    /// 每隔 N 帧渲染一帧，输出仍按完整动画的帧号命名
+    #[arg(long, value_name = "N")]
+    pub frame_step: Option<usize>,
+
+    /// 跳过输出目录中已存在的帧，用于中断后继续渲染
+    #[arg(long)]
+    pub skip_existing: bool,
 }
 
 impl SimpleCli {
@@ -35,15 +51,16 @@ impl SimpleCli {
 
             info!("已创建临时示例配置: {temp_config_path}");
 
-            let settings = TomlConfigLoader::load_from_file(temp_config_path)
+            let mut settings = TomlConfigLoader::load_from_file(temp_config_path)
                 .map_err(|e| format!("加载示例配置失败: {e}"))?;
+            cli.apply_overrides(&mut settings)?;
 
             let should_start_gui = !cli.headless;
             return Ok((settings, should_start_gui));
         }
 
         // 加载配置文件或使用默认设置
-        let settings = if let Some(config_path) = &cli.config {
+        let mut settings = if let Some(config_path) = &cli.config {
             info!("加载配置文件: {config_path}");
             TomlConfigLoader::load_from_file(config_path)
                 .map_err(|e| format!("配置文件加载失败: {e}"))?
@@ -51,8 +68,77 @@ impl SimpleCli {
             info!("使用默认设置");
             RenderSettings::default()
         };
+        cli.apply_overrides(&mut settings)?;
 
         let should_start_gui = !cli.headless;
         Ok((settings, should_start_gui))
     }
+
+    /// 命令行参数覆盖配置文件中的设置
+    fn apply_overrides(&self, settings: &mut RenderSettings) -> Result<(), String> {
+        if let Some(video_format) = &self.video_format {
+            settings.video_format = VideoFormat::parse(video_format)?;
+        }
+        if let Some(frames) = &self.frames {
+            (settings.frame_start, settings.frame_end) = parse_frame_range(frames)?;
+        }
+        if let Some(frame_step) = self.frame_step {
+            if frame_step == 0 {
+                return Err("帧步长必须大于0".to_string());
+            }
+            settings.frame_step = frame_step;
+        }
+        if self.skip_existing {
+            settings.skip_existing = true;
+        }
+        Ok(())
+    }
+}
+
+/// 解析帧范围 `A-B`、`A-`、`-B` 或单帧 `A`
+fn parse_frame_range(range: &str) -> Result<(Option<usize>, Option<usize>), String> {
+    let parse_bound = |bound: &str| -> Result<Option<usize>, String> {
+        let bound = bound.trim();
+        if bound.is_empty() {
+            return Ok(None);
+        }
+        bound
+            .parse()
+            .map(Some)
+            .map_err(|e| format!("无效的帧号 '{bound}': {e}"))
+    };
+    match range.split_once('-') {
+        Some((start, end)) => match (parse_bound(start)?, parse_bound(end)?) {
+            (Some(start), Some(end)) if end < start => {
+                Err(format!("帧范围 '{range}' 的末帧不能小于首帧"))
+            }
+            bounds => Ok(bounds),
+        },
+        None => {
+            let frame = parse_bound(range)?.ok_or("帧范围不能为空")?;
+            Ok((Some(frame), Some(frame)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_range_forms() {
+        assert_eq!(parse_frame_range("120-240"), Ok((Some(120), Some(240))));
+        assert_eq!(parse_frame_range("120-"), Ok((Some(120), None)));
+        assert_eq!(parse_frame_range("-240"), Ok((None, Some(240))));
+        assert_eq!(parse_frame_range("7"), Ok((Some(7), Some(7))));
+        assert_eq!(parse_frame_range(" 3 - 5 "), Ok((Some(3), Some(5))));
+    }
+
+    #[test]
+    fn frame_range_rejects_invalid() {
+        assert!(parse_frame_range("240-120").is_err());
+        assert!(parse_frame_range("").is_err());
+        assert!(parse_frame_range("a-5").is_err());
+        assert!(parse_frame_range("1-2-3").is_err());
+    }
 }
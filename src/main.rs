@@ -1,5 +1,6 @@
 use log::{error, info, warn};
 use std::fs;
+use std::path::Path;
 use std::time::Instant;
 
 mod core;
@@ -14,7 +15,9 @@ use crate::ui::app::start_gui;
 use core::renderer::Renderer;
 use io::model_loader::ModelLoader;
 use io::simple_cli::SimpleCli;
-use utils::render_utils::{render_single_frame, run_animation_loop};
+use utils::render_utils::{is_partial_render, render_single_frame, run_animation_loop};
+use utils::save_utils::color_output_path;
+use utils::video_utils::encode_animation;
 
 fn main() -> Result<(), String> {
     // 初始化日志系统
@@ -33,7 +36,7 @@ fn main() -> Result<(), String> {
 
     info!("🎨 光栅化渲染器启动");
 
-    let (settings, should_start_gui) = SimpleCli::process()?;
+    let (mut settings, should_start_gui) = SimpleCli::process()?;
 
     // 判断是否应该启动GUI模式
     if should_start_gui {
@@ -45,14 +48,13 @@ fn main() -> Result<(), String> {
         return Ok(());
     }
 
-    // 无头渲染模式 - 需要OBJ文件
-    if settings.obj.is_none() {
-        error!("无头模式需要指定OBJ文件路径");
+    // 无头渲染模式 - 需要OBJ文件或程序化图元
+    if settings.model_source().is_none() {
+        error!("无头模式需要指定OBJ文件路径或 [[object]] 图元");
         return Err("缺少OBJ文件路径".to_string());
     }
 
     let start_time = Instant::now();
-    let obj_path = settings.obj.as_ref().unwrap();
 
     // 确保输出目录存在
     fs::create_dir_all(&settings.output_dir).map_err(|e| {
@@ -67,8 +69,8 @@ fn main() -> Result<(), String> {
     }
 
     // 加载模型和创建场景
-    let (mut scene, _model_data) = ModelLoader::load_and_create_scene(obj_path, &settings)
-        .map_err(|e| {
+    let (mut scene, model_data) =
+        ModelLoader::load_and_create_scene(&mut settings).map_err(|e| {
             error!("模型加载失败: {e}");
             "模型加载失败".to_string()
         })?;
@@ -78,10 +80,30 @@ fn main() -> Result<(), String> {
 
     // 渲染动画或单帧
     if settings.animate {
-        run_animation_loop(&mut scene, &mut renderer, &settings).map_err(|e| {
-            error!("动画渲染失败: {e}");
-            "动画渲染失败".to_string()
-        })?;
+        let frame_names = run_animation_loop(&mut scene, &mut renderer, &settings, &model_data)
+            .map_err(|e| {
+                error!("动画渲染失败: {e}");
+                "动画渲染失败".to_string()
+            })?;
+
+        // 内置格式直接编码为动画文件，MP4 仍只输出帧序列；分段渲染只输出帧，合并后再编码
+        if is_partial_render(&settings) {
+            info!("分段渲染，跳过动画文件编码");
+        } else if !settings.video_format.needs_ffmpeg() {
+            let frame_paths: Vec<_> = frame_names
+                .iter()
+                .map(|name| color_output_path(&settings.output_dir, name))
+                .collect();
+            let output_path = Path::new(&settings.output_dir).join(format!(
+                "{}.{}",
+                settings.output,
+                settings.video_format.extension()
+            ));
+            encode_animation(&frame_paths, &settings, &output_path).map_err(|e| {
+                error!("动画编码失败: {e}");
+                "动画编码失败".to_string()
+            })?;
+        }
     } else {
         info!("--- 开始单帧渲染 ---");
         info!("分辨率: {}x{}", settings.width, settings.height);
@@ -97,10 +119,10 @@ fn main() -> Result<(), String> {
         );
         info!("材质: {}", settings.get_lighting_description());
 
-        if settings.use_background_image {
-            if let Some(bg_path) = &settings.background_image_path {
-                info!("背景图片: {bg_path}");
-            }
+        if settings.use_background_image
+            && let Some(bg_path) = &settings.background_image_path
+        {
+            info!("背景图片: {bg_path}");
         }
         if settings.enable_gradient_background {
             info!("渐变背景: 启用");
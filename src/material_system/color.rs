@@ -86,6 +86,34 @@ pub fn linear_rgb_to_u8(linear_color: &Color, apply_gamma: bool) -> [u8; 3] {
     ]
 }
 
+/// 将u8颜色通道解码为线性值，与 [`linear_rgb_to_u8`] 互逆
+///
+/// # 参数
+/// * `value` - 颜色通道值 [0-255]
+/// * `apply_gamma` - 该值是否经过gamma矫正
+pub fn u8_to_linear_channel(value: u8, apply_gamma: bool) -> f32 {
+    let display = value as f32 / 255.0;
+    if apply_gamma {
+        display.powf(2.2)
+    } else {
+        display
+    }
+}
+
+/// 将线性值编码为u8颜色通道，四舍五入使解码后再编码保持原值
+///
+/// # 参数
+/// * `linear` - 线性空间的颜色通道值 [0.0-1.0]
+/// * `apply_gamma` - 是否应用gamma矫正
+pub fn linear_channel_to_u8(linear: f32, apply_gamma: bool) -> u8 {
+    let display = if apply_gamma {
+        linear.max(0.0).powf(1.0 / 2.2)
+    } else {
+        linear
+    };
+    (display * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
 /// 获取基于种子的随机颜色。
 ///
 /// 如果`colorize`为false，返回默认的灰色。
@@ -1,4 +1,7 @@
-use crate::io::render_settings::{RenderSettings, parse_vec3};
+use crate::geometry::morph::MorphTarget;
+use crate::geometry::skeleton::{Skeleton, SkinWeights};
+use crate::io::obj_loader::SharedTextures;
+use crate::io::render_settings::{MaterialMode, RenderSettings, parse_vec3};
 use crate::material_system::texture::Texture;
 use log::warn;
 use nalgebra::{Point3, Vector2, Vector3};
@@ -11,26 +14,78 @@ pub struct Vertex {
     pub texcoord: Vector2<f32>,
 }
 
-/// 材质类型枚举
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// 材质类型枚举（即着色模型）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum MaterialType {
+    #[default]
     Phong,
     #[allow(clippy::upper_case_acronyms)]
     PBR,
+    /// 卡通/赛璐璐着色：光照量化为色阶或查找渐变纹理
+    Toon,
+    /// 纯 Lambert 漫反射
+    Lambert,
+    /// Oren-Nayar 粗糙漫反射，粗糙度取自 roughness
+    OrenNayar,
+    /// 无光照，直接输出表面颜色
+    Unlit,
+    /// 材质捕获：按视图空间法线采样 matcap 纹理
+    Matcap,
+}
+
+impl MaterialType {
+    pub const ALL: [MaterialType; 7] = [
+        MaterialType::Phong,
+        MaterialType::PBR,
+        MaterialType::Toon,
+        MaterialType::Lambert,
+        MaterialType::OrenNayar,
+        MaterialType::Unlit,
+        MaterialType::Matcap,
+    ];
+
+    /// 不依赖光源的着色模型，每个像素只计算一次响应
+    pub fn is_unlit(self) -> bool {
+        matches!(self, MaterialType::Unlit | MaterialType::Matcap)
+    }
+
+    /// 界面显示名称
+    pub fn display_name(self) -> &'static str {
+        match self {
+            MaterialType::Phong => "Phong着色",
+            MaterialType::PBR => "PBR渲染",
+            MaterialType::Toon => "卡通着色",
+            MaterialType::Lambert => "Lambert漫反射",
+            MaterialType::OrenNayar => "Oren-Nayar",
+            MaterialType::Unlit => "无光照",
+            MaterialType::Matcap => "Matcap",
+        }
+    }
 }
 
 /// 材质结构体，统一包含所有参数
 #[derive(Debug, Clone)]
 pub struct Material {
-    pub material_type: MaterialType, // 材质类型
-    pub base_color: Vector3<f32>,    // 基础色（PBR/Phong通用）
-    pub alpha: f32,                  // 透明度
-    pub texture: Option<Texture>,    // 纹理资源
+    pub name: String,                   // 材质名（MTL 中的 newmtl）
+    pub dedicated_mesh: Option<String>, // 按网格名覆盖时为该网格复制出的专用材质所属的网格名
+    pub material_type: MaterialType,    // 材质类型
+    pub base_color: Vector3<f32>,       // 基础色（PBR/Phong通用）
+    pub alpha: f32,                     // 透明度
+    pub texture: Option<Texture>,       // 纹理资源
+    pub alpha_texture: Option<Texture>, // 透明度遮罩纹理（map_d），用于 alpha 测试镂空
 
     // ===== PBR参数 =====
     pub metallic: f32,
     pub roughness: f32,
+    pub roughness_from_shininess: bool, // MTL 未给出 Pr，保留 MTL 参数时由 Ns 推导粗糙度
     pub ambient_occlusion: f32,
+    pub clearcoat: f32,            // 清漆层强度
+    pub clearcoat_roughness: f32,  // 清漆层粗糙度
+    pub sheen_color: Vector3<f32>, // Charlie 布料光泽颜色，全零时关闭
+    pub sheen_roughness: f32,      // 布料光泽粗糙度
+    pub anisotropy: f32,           // 各向异性强度(-1..1)，正值沿切线方向拉伸高光
+    pub anisotropy_rotation: f32,  // 切线绕法线的旋转角度（度）
+    pub transmission: f32,         // 薄表面透射比例
 
     // ===== Phong参数 =====
     pub specular: Vector3<f32>,
@@ -41,24 +96,49 @@ pub struct Material {
     // ===== 通用参数 =====
     pub emissive: Vector3<f32>,
     pub ambient_factor: Vector3<f32>,
+    pub ambient_from_mtl: bool, // ambient_factor 取自 MTL 的 Ka，基础色等参数不变时不重新推导
+    pub ior: f32,               // 折射率（MTL Ni），决定电介质的 F0
+    pub illumination_model: u8, // MTL illum：0 无明暗，1 仅漫反射，>=2 含高光
+
+    // ===== 风格化参数 =====
+    pub toon_bands: u32,                 // 卡通着色色阶数
+    pub ramp_texture: Option<Texture>,   // 卡通着色渐变纹理，按 N·L 横向采样
+    pub matcap_texture: Option<Texture>, // matcap 纹理
 }
 
 impl Material {
     pub fn default(material_type: MaterialType) -> Self {
         Material {
+            name: "default".to_string(),
+            dedicated_mesh: None,
             material_type,
             base_color: Vector3::new(0.8, 0.8, 0.8),
             alpha: 1.0,
             texture: None,
+            alpha_texture: None,
             metallic: 0.0,
             roughness: 0.5,
             ambient_occlusion: 1.0,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.1,
+            sheen_color: Vector3::zeros(),
+            sheen_roughness: 0.5,
+            anisotropy: 0.0,
+            anisotropy_rotation: 0.0,
+            transmission: 0.0,
             specular: Vector3::new(0.5, 0.5, 0.5),
             shininess: 32.0,
             diffuse_intensity: 1.0,
             specular_intensity: 1.0,
             emissive: Vector3::zeros(),
             ambient_factor: Vector3::new(1.0, 1.0, 1.0),
+            ambient_from_mtl: false,
+            roughness_from_shininess: false,
+            ior: 1.5,
+            illumination_model: 2,
+            toon_bands: 4,
+            ramp_texture: None,
+            matcap_texture: None,
         }
     }
 }
@@ -67,8 +147,16 @@ impl Material {
 pub struct Mesh {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    /// 线段图元的顶点索引，每两个一组
+    pub lines: Vec<u32>,
+    /// 点图元的顶点索引
+    pub points: Vec<u32>,
     pub material_id: usize,
     pub name: String,
+    /// 每个顶点的蒙皮权重，与 `vertices` 一一对应；为空表示不参与蒙皮
+    pub skin_weights: Vec<SkinWeights>,
+    /// 变形目标，每个目标的偏移与 `vertices` 一一对应
+    pub morph_targets: Vec<MorphTarget>,
 }
 
 #[derive(Debug, Clone)]
@@ -76,6 +164,12 @@ pub struct Model {
     pub meshes: Vec<Mesh>,
     pub materials: Vec<Material>,
     pub name: String,
+    /// 低细节层级（LOD1、LOD2…），每级是一组完整的网格，与 `meshes` 共用材质列表
+    pub lods: Vec<Vec<Mesh>>,
+    /// 骨架与动画片段，网格通过 `skin_weights` 绑定到其关节
+    pub skeleton: Option<Skeleton>,
+    /// 归一化时使用的中心和缩放系数，未归一化时为原点和 1
+    pub normalization: (Vector3<f32>, f32),
 }
 
 /// 材质响应计算（统一接口）
@@ -84,7 +178,20 @@ pub fn compute_material_response(
     light_dir: &Vector3<f32>,
     view_dir: &Vector3<f32>,
     surface_normal: &Vector3<f32>,
+    tangent: Option<&Vector3<f32>>,
 ) -> Vector3<f32> {
+    // illum 0：仅颜色，不做明暗计算；Unlit 和 Matcap 本身不受光照影响
+    if material.illumination_model == 0
+        && !matches!(
+            material.material_type,
+            MaterialType::Unlit | MaterialType::Matcap
+        )
+    {
+        return material.base_color * material.diffuse_intensity + material.emissive;
+    }
+    // illum 1：仅漫反射，去掉所有高光波瓣
+    let specular_lobes = material.illumination_model != 1;
+
     match material.material_type {
         MaterialType::Phong => {
             let n_dot_l = surface_normal.dot(light_dir).max(0.0);
@@ -92,49 +199,186 @@ pub fn compute_material_response(
                 return material.emissive;
             }
             let diffuse = material.base_color * material.diffuse_intensity * n_dot_l;
+            if !specular_lobes {
+                return diffuse + material.emissive;
+            }
             let halfway_dir = (light_dir + view_dir).normalize();
             let n_dot_h = surface_normal.dot(&halfway_dir).max(0.0);
             let spec_intensity = n_dot_h.powf(material.shininess);
             let specular = material.specular * material.specular_intensity * spec_intensity;
             diffuse + specular + material.emissive
         }
+        MaterialType::Lambert => {
+            let n_dot_l = surface_normal.dot(light_dir).max(0.0);
+            material.base_color * material.diffuse_intensity * n_dot_l + material.emissive
+        }
+        MaterialType::OrenNayar => {
+            let n = surface_normal;
+            let n_dot_l = n.dot(light_dir).max(0.0);
+            if n_dot_l <= 0.0 {
+                return material.emissive;
+            }
+            let n_dot_v = n.dot(view_dir).max(0.0);
+            // roughness 0-1 映射到表面朝向标准差 0-90 度
+            let sigma = material.roughness.clamp(0.0, 1.0) * std::f32::consts::FRAC_PI_2;
+            let sigma2 = sigma * sigma;
+            let a = 1.0 - 0.5 * sigma2 / (sigma2 + 0.33);
+            let b = 0.45 * sigma2 / (sigma2 + 0.09);
+            // 光线与视线在切平面上投影夹角的余弦
+            let l_proj = light_dir - n * n.dot(light_dir);
+            let v_proj = view_dir - n * n.dot(view_dir);
+            let cos_phi = if l_proj.norm_squared() > 1e-8 && v_proj.norm_squared() > 1e-8 {
+                l_proj.normalize().dot(&v_proj.normalize()).max(0.0)
+            } else {
+                0.0
+            };
+            let theta_i = n_dot_l.acos();
+            let theta_r = n_dot_v.acos();
+            let alpha = theta_i.max(theta_r);
+            let beta = theta_i.min(theta_r);
+            let oren_nayar = a + b * cos_phi * alpha.sin() * beta.tan();
+            material.base_color * material.diffuse_intensity * n_dot_l * oren_nayar
+                + material.emissive
+        }
+        MaterialType::Toon => {
+            let n_dot_l = surface_normal.dot(light_dir).max(0.0);
+            let shade = if let Some(ramp) = &material.ramp_texture {
+                let [r, g, b] = ramp.sample(n_dot_l.clamp(0.01, 0.99), 0.5);
+                Vector3::new(r, g, b)
+            } else {
+                let bands = material.toon_bands.max(2) as f32;
+                let level = (n_dot_l * bands).ceil() / bands;
+                Vector3::new(level, level, level)
+            };
+            let diffuse = material.base_color.component_mul(&shade) * material.diffuse_intensity;
+            // 硬边高光
+            let halfway_dir = (light_dir + view_dir).normalize();
+            let spec = surface_normal
+                .dot(&halfway_dir)
+                .max(0.0)
+                .powf(material.shininess);
+            let specular = if specular_lobes && n_dot_l > 0.0 && spec > 0.5 {
+                material.specular * material.specular_intensity
+            } else {
+                Vector3::zeros()
+            };
+            diffuse + specular + material.emissive
+        }
+        MaterialType::Unlit => Vector3::new(1.0, 1.0, 1.0) + material.emissive,
+        MaterialType::Matcap => {
+            // 视图空间法线的 xy 映射到 matcap 纹理坐标
+            let n = surface_normal;
+            let (u, v) = (n.x * 0.5 + 0.5, n.y * 0.5 + 0.5);
+            let matcap = if let Some(matcap) = &material.matcap_texture {
+                let [r, g, b] = matcap.sample(u.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+                Vector3::new(r, g, b)
+            } else {
+                // 没有纹理时使用程序化的柔光球
+                let facing = n.z.max(0.0);
+                let rim = (1.0 - facing).powi(3) * 0.3;
+                let key =
+                    (0.2 + 0.8 * (n.dot(&Vector3::new(-0.4, 0.6, 0.7).normalize())).max(0.0)) + rim;
+                Vector3::new(key, key, key)
+            };
+            matcap.component_mul(&material.base_color) + material.emissive
+        }
         MaterialType::PBR => {
             let base_color = material.base_color;
             let metallic = material.metallic;
             let roughness = material.roughness;
             let ao = material.ambient_occlusion;
 
-            let l = *light_dir;
+            let n = *surface_normal;
             let v = *view_dir;
-            let h = (l + v).normalize();
-
-            let n_dot_l = surface_normal.dot(&l).max(0.0);
-            let n_dot_v = surface_normal.dot(&v).max(0.0);
-            let n_dot_h = surface_normal.dot(&h).max(0.0);
-            let h_dot_v = h.dot(&v).max(0.0);
+            let n_dot_v = n.dot(&v).max(0.0);
+            let raw_n_dot_l = n.dot(light_dir);
 
-            if n_dot_l <= 0.0 {
-                return material.emissive;
+            // 薄表面透射：光源位于背面时，把光线镜像到正面计算透射高光
+            if raw_n_dot_l <= 0.0 {
+                if material.transmission <= 0.0 {
+                    return material.emissive;
+                }
+                let l_mirrored = (light_dir - 2.0 * raw_n_dot_l * n).normalize();
+                let n_dot_l = -raw_n_dot_l;
+                let spec = if specular_lobes {
+                    let h = (l_mirrored + v).normalize();
+                    let d = pbr::distribution_ggx(n.dot(&h).max(0.0), roughness);
+                    let g = pbr::geometry_smith(n_dot_v, n_dot_l, roughness);
+                    d * g / (4.0 * n_dot_v * n_dot_l).max(0.001)
+                } else {
+                    0.0
+                };
+                let transmitted = base_color
+                    * (spec + 1.0 / std::f32::consts::PI)
+                    * (material.transmission * (1.0 - metallic) * n_dot_l);
+                return transmitted + material.emissive;
             }
 
+            let l = *light_dir;
+            let h = (l + v).normalize();
+            let n_dot_l = raw_n_dot_l;
+            let n_dot_h = n.dot(&h).max(0.0);
+            let h_dot_v = h.dot(&v).max(0.0);
+
             // 标准PBR F0计算
-            let f0_dielectric = Vector3::new(0.04, 0.04, 0.04);
+            let f0_scalar = ((material.ior - 1.0) / (material.ior + 1.0)).powi(2);
+            let f0_dielectric = Vector3::new(f0_scalar, f0_scalar, f0_scalar);
             let f0 = f0_dielectric.lerp(&base_color, metallic);
-
-            let d = pbr::distribution_ggx(n_dot_h, roughness);
-            let g = pbr::geometry_smith(n_dot_v, n_dot_l, roughness);
             let f = pbr::fresnel_schlick(h_dot_v, f0);
 
-            let numerator = d * g * f;
-            let denominator = 4.0 * n_dot_v * n_dot_l;
-            let specular = numerator / denominator.max(0.001);
+            let specular = if !specular_lobes {
+                Vector3::zeros()
+            } else if material.anisotropy.abs() > 1e-3 {
+                // 各向异性GGX，切线方向绕法线旋转 anisotropy_rotation
+                let (t, b) = pbr::tangent_frame(&n, tangent, material.anisotropy_rotation);
+                let (at, ab) = pbr::anisotropic_alphas(roughness, material.anisotropy);
+                let d = pbr::distribution_ggx_anisotropic(n_dot_h, t.dot(&h), b.dot(&h), at, ab);
+                let vis = pbr::visibility_smith_ggx_anisotropic(
+                    n_dot_v,
+                    n_dot_l,
+                    (t.dot(&v), b.dot(&v)),
+                    (t.dot(&l), b.dot(&l)),
+                    at,
+                    ab,
+                );
+                f * (d * vis)
+            } else {
+                let d = pbr::distribution_ggx(n_dot_h, roughness);
+                let g = pbr::geometry_smith(n_dot_v, n_dot_l, roughness);
+                let numerator = d * g * f;
+                let denominator = 4.0 * n_dot_v * n_dot_l;
+                numerator / denominator.max(0.001)
+            };
 
             let k_s = f;
-            let k_d = (Vector3::new(1.0, 1.0, 1.0) - k_s) * (1.0 - metallic);
+            let k_d = (Vector3::new(1.0, 1.0, 1.0) - k_s)
+                * (1.0 - metallic)
+                * (1.0 - material.transmission);
             let diffuse = k_d.component_mul(&base_color) / std::f32::consts::PI;
 
+            // Charlie 布料光泽
+            let sheen = if specular_lobes && material.sheen_color.max() > 0.0 {
+                let d = pbr::distribution_charlie(n_dot_h, material.sheen_roughness);
+                let vis = pbr::visibility_neubelt(n_dot_v, n_dot_l);
+                material.sheen_color * (d * vis)
+            } else {
+                Vector3::zeros()
+            };
+
             // 标准Cook-Torrance BRDF
-            let brdf_result = (diffuse + specular) * n_dot_l * ao;
+            let mut brdf_result = (diffuse + specular + sheen) * n_dot_l * ao;
+
+            // 清漆层：独立粗糙度的电介质GGX，按其菲涅尔衰减底层
+            if specular_lobes && material.clearcoat > 0.0 {
+                let coat_roughness = material.clearcoat_roughness.clamp(0.03, 1.0);
+                let fc = pbr::fresnel_schlick(h_dot_v, Vector3::new(0.04, 0.04, 0.04)).x
+                    * material.clearcoat;
+                let dc = pbr::distribution_ggx(n_dot_h, coat_roughness);
+                let vc = pbr::visibility_kelemen(l.dot(&h));
+                let coat = dc * vc * fc * n_dot_l;
+                brdf_result = brdf_result * (1.0 - fc) + Vector3::new(coat, coat, coat);
+            }
+
             brdf_result + material.emissive
         }
     }
@@ -165,6 +409,86 @@ pub mod pbr {
         ggx1 * ggx2
     }
 
+    /// 构建切线空间，切线缺失或退化时根据法线选取一个正交方向
+    pub fn tangent_frame(
+        n: &Vector3<f32>,
+        tangent: Option<&Vector3<f32>>,
+        rotation_degrees: f32,
+    ) -> (Vector3<f32>, Vector3<f32>) {
+        let orthogonalized = tangent
+            .map(|t| t - n * n.dot(t))
+            .filter(|t| t.norm_squared() > 1e-8)
+            .map(|t| t.normalize());
+        let t = orthogonalized.unwrap_or_else(|| {
+            let reference = if n.y.abs() < 0.999 {
+                Vector3::y()
+            } else {
+                Vector3::x()
+            };
+            reference.cross(n).normalize()
+        });
+        let b = n.cross(&t);
+        if rotation_degrees == 0.0 {
+            return (t, b);
+        }
+        let (sin, cos) = rotation_degrees.to_radians().sin_cos();
+        (t * cos + b * sin, b * cos - t * sin)
+    }
+
+    /// 各向异性下切线/副切线方向的 alpha
+    pub fn anisotropic_alphas(roughness: f32, anisotropy: f32) -> (f32, f32) {
+        let alpha = roughness * roughness;
+        let anisotropy = anisotropy.clamp(-1.0, 1.0);
+        let at = (alpha * (1.0 + anisotropy)).max(0.001);
+        let ab = (alpha * (1.0 - anisotropy)).max(0.001);
+        (at, ab)
+    }
+
+    pub fn distribution_ggx_anisotropic(
+        n_dot_h: f32,
+        t_dot_h: f32,
+        b_dot_h: f32,
+        at: f32,
+        ab: f32,
+    ) -> f32 {
+        let x = t_dot_h / at;
+        let y = b_dot_h / ab;
+        let denominator = x * x + y * y + n_dot_h * n_dot_h;
+        1.0 / (std::f32::consts::PI * at * ab * denominator * denominator).max(0.0001)
+    }
+
+    /// 高度相关的各向异性 Smith 可见性项（已包含 1 / (4 NoL NoV)）
+    pub fn visibility_smith_ggx_anisotropic(
+        n_dot_v: f32,
+        n_dot_l: f32,
+        (t_dot_v, b_dot_v): (f32, f32),
+        (t_dot_l, b_dot_l): (f32, f32),
+        at: f32,
+        ab: f32,
+    ) -> f32 {
+        let lambda_v = n_dot_l * Vector3::new(at * t_dot_v, ab * b_dot_v, n_dot_v).norm();
+        let lambda_l = n_dot_v * Vector3::new(at * t_dot_l, ab * b_dot_l, n_dot_l).norm();
+        0.5 / (lambda_v + lambda_l).max(0.0001)
+    }
+
+    /// Charlie 布料分布（Estevez & Kulla）
+    pub fn distribution_charlie(n_dot_h: f32, roughness: f32) -> f32 {
+        let alpha = roughness.clamp(0.07, 1.0).powi(2);
+        let inv_alpha = 1.0 / alpha;
+        let sin2h = (1.0 - n_dot_h * n_dot_h).max(0.0078125);
+        (2.0 + inv_alpha) * sin2h.powf(inv_alpha * 0.5) / (2.0 * std::f32::consts::PI)
+    }
+
+    /// Neubelt 布料可见性项
+    pub fn visibility_neubelt(n_dot_v: f32, n_dot_l: f32) -> f32 {
+        1.0 / (4.0 * (n_dot_l + n_dot_v - n_dot_l * n_dot_v)).max(0.0001)
+    }
+
+    /// Kelemen 清漆可见性项
+    pub fn visibility_kelemen(l_dot_h: f32) -> f32 {
+        0.25 / (l_dot_h * l_dot_h).max(0.0001)
+    }
+
     pub fn fresnel_schlick(cos_theta: f32, f0: Vector3<f32>) -> Vector3<f32> {
         let cos_theta = cos_theta.clamp(0.0, 1.0);
         let one_minus_cos_theta = 1.0 - cos_theta;
@@ -173,54 +497,341 @@ pub mod pbr {
     }
 }
 
-/// 材质参数应用（统一接口）
-pub fn apply_material_parameters(model: &mut Model, args: &RenderSettings) {
-    for material in &mut model.materials {
-        match material.material_type {
+/// 逐材质覆盖参数，对应 TOML 中的 `[[material_override]]`
+///
+/// `target` 可以是材质名（MTL 中的 `newmtl`）或网格名（OBJ 中的 `o`/`g`），
+/// 网格名匹配时该网格会获得一份独立的材质副本，不影响共享同一材质的其他网格。
+/// 颜色字段使用与 `RenderSettings` 相同的 "r,g,b" 字符串格式。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaterialOverride {
+    pub target: String,
+    pub base_color: Option<String>,
+    pub alpha: Option<f32>,
+    pub emissive: Option<String>,
+    pub metallic: Option<f32>,
+    pub roughness: Option<f32>,
+    pub ambient_occlusion: Option<f32>,
+    pub clearcoat: Option<f32>,
+    pub clearcoat_roughness: Option<f32>,
+    pub sheen_color: Option<String>,
+    pub sheen_roughness: Option<f32>,
+    pub anisotropy: Option<f32>,
+    pub anisotropy_rotation: Option<f32>,
+    pub transmission: Option<f32>,
+    pub specular_color: Option<String>,
+    pub shininess: Option<f32>,
+    pub diffuse_intensity: Option<f32>,
+    pub specular_intensity: Option<f32>,
+}
+
+impl MaterialOverride {
+    /// 以材质当前值生成完整的覆盖项（GUI 编辑单个材质时使用）
+    pub fn from_material(target: &str, material: &Material) -> Self {
+        let to_str = |v: &Vector3<f32>| format!("{},{},{}", v.x, v.y, v.z);
+        Self {
+            target: target.to_string(),
+            base_color: Some(to_str(&material.base_color)),
+            alpha: Some(material.alpha),
+            emissive: Some(to_str(&material.emissive)),
+            metallic: Some(material.metallic),
+            roughness: Some(material.roughness),
+            ambient_occlusion: Some(material.ambient_occlusion),
+            clearcoat: Some(material.clearcoat),
+            clearcoat_roughness: Some(material.clearcoat_roughness),
+            sheen_color: Some(to_str(&material.sheen_color)),
+            sheen_roughness: Some(material.sheen_roughness),
+            anisotropy: Some(material.anisotropy),
+            anisotropy_rotation: Some(material.anisotropy_rotation),
+            transmission: Some(material.transmission),
+            specular_color: Some(to_str(&material.specular)),
+            shininess: Some(material.shininess),
+            diffuse_intensity: Some(material.diffuse_intensity),
+            specular_intensity: Some(material.specular_intensity),
+        }
+    }
+
+    /// 将覆盖项中已设置的字段写入材质
+    pub fn apply_to(&self, material: &mut Material) {
+        let ambient_inputs = material.ambient_inputs();
+        let parse_color = |name: &str, value: &Option<String>| -> Option<Vector3<f32>> {
+            let value = value.as_ref()?;
+            parse_vec3(value)
+                .map_err(|e| warn!("材质覆盖 '{}' 的 {name} 无效: {e}", self.target))
+                .ok()
+        };
+
+        if let Some(base_color) = parse_color("base_color", &self.base_color) {
+            material.base_color = base_color;
+        }
+        if let Some(emissive) = parse_color("emissive", &self.emissive) {
+            material.emissive = emissive;
+        }
+        if let Some(specular) = parse_color("specular_color", &self.specular_color) {
+            material.specular = specular;
+        }
+        if let Some(sheen_color) = parse_color("sheen_color", &self.sheen_color) {
+            material.sheen_color = sheen_color;
+        }
+        if let Some(clearcoat) = self.clearcoat {
+            material.clearcoat = clearcoat.clamp(0.0, 1.0);
+        }
+        if let Some(clearcoat_roughness) = self.clearcoat_roughness {
+            material.clearcoat_roughness = clearcoat_roughness.clamp(0.0, 1.0);
+        }
+        if let Some(sheen_roughness) = self.sheen_roughness {
+            material.sheen_roughness = sheen_roughness.clamp(0.0, 1.0);
+        }
+        if let Some(anisotropy) = self.anisotropy {
+            material.anisotropy = anisotropy.clamp(-1.0, 1.0);
+        }
+        if let Some(anisotropy_rotation) = self.anisotropy_rotation {
+            material.anisotropy_rotation = anisotropy_rotation;
+        }
+        if let Some(transmission) = self.transmission {
+            material.transmission = transmission.clamp(0.0, 1.0);
+        }
+        if let Some(alpha) = self.alpha {
+            material.alpha = alpha.clamp(0.0, 1.0);
+        }
+        if let Some(metallic) = self.metallic {
+            material.metallic = metallic.clamp(0.0, 1.0);
+        }
+        if let Some(roughness) = self.roughness {
+            material.roughness = roughness.clamp(0.0, 1.0);
+        }
+        if let Some(ambient_occlusion) = self.ambient_occlusion {
+            material.ambient_occlusion = ambient_occlusion.clamp(0.0, 1.0);
+        }
+        if let Some(shininess) = self.shininess {
+            material.shininess = shininess.max(1.0);
+        }
+        if let Some(diffuse_intensity) = self.diffuse_intensity {
+            material.diffuse_intensity = diffuse_intensity.clamp(0.0, 2.0);
+        }
+        if let Some(specular_intensity) = self.specular_intensity {
+            material.specular_intensity = specular_intensity.clamp(0.0, 2.0);
+        }
+        material.refresh_ambient_factor(ambient_inputs);
+    }
+}
+
+impl Material {
+    /// 有参与 alpha 测试的透明度来源：遮罩纹理（map_d）或带 alpha 通道的漫反射纹理
+    pub fn has_alpha_cutout(&self) -> bool {
+        self.alpha_texture.is_some() || self.texture.as_ref().is_some_and(Texture::has_alpha)
+    }
+
+    /// 纹理坐标处的覆盖率：遮罩纹理与漫反射纹理 alpha 的乘积
+    pub fn alpha_coverage(&self, u: f32, v: f32) -> f32 {
+        let mut coverage = 1.0;
+        if let Some(mask) = &self.alpha_texture {
+            coverage *= mask.sample_mask(u, v);
+        }
+        if let Some(texture) = self.texture.as_ref().filter(|tex| tex.has_alpha()) {
+            coverage *= texture.sample_rgba(u, v)[3];
+        }
+        coverage
+    }
+
+    /// 推导环境光响应所依赖的参数：基础色、金属度和 AO
+    fn ambient_inputs(&self) -> (Vector3<f32>, f32, f32) {
+        (self.base_color, self.metallic, self.ambient_occlusion)
+    }
+
+    /// 参数修改后刷新环境光响应
+    ///
+    /// MTL 显式给出的 Ka 只在 `previous_inputs` 对应的基础色、金属度或 AO 被改动时才由它们重新推导。
+    pub fn refresh_ambient_factor(&mut self, previous_inputs: (Vector3<f32>, f32, f32)) {
+        if self.ambient_from_mtl && self.ambient_inputs() == previous_inputs {
+            return;
+        }
+        self.ambient_from_mtl = false;
+        self.update_ambient_factor();
+    }
+
+    /// 根据着色模型重新计算环境光响应
+    pub fn update_ambient_factor(&mut self) {
+        self.ambient_factor = match self.material_type {
             MaterialType::PBR => {
-                material.metallic = args.metallic.clamp(0.0, 1.0);
-                material.roughness = args.roughness.clamp(0.0, 1.0);
-                material.ambient_occlusion = args.ambient_occlusion.clamp(0.0, 1.0);
-                material.alpha = args.alpha.clamp(0.0, 1.0);
+                let ambient_response = self.ambient_occlusion * (1.0 - self.metallic);
+                Vector3::new(ambient_response, ambient_response, ambient_response)
+            }
+            _ => self.base_color * 0.3,
+        };
+    }
+}
 
-                if let Ok(base_color) = parse_vec3(&args.base_color) {
-                    material.base_color = base_color;
-                } else {
-                    warn!("无法解析基础颜色, 使用默认值: {:?}", material.base_color);
-                }
+impl Model {
+    /// 从原始模型恢复材质列表与网格的材质绑定
+    ///
+    /// 材质参数的应用需要以 MTL 原始值为起点，否则重复应用会累积修改。
+    pub fn restore_materials_from(&mut self, source: &Model) {
+        self.materials = source.materials.clone();
+        for (mesh, source_mesh) in self.all_meshes_mut().zip(source.all_meshes()) {
+            mesh.material_id = source_mesh.material_id;
+        }
+    }
 
-                if let Ok(emissive) = parse_vec3(&args.emissive) {
-                    material.emissive = emissive;
-                }
+    /// 所有细节层级的网格，原始网格在最前
+    pub fn all_meshes(&self) -> impl Iterator<Item = &Mesh> {
+        self.meshes.iter().chain(self.lods.iter().flatten())
+    }
 
-                let ambient_response = material.ambient_occlusion * (1.0 - material.metallic);
-                material.ambient_factor =
-                    Vector3::new(ambient_response, ambient_response, ambient_response);
+    /// 所有细节层级的网格（可变），原始网格在最前
+    pub fn all_meshes_mut(&mut self) -> impl Iterator<Item = &mut Mesh> {
+        self.meshes.iter_mut().chain(self.lods.iter_mut().flatten())
+    }
+}
+
+/// 用全局参数覆盖单个材质
+pub fn apply_global_parameters(material: &mut Material, args: &RenderSettings) {
+    let ambient_inputs = material.ambient_inputs();
+    match material.material_type {
+        MaterialType::PBR => {
+            material.metallic = args.metallic.clamp(0.0, 1.0);
+            material.roughness = args.roughness.clamp(0.0, 1.0);
+            material.ambient_occlusion = args.ambient_occlusion.clamp(0.0, 1.0);
+            material.alpha = args.alpha.clamp(0.0, 1.0);
+            material.clearcoat = args.clearcoat.clamp(0.0, 1.0);
+            material.clearcoat_roughness = args.clearcoat_roughness.clamp(0.0, 1.0);
+            material.sheen_roughness = args.sheen_roughness.clamp(0.0, 1.0);
+            material.anisotropy = args.anisotropy.clamp(-1.0, 1.0);
+            material.anisotropy_rotation = args.anisotropy_rotation;
+            material.transmission = args.transmission.clamp(0.0, 1.0);
+
+            if let Ok(sheen_color) = parse_vec3(&args.sheen_color) {
+                material.sheen_color = sheen_color;
             }
-            MaterialType::Phong => {
-                if let Ok(specular_color) = parse_vec3(&args.specular_color) {
-                    material.specular = specular_color;
-                } else {
-                    warn!("无法解析镜面反射颜色, 使用默认值: {:?}", material.specular);
-                }
 
-                material.shininess = args.shininess.max(1.0);
-                material.diffuse_intensity = args.diffuse_intensity.clamp(0.0, 2.0);
-                material.specular_intensity = args.specular_intensity.clamp(0.0, 2.0);
-                material.alpha = args.alpha.clamp(0.0, 1.0);
+            if let Ok(base_color) = parse_vec3(&args.base_color) {
+                material.base_color = base_color;
+            } else {
+                warn!("无法解析基础颜色, 使用默认值: {:?}", material.base_color);
+            }
 
-                if let Ok(diffuse_color) = parse_vec3(&args.diffuse_color) {
-                    material.base_color = diffuse_color;
-                } else {
-                    warn!("无法解析漫反射颜色, 使用默认值: {:?}", material.base_color);
-                }
+            if let Ok(emissive) = parse_vec3(&args.emissive) {
+                material.emissive = emissive;
+            }
+        }
+        _ => {
+            if let Ok(specular_color) = parse_vec3(&args.specular_color) {
+                material.specular = specular_color;
+            } else {
+                warn!("无法解析镜面反射颜色, 使用默认值: {:?}", material.specular);
+            }
 
-                if let Ok(emissive) = parse_vec3(&args.emissive) {
-                    material.emissive = emissive;
-                }
+            material.shininess = args.shininess.max(1.0);
+            material.diffuse_intensity = args.diffuse_intensity.clamp(0.0, 2.0);
+            material.specular_intensity = args.specular_intensity.clamp(0.0, 2.0);
+            material.alpha = args.alpha.clamp(0.0, 1.0);
+            material.roughness = args.roughness.clamp(0.0, 1.0);
+
+            if let Ok(diffuse_color) = parse_vec3(&args.diffuse_color) {
+                material.base_color = diffuse_color;
+            } else {
+                warn!("无法解析漫反射颜色, 使用默认值: {:?}", material.base_color);
+            }
+
+            if let Ok(emissive) = parse_vec3(&args.emissive) {
+                material.emissive = emissive;
+            }
+        }
+    }
+    material.refresh_ambient_factor(ambient_inputs);
+}
+
+/// 计算材质在当前设置下的最终参数（全局模式 + 按材质名覆盖）
+pub fn resolve_material(material: &Material, args: &RenderSettings) -> Material {
+    let mut resolved = material.clone();
+    // 着色模型是全局选择，切换后无需重新加载模型
+    resolved.material_type = args.shading_model;
+    resolved.toon_bands = args.toon_bands.max(2);
+    if args.material_mode == MaterialMode::Global {
+        apply_global_parameters(&mut resolved, args);
+    } else if resolved.roughness_from_shininess {
+        // 常用的 Blinn-Phong 指数与 GGX 粗糙度换算
+        resolved.roughness = (2.0 / (resolved.shininess.max(0.0) + 2.0))
+            .sqrt()
+            .clamp(0.0, 1.0);
+    }
+    for material_override in args
+        .material_overrides
+        .iter()
+        .filter(|o| o.target == material.name)
+    {
+        material_override.apply_to(&mut resolved);
+    }
+    resolved
+}
+
+/// 场景自带几何（地形、地面）的粗糙漫反射材质
+///
+/// 着色模型和风格化查找纹理跟随全局设置，但不受全局材质参数影响，可被同名的 `[[material_override]]` 修改。
+pub fn environment_material(
+    name: &str,
+    base_color: Vector3<f32>,
+    texture: Option<Texture>,
+    shared_textures: &SharedTextures,
+    args: &RenderSettings,
+) -> Material {
+    let mut material = Material::default(args.shading_model);
+    material.name = name.to_string();
+    material.base_color = base_color;
+    material.texture = texture;
+    material.ramp_texture = shared_textures.ramp.clone();
+    material.matcap_texture = shared_textures.matcap.clone();
+    material.roughness = 0.9;
+    material.specular = Vector3::new(0.1, 0.1, 0.1);
+    material.shininess = 8.0;
+    material.toon_bands = args.toon_bands.max(2);
+    for material_override in args.material_overrides.iter().filter(|o| o.target == name) {
+        material_override.apply_to(&mut material);
+    }
+    material.update_ambient_factor();
+    material
+}
+
+/// 材质参数应用（统一接口）
+///
+/// - `Global` 模式：全局参数覆盖所有材质（旧行为）
+/// - `FromFile` 模式：保留 MTL 中的材质参数
+///
+/// 随后依次应用按材质名、按网格名匹配的 `[[material_override]]`，网格名优先级更高。
+/// 按网格名覆盖时复制出的专用材质会被复用，重复调用不会使材质列表增长；
+/// 要撤销已移除的覆盖，调用前需用 [`Model::restore_materials_from`] 恢复加载时的材质。
+pub fn apply_material_parameters(model: &mut Model, args: &RenderSettings) {
+    for material in &mut model.materials {
+        *material = resolve_material(material, args);
+    }
 
-                material.ambient_factor = material.base_color * 0.3;
+    // 专用材质按网格名复用，重复调用不会追加新材质
+    for material_override in &args.material_overrides {
+        let target = material_override.target.as_str();
+        for mesh in model
+            .meshes
+            .iter_mut()
+            .chain(model.lods.iter_mut().flatten())
+            .filter(|mesh| mesh.name == target)
+        {
+            let Some(current) = model.materials.get(mesh.material_id) else {
+                continue;
+            };
+            if current.dedicated_mesh.as_deref() != Some(target) {
+                let existing = model.materials.iter().position(|material| {
+                    material.dedicated_mesh.as_deref() == Some(target)
+                        && material.name == current.name
+                });
+                mesh.material_id = match existing {
+                    Some(index) => index,
+                    None => {
+                        let mut dedicated = current.clone();
+                        dedicated.dedicated_mesh = Some(target.to_string());
+                        model.materials.push(dedicated);
+                        model.materials.len() - 1
+                    }
+                };
             }
+            material_override.apply_to(&mut model.materials[mesh.material_id]);
         }
     }
 }
@@ -1,25 +1,212 @@
 use image::{DynamicImage, GenericImageView};
 use log::warn;
+use nalgebra::Vector2;
 use std::path::Path;
 use std::sync::Arc;
 
 use crate::material_system::color::{Color, srgb_to_linear};
 
+/// 纹理坐标环绕模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WrapMode {
+    /// 重复平铺
+    #[default]
+    Repeat,
+    /// 钳制到边缘
+    Clamp,
+    /// 镜像重复
+    Mirror,
+}
+
+impl WrapMode {
+    /// 将任意纹理坐标映射到 [0, 1]
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            WrapMode::Repeat => t - t.floor(),
+            WrapMode::Clamp => t.clamp(0.0, 1.0),
+            WrapMode::Mirror => {
+                let period = t.rem_euclid(2.0);
+                if period > 1.0 { 2.0 - period } else { period }
+            }
+        }
+    }
+}
+
+/// 纹理颜色空间标记
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// 颜色贴图，采样时需要从 sRGB 解码到线性空间
+    #[default]
+    Srgb,
+    /// 数据贴图（透明度、粗糙度等），直接按线性值使用
+    Linear,
+}
+
+/// 纹理坐标变换，对应 MTL 的 `-o`/`-s` 选项
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvTransform {
+    pub offset: Vector2<f32>,
+    pub scale: Vector2<f32>,
+    /// 绕纹理中心 (0.5, 0.5) 的旋转角度（度）
+    pub rotation: f32,
+}
+
+impl Default for UvTransform {
+    fn default() -> Self {
+        Self {
+            offset: Vector2::zeros(),
+            scale: Vector2::new(1.0, 1.0),
+            rotation: 0.0,
+        }
+    }
+}
+
+impl UvTransform {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 变换顺序：缩放 -> 绕中心旋转 -> 偏移
+    pub fn apply(&self, u: f32, v: f32) -> (f32, f32) {
+        if self.is_identity() {
+            return (u, v);
+        }
+        let mut u = u * self.scale.x;
+        let mut v = v * self.scale.y;
+        if self.rotation != 0.0 {
+            let (sin, cos) = self.rotation.to_radians().sin_cos();
+            let (du, dv) = (u - 0.5, v - 0.5);
+            u = du * cos - dv * sin + 0.5;
+            v = du * sin + dv * cos + 0.5;
+        }
+        (u + self.offset.x, v + self.offset.y)
+    }
+}
+
+/// MTL 纹理语句中的选项（`map_Kd -o 0.5 0.5 -s 2 2 tex.png`）
+#[derive(Debug, Clone, Default)]
+pub struct TextureOptions {
+    pub wrap_mode: Option<WrapMode>,
+    pub color_space: Option<ColorSpace>,
+    pub uv_transform: UvTransform,
+}
+
+impl TextureOptions {
+    /// 解析 MTL 纹理语句，返回文件名与选项
+    ///
+    /// 支持标准选项 `-o`、`-s`、`-clamp`，以及扩展选项
+    /// `-wrap repeat|clamp|mirror`、`-rot <度>`、`-colorspace srgb|linear`。
+    /// 其余标准选项按其参数个数跳过，以 `#` 开头的记号及其后内容视为注释。
+    pub fn parse_statement(statement: &str) -> (String, TextureOptions) {
+        let tokens: Vec<&str> = statement
+            .split_whitespace()
+            .take_while(|t| !t.starts_with('#'))
+            .collect();
+        let mut options = TextureOptions::default();
+        let mut i = 0;
+
+        // 读取紧随选项之后的 1~3 个数值
+        let read_numbers = |start: usize, max: usize| -> Vec<f32> {
+            tokens[start..]
+                .iter()
+                .take(max)
+                .map_while(|t| t.parse::<f32>().ok())
+                .collect()
+        };
+
+        while i < tokens.len() && tokens[i].starts_with('-') {
+            let option = tokens[i];
+            i += 1;
+            match option {
+                "-o" => {
+                    let values = read_numbers(i, 3);
+                    if let Some(&u) = values.first() {
+                        options.uv_transform.offset.x = u;
+                    }
+                    if let Some(&v) = values.get(1) {
+                        options.uv_transform.offset.y = v;
+                    }
+                    i += values.len();
+                }
+                "-s" => {
+                    let values = read_numbers(i, 3);
+                    if let Some(&u) = values.first() {
+                        options.uv_transform.scale.x = u;
+                        // 只给出一个分量时视为均匀缩放
+                        options.uv_transform.scale.y = values.get(1).copied().unwrap_or(u);
+                    }
+                    i += values.len();
+                }
+                "-rot" => {
+                    let values = read_numbers(i, 1);
+                    if let Some(&deg) = values.first() {
+                        options.uv_transform.rotation = deg;
+                    }
+                    i += values.len();
+                }
+                "-clamp" => {
+                    if let Some(flag) = tokens.get(i) {
+                        options.wrap_mode = Some(if *flag == "on" {
+                            WrapMode::Clamp
+                        } else {
+                            WrapMode::Repeat
+                        });
+                        i += 1;
+                    }
+                }
+                "-wrap" => {
+                    if let Some(mode) = tokens.get(i) {
+                        match *mode {
+                            "repeat" => options.wrap_mode = Some(WrapMode::Repeat),
+                            "clamp" => options.wrap_mode = Some(WrapMode::Clamp),
+                            "mirror" => options.wrap_mode = Some(WrapMode::Mirror),
+                            other => warn!("未知的纹理环绕模式: {other}"),
+                        }
+                        i += 1;
+                    }
+                }
+                "-colorspace" => {
+                    if let Some(space) = tokens.get(i) {
+                        match space.to_ascii_lowercase().as_str() {
+                            "srgb" => options.color_space = Some(ColorSpace::Srgb),
+                            "linear" | "raw" => options.color_space = Some(ColorSpace::Linear),
+                            other => warn!("未知的纹理颜色空间: {other}"),
+                        }
+                        i += 1;
+                    }
+                }
+                "-blendu" | "-blendv" | "-cc" | "-imfchan" | "-type" => i += 1,
+                "-bm" | "-boost" | "-texres" => i += 1,
+                "-mm" => i += 2,
+                "-t" => i += read_numbers(i, 3).len(),
+                other => {
+                    warn!("未知的纹理选项: {other}");
+                }
+            }
+        }
+
+        let file_name = tokens
+            .get(i..)
+            .map(|rest| rest.join(" "))
+            .unwrap_or_default();
+        (file_name, options)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Texture {
     pub image: Arc<DynamicImage>,
     pub width: u32,
     pub height: u32,
+    pub wrap_mode: WrapMode,
+    pub color_space: ColorSpace,
+    pub uv_transform: UvTransform,
 }
 
 impl Texture {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Option<Self> {
         match image::open(path) {
-            Ok(img) => Some(Texture {
-                width: img.width(),
-                height: img.height(),
-                image: Arc::new(img),
-            }),
+            Ok(img) => Some(Self::from_image(img)),
             Err(e) => {
                 warn!("无法加载纹理: {e}");
                 None
@@ -27,19 +214,80 @@ impl Texture {
         }
     }
 
+    pub fn from_image(img: DynamicImage) -> Self {
+        Texture {
+            width: img.width(),
+            height: img.height(),
+            image: Arc::new(img),
+            wrap_mode: WrapMode::default(),
+            color_space: ColorSpace::default(),
+            uv_transform: UvTransform::default(),
+        }
+    }
+
+    /// 纹理加载失败时使用的 1x1 占位纹理
+    pub fn fallback() -> Self {
+        Self::from_image(DynamicImage::new_rgb8(1, 1))
+    }
+
+    /// 应用 MTL 纹理选项，未指定的项保持原值
+    pub fn with_options(mut self, options: &TextureOptions) -> Self {
+        if let Some(wrap_mode) = options.wrap_mode {
+            self.wrap_mode = wrap_mode;
+        }
+        if let Some(color_space) = options.color_space {
+            self.color_space = color_space;
+        }
+        self.uv_transform = options.uv_transform;
+        self
+    }
+
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// 图像是否带有 alpha 通道
+    pub fn has_alpha(&self) -> bool {
+        self.image.color().has_alpha()
+    }
+
+    fn fetch_pixel(&self, u: f32, v: f32) -> [u8; 4] {
+        let (u, v) = self.uv_transform.apply(u, v);
+        let u = self.wrap_mode.apply(u);
+        let v = self.wrap_mode.apply(v);
+        let x = ((u * self.width as f32) as u32).min(self.width - 1);
+        let y = (((1.0 - v) * self.height as f32) as u32).min(self.height - 1);
+        self.image.get_pixel(x, y).0
+    }
+
     pub fn sample(&self, u: f32, v: f32) -> [f32; 3] {
-        let u = u.fract().abs();
-        let v = v.fract().abs();
-        let x = (u * self.width as f32) as u32 % self.width;
-        let y = ((1.0 - v) * self.height as f32) as u32 % self.height;
+        let [r, g, b, _] = self.sample_rgba(u, v);
+        [r, g, b]
+    }
 
-        let pixel = self.image.get_pixel(x, y);
-        let srgb_color = Color::new(
+    /// 采样 RGBA，RGB 按颜色空间解码，alpha 始终为线性值
+    pub fn sample_rgba(&self, u: f32, v: f32) -> [f32; 4] {
+        let pixel = self.fetch_pixel(u, v);
+        let color = Color::new(
             pixel[0] as f32 / 255.0,
             pixel[1] as f32 / 255.0,
             pixel[2] as f32 / 255.0,
         );
-        let linear_color = srgb_to_linear(&srgb_color);
-        [linear_color.x, linear_color.y, linear_color.z]
+        let color = match self.color_space {
+            ColorSpace::Srgb => srgb_to_linear(&color),
+            ColorSpace::Linear => color,
+        };
+        [color.x, color.y, color.z, pixel[3] as f32 / 255.0]
+    }
+
+    /// 采样遮罩值：有 alpha 通道时取 alpha，否则取红色通道（灰度图）
+    pub fn sample_mask(&self, u: f32, v: f32) -> f32 {
+        let pixel = self.fetch_pixel(u, v);
+        if self.has_alpha() {
+            pixel[3] as f32 / 255.0
+        } else {
+            pixel[0] as f32 / 255.0
+        }
     }
 }
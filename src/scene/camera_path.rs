@@ -0,0 +1,297 @@
+use crate::io::render_settings::{RenderSettings, parse_point3, parse_vec3};
+use nalgebra::{Point3, Rotation3, Unit, Vector3};
+
+/// 弧长查找表中每段曲线的采样数
+const ARC_SAMPLES_PER_SEGMENT: usize = 32;
+
+/// 相机路径的曲线类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplineType {
+    /// 经过所有控制点的均匀 Catmull-Rom 样条
+    #[default]
+    CatmullRom,
+    /// 分段三次贝塞尔：锚点, 控制点, 控制点, 锚点, ...
+    Bezier,
+}
+
+impl SplineType {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "catmull_rom" => Ok(Self::CatmullRom),
+            "bezier" => Ok(Self::Bezier),
+            _ => Err(format!("未知的路径曲线类型: {name}")),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CatmullRom => "catmull_rom",
+            Self::Bezier => "bezier",
+        }
+    }
+
+    /// 控制点数量对应的曲线段数，数量不合法时返回错误
+    fn segment_count(&self, point_count: usize, closed: bool) -> Result<usize, String> {
+        match self {
+            Self::CatmullRom => {
+                if point_count < 2 {
+                    return Err("Catmull-Rom 路径至少需要 2 个控制点".to_string());
+                }
+                Ok(if closed { point_count } else { point_count - 1 })
+            }
+            Self::Bezier => {
+                if closed && point_count >= 3 && point_count.is_multiple_of(3) {
+                    Ok(point_count / 3)
+                } else if !closed && point_count >= 4 && (point_count - 1).is_multiple_of(3) {
+                    Ok((point_count - 1) / 3)
+                } else if closed {
+                    Err(format!(
+                        "闭合贝塞尔路径的控制点数应为 3 的倍数，当前为 {point_count}"
+                    ))
+                } else {
+                    Err(format!(
+                        "贝塞尔路径的控制点数应为 3n+1，当前为 {point_count}"
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// `[camera_path]` 配置：相机位置沿样条移动，注视点固定或沿第二条样条移动
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraPath {
+    pub spline: SplineType,
+    /// 相机位置的控制点
+    pub points: Vec<Point3<f32>>,
+    /// 首尾相连，循环播放时没有跳变
+    pub closed: bool,
+    /// 固定注视点，未设置时使用 `[camera] at`
+    pub target: Option<Point3<f32>>,
+    /// 注视点路径的控制点（曲线类型与闭合方式同相机路径），非空时优先于 `target`
+    pub target_points: Vec<Point3<f32>>,
+    /// 走完整条路径的时长（秒）
+    pub duration: f32,
+    /// 按弧长匀速移动，否则每段曲线用时相同
+    pub constant_speed: bool,
+    /// 绕视线方向的滚转角（度），沿路径均匀分布并线性插值；单个值表示恒定滚转
+    pub roll: Vec<f32>,
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        Self {
+            spline: SplineType::default(),
+            points: Vec::new(),
+            closed: false,
+            target: None,
+            target_points: Vec::new(),
+            duration: 5.0,
+            constant_speed: true,
+            roll: Vec::new(),
+        }
+    }
+}
+
+impl CameraPath {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.duration.is_nan() || self.duration <= 0.0 {
+            return Err("相机路径时长必须大于0".to_string());
+        }
+        self.spline.segment_count(self.points.len(), self.closed)?;
+        if !self.target_points.is_empty() {
+            self.spline
+                .segment_count(self.target_points.len(), self.closed)
+                .map_err(|e| format!("注视点路径: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// 带弧长查找表的样条
+struct Spline {
+    spline: SplineType,
+    points: Vec<Point3<f32>>,
+    closed: bool,
+    segments: usize,
+    /// 均匀参数采样处的累计弧长，首项为 0
+    arc_lengths: Vec<f32>,
+}
+
+impl Spline {
+    fn new(spline: SplineType, points: &[Point3<f32>], closed: bool) -> Result<Self, String> {
+        let segments = spline.segment_count(points.len(), closed)?;
+        let mut result = Self {
+            spline,
+            points: points.to_vec(),
+            closed,
+            segments,
+            arc_lengths: Vec::new(),
+        };
+
+        let samples = segments * ARC_SAMPLES_PER_SEGMENT;
+        let mut total = 0.0;
+        let mut previous = result.evaluate(0.0);
+        result.arc_lengths.reserve(samples + 1);
+        result.arc_lengths.push(0.0);
+        for i in 1..=samples {
+            let current = result.evaluate(i as f32 / samples as f32);
+            total += (current - previous).norm();
+            result.arc_lengths.push(total);
+            previous = current;
+        }
+        Ok(result)
+    }
+
+    /// 按控制点索引取点，闭合路径回绕，开放路径钳制到端点
+    fn point(&self, index: isize) -> Vector3<f32> {
+        let count = self.points.len() as isize;
+        let index = if self.closed {
+            index.rem_euclid(count)
+        } else {
+            index.clamp(0, count - 1)
+        };
+        self.points[index as usize].coords
+    }
+
+    /// 曲线参数 `u` ∈ [0, 1] 处的位置，每段曲线占相同的参数区间
+    fn evaluate(&self, u: f32) -> Point3<f32> {
+        let scaled = u.clamp(0.0, 1.0) * self.segments as f32;
+        let segment = (scaled.floor() as usize).min(self.segments - 1);
+        let t = scaled - segment as f32;
+        let segment = segment as isize;
+
+        let position = match self.spline {
+            SplineType::CatmullRom => {
+                let p0 = self.point(segment - 1);
+                let p1 = self.point(segment);
+                let p2 = self.point(segment + 1);
+                let p3 = self.point(segment + 2);
+                let t2 = t * t;
+                let t3 = t2 * t;
+                (p1 * 2.0
+                    + (p2 - p0) * t
+                    + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+                    + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+                    * 0.5
+            }
+            SplineType::Bezier => {
+                let base = segment * 3;
+                let p0 = self.point(base);
+                let p1 = self.point(base + 1);
+                let p2 = self.point(base + 2);
+                let p3 = self.point(base + 3);
+                let s = 1.0 - t;
+                p0 * (s * s * s)
+                    + p1 * (3.0 * s * s * t)
+                    + p2 * (3.0 * s * t * t)
+                    + p3 * (t * t * t)
+            }
+        };
+        Point3::from(position)
+    }
+
+    /// 把路径进度（0..1，按弧长）换算成曲线参数
+    fn parameter_at_distance(&self, progress: f32) -> f32 {
+        let total = *self.arc_lengths.last().unwrap_or(&0.0);
+        if total <= f32::EPSILON {
+            return progress;
+        }
+        let distance = progress.clamp(0.0, 1.0) * total;
+        let upper = self
+            .arc_lengths
+            .partition_point(|&length| length < distance)
+            .clamp(1, self.arc_lengths.len() - 1);
+        let (before, after) = (self.arc_lengths[upper - 1], self.arc_lengths[upper]);
+        let fraction = if after > before {
+            (distance - before) / (after - before)
+        } else {
+            0.0
+        };
+        (upper as f32 - 1.0 + fraction) / (self.arc_lengths.len() - 1) as f32
+    }
+
+    fn sample(&self, progress: f32, constant_speed: bool) -> Point3<f32> {
+        let u = if constant_speed {
+            self.parameter_at_distance(progress)
+        } else {
+            progress
+        };
+        self.evaluate(u)
+    }
+}
+
+/// 预先建立弧长表的相机路径，按时间采样相机位置、注视点和上方向
+pub struct CameraPathSampler {
+    position: Spline,
+    target: Option<Spline>,
+    fixed_target: Option<Point3<f32>>,
+    duration: f32,
+    constant_speed: bool,
+    roll: Vec<f32>,
+}
+
+impl CameraPathSampler {
+    pub fn new(path: &CameraPath) -> Result<Self, String> {
+        path.validate()?;
+        let target = if path.target_points.is_empty() {
+            None
+        } else {
+            Some(Spline::new(path.spline, &path.target_points, path.closed)?)
+        };
+        Ok(Self {
+            position: Spline::new(path.spline, &path.points, path.closed)?,
+            target,
+            fixed_target: path.target,
+            duration: path.duration,
+            constant_speed: path.constant_speed,
+            roll: path.roll.clone(),
+        })
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// 闭合路径的终点与起点是同一位姿
+    pub fn is_closed(&self) -> bool {
+        self.position.closed
+    }
+
+    /// 在路径进度 `progress` 处的滚转角（度）
+    fn roll_degrees(&self, progress: f32) -> f32 {
+        match self.roll.as_slice() {
+            [] => 0.0,
+            [single] => *single,
+            values => {
+                let scaled = progress.clamp(0.0, 1.0) * (values.len() - 1) as f32;
+                let index = (scaled.floor() as usize).min(values.len() - 2);
+                let t = scaled - index as f32;
+                values[index] + (values[index + 1] - values[index]) * t
+            }
+        }
+    }
+
+    /// 把第 `time` 秒的相机位置、注视点和上方向写入设置
+    pub fn apply(&self, time: f32, settings: &mut RenderSettings) -> Result<(), String> {
+        let progress = (time / self.duration).clamp(0.0, 1.0);
+        let from = self.position.sample(progress, self.constant_speed);
+        let at = match (&self.target, self.fixed_target) {
+            (Some(target), _) => target.sample(progress, self.constant_speed),
+            (None, Some(fixed)) => fixed,
+            (None, None) => parse_point3(&settings.camera_at)?,
+        };
+        settings.camera_from = format!("{},{},{}", from.x, from.y, from.z);
+        settings.camera_at = format!("{},{},{}", at.x, at.y, at.z);
+
+        let roll = self.roll_degrees(progress);
+        if roll != 0.0
+            && let Some(forward) = Unit::try_new(at - from, f32::EPSILON)
+        {
+            let up = Rotation3::from_axis_angle(&forward, roll.to_radians())
+                * parse_vec3(&settings.camera_up)?;
+            settings.camera_up = format!("{},{},{}", up.x, up.y, up.z);
+        }
+        Ok(())
+    }
+}
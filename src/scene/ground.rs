@@ -0,0 +1,121 @@
+use crate::io::obj_loader::SharedTextures;
+use crate::io::render_settings::RenderSettings;
+use crate::material_system::materials::{Mesh, Vertex, environment_material};
+use crate::material_system::texture::Texture;
+use crate::scene::scene_object::SceneObject;
+use nalgebra::{Point3, Vector2, Vector3};
+
+/// 地面网格每边的单元数
+///
+/// 光栅化器没有近平面裁剪，顶点位于相机后方的三角形会被整体丢弃，
+/// 细分后被丢弃的只是相机脚下很小的一块。
+const GROUND_CELLS: usize = 64;
+
+/// 决定地面网格的参数，变化时需要重新生成
+#[derive(Debug, Clone, PartialEq)]
+struct GroundKey {
+    size: f32,
+    height: f32,
+    texture: Option<String>,
+    texture_tiling: f32,
+}
+
+impl GroundKey {
+    fn from_settings(settings: &RenderSettings) -> Self {
+        Self {
+            size: settings.ground_plane_size,
+            height: settings.ground_plane_height,
+            texture: settings.ground_plane_texture.clone(),
+            texture_tiling: settings.ground_plane_texture_tiling,
+        }
+    }
+}
+
+/// 真实几何地面
+///
+/// 与模型一起做深度测试，参与所有光源的光照并接收阴影；
+/// 地面本身不投射阴影，也不扩大阴影贴图的覆盖范围。
+#[derive(Debug, Clone)]
+pub struct GroundPlane {
+    key: GroundKey,
+    texture: Option<Texture>,
+    shared_textures: SharedTextures,
+    pub object: SceneObject,
+}
+
+impl GroundPlane {
+    pub fn build(settings: &RenderSettings) -> Result<Self, String> {
+        let key = GroundKey::from_settings(settings);
+        let texture = match &key.texture {
+            Some(path) => {
+                Some(Texture::from_file(path).ok_or_else(|| format!("无法加载地面纹理: {path}"))?)
+            }
+            None => None,
+        };
+
+        let side = GROUND_CELLS + 1;
+        let vertices = (0..side * side)
+            .map(|index| {
+                let u = (index % side) as f32 / GROUND_CELLS as f32;
+                let v = (index / side) as f32 / GROUND_CELLS as f32;
+                Vertex {
+                    position: Point3::new((u - 0.5) * key.size, key.height, (v - 0.5) * key.size),
+                    normal: Vector3::y(),
+                    texcoord: Vector2::new(u, 1.0 - v) * key.texture_tiling,
+                }
+            })
+            .collect();
+        let mut indices = Vec::with_capacity(GROUND_CELLS * GROUND_CELLS * 6);
+        for row in 0..GROUND_CELLS {
+            for column in 0..GROUND_CELLS {
+                let a = (row * side + column) as u32;
+                let b = a + 1;
+                let c = a + side as u32;
+                let d = c + 1;
+                // 从 +Y 俯视时为逆时针
+                indices.extend_from_slice(&[a, c, b, b, c, d]);
+            }
+        }
+
+        let mut object = SceneObject::empty("ground");
+        object.model.meshes.push(Mesh {
+            vertices,
+            indices,
+            lines: Vec::new(),
+            points: Vec::new(),
+            material_id: 0,
+            name: "ground".to_string(),
+            skin_weights: Vec::new(),
+            morph_targets: Vec::new(),
+        });
+        object.cast_shadows = false;
+        object.receive_shadows = true;
+        object.cull_behind_camera = true;
+
+        let mut ground = Self {
+            key,
+            texture,
+            shared_textures: SharedTextures::load_lookups(settings),
+            object,
+        };
+        ground.update_parameters(settings);
+        Ok(ground)
+    }
+
+    /// 设置变化后是否需要重新生成网格
+    pub fn needs_rebuild(&self, settings: &RenderSettings) -> bool {
+        self.key != GroundKey::from_settings(settings)
+    }
+
+    /// 同步材质参数
+    pub fn update_parameters(&mut self, settings: &RenderSettings) {
+        self.shared_textures.sync_lookups(settings);
+        self.object.model.materials = vec![environment_material(
+            "ground",
+            settings.get_ground_plane_color_vec(),
+            self.texture.clone(),
+            &self.shared_textures,
+            settings,
+        )];
+    }
+}
@@ -1,2 +1,6 @@
+pub mod camera_path;
+pub mod ground;
 pub mod scene_object;
 pub mod scene_utils;
+pub mod terrain;
+pub mod timeline;
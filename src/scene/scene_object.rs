@@ -1,6 +1,12 @@
+use crate::geometry::camera::{Camera, ProjectionType};
+use crate::geometry::edges::EdgeAdjacency;
+use crate::geometry::morph::morph_vertices;
+use crate::geometry::skeleton::skin_vertices;
 use crate::geometry::transform::TransformFactory;
-use crate::material_system::materials::Model;
-use nalgebra::{Matrix4, Vector3};
+use crate::material_system::materials::{Mesh, Model, Vertex};
+use nalgebra::{Matrix4, Point3, Vector3};
+use std::borrow::Cow;
+use std::sync::OnceLock;
 
 /// 表示场景中的单个对象实例
 ///
@@ -12,14 +18,51 @@ pub struct SceneObject {
 
     /// 对象在世界空间中的变换矩阵
     pub transform: Matrix4<f32>,
+
+    /// 是否写入阴影贴图
+    pub cast_shadows: bool,
+
+    /// 是否在着色时查询阴影贴图
+    pub receive_shadows: bool,
+
+    /// 是否丢弃有顶点位于相机后方的三角形
+    ///
+    /// 渲染器没有近平面裁剪，延伸到相机下方的地面网格需要开启，
+    /// 否则这些三角形的投影会横跨整个画面。
+    pub cull_behind_camera: bool,
+
+    /// 当前选中的细节层级，0 为原始网格，`n` 对应 `model.lods[n - 1]`
+    pub lod_level: usize,
+
+    /// 当前姿态的蒙皮矩阵（模型空间），为空时使用静止姿态
+    pub skin_matrices: Vec<Matrix4<f32>>,
+
+    /// 各变形目标的当前权重（按名称），未列出的目标权重为 0
+    pub morph_weights: Vec<(String, f32)>,
+
+    /// 局部空间包围球（中心, 半径），用于估算屏幕尺寸
+    bounding_sphere: Option<(Point3<f32>, f32)>,
+
+    /// 各细节层级的边-面邻接关系，下标同 `lod_level`，首次使用时构建，替换模型时清空
+    edge_adjacency: Vec<OnceLock<EdgeAdjacency>>,
 }
 
 impl SceneObject {
     /// 从模型数据创建新的场景对象
     pub fn from_model_data(model: Model) -> Self {
+        let bounding_sphere = Self::compute_bounding_sphere(&model);
+        let edge_adjacency = Self::empty_adjacency_cache(&model);
         Self {
             model,
             transform: Matrix4::identity(),
+            cast_shadows: true,
+            receive_shadows: false,
+            cull_behind_camera: false,
+            lod_level: 0,
+            skin_matrices: Vec::new(),
+            morph_weights: Vec::new(),
+            bounding_sphere,
+            edge_adjacency,
         }
     }
 
@@ -30,8 +73,19 @@ impl SceneObject {
                 meshes: Vec::new(),
                 materials: Vec::new(),
                 name: name.to_string(),
+                lods: Vec::new(),
+                skeleton: None,
+                normalization: (Vector3::zeros(), 1.0),
             },
             transform: Matrix4::identity(),
+            cast_shadows: true,
+            receive_shadows: false,
+            cull_behind_camera: false,
+            lod_level: 0,
+            skin_matrices: Vec::new(),
+            morph_weights: Vec::new(),
+            bounding_sphere: None,
+            edge_adjacency: vec![OnceLock::new()],
         }
     }
 
@@ -57,6 +111,165 @@ impl SceneObject {
             * scale_matrix;
     }
 
+    /// 当前细节层级的边-面邻接关系，同一层级只构建一次
+    ///
+    /// 须在 [`Self::swap_lod_meshes`] 换入当前层级之后调用（渲染期间），此时 `model.meshes` 即该层级的网格。
+    pub fn edge_adjacency(&self) -> Cow<'_, EdgeAdjacency> {
+        match self.edge_adjacency.get(self.lod_level) {
+            Some(cache) => Cow::Borrowed(cache.get_or_init(|| EdgeAdjacency::build(&self.model))),
+            None => Cow::Owned(EdgeAdjacency::build(&self.model)),
+        }
+    }
+
+    fn empty_adjacency_cache(model: &Model) -> Vec<OnceLock<EdgeAdjacency>> {
+        (0..=model.lods.len()).map(|_| OnceLock::new()).collect()
+    }
+
+    /// 替换几何数据（如 OBJ 序列的下一帧），保留变换和变形权重
+    pub fn set_model(&mut self, model: Model) {
+        self.bounding_sphere = Self::compute_bounding_sphere(&model);
+        self.edge_adjacency = Self::empty_adjacency_cache(&model);
+        self.model = model;
+        self.lod_level = 0;
+        self.skin_matrices.clear();
+    }
+
+    /// 世界空间轴对齐包围盒，没有顶点时返回 None
+    pub fn world_bounds(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        let mut vertices = self.model.meshes.iter().flat_map(|mesh| &mesh.vertices);
+        let first = self.transform.transform_point(&vertices.next()?.position);
+        Some(vertices.fold((first, first), |(min, max), vertex| {
+            let world = self.transform.transform_point(&vertex.position);
+            (min.inf(&world), max.sup(&world))
+        }))
+    }
+
+    /// 包围球投影到屏幕上的直径（像素），没有顶点时返回 None
+    pub fn projected_diameter(&self, camera: &Camera, viewport_height: usize) -> Option<f32> {
+        let (center, radius) = self.bounding_sphere?;
+        let world_center = self.transform.transform_point(&center);
+        // 非均匀缩放取最大的轴向缩放
+        let max_scale = (0..3)
+            .map(|axis| self.transform.fixed_view::<3, 1>(0, axis).norm())
+            .fold(0.0_f32, f32::max);
+        let world_radius = radius * max_scale;
+        let pixels_per_unit = camera.projection_matrix()[(1, 1)] * viewport_height as f32 * 0.5;
+        let diameter = 2.0 * world_radius * pixels_per_unit;
+        Some(match camera.params.projection {
+            ProjectionType::Perspective { .. } => {
+                // 相机位于包围球内时按贴脸处理，取最高细节
+                let distance = (world_center - camera.position()).norm();
+                diameter / distance.max(world_radius).max(1e-6)
+            }
+            ProjectionType::Orthographic { .. } => diameter,
+        })
+    }
+
+    /// 按屏幕尺寸选择细节层级，返回层级是否改变
+    ///
+    /// 投影直径小于 `switch_size` 时切换到 LOD1，之后每缩小一半再降一级。
+    /// `hysteresis` 为 `Some` 时从当前层级出发，切换阈值两侧留有该比例的缓冲带，
+    /// 尺寸在阈值附近抖动时不会反复切换，但结果依赖之前的层级；
+    /// 为 `None` 时直接按阈值选择，结果只取决于当前相机。
+    pub fn update_lod(
+        &mut self,
+        camera: &Camera,
+        viewport_height: usize,
+        switch_size: f32,
+        hysteresis: Option<f32>,
+    ) -> bool {
+        let previous = self.lod_level;
+        let max_level = self.model.lods.len();
+        let Some(diameter) = self
+            .projected_diameter(camera, viewport_height)
+            .filter(|_| max_level > 0)
+        else {
+            self.lod_level = 0;
+            return previous != 0;
+        };
+
+        // 第 level - 1 级与第 level 级之间的切换阈值
+        let threshold = |level: usize| switch_size * 0.5_f32.powi(level as i32 - 1);
+        let mut level = match hysteresis {
+            Some(_) => previous.min(max_level),
+            None => 0,
+        };
+        let hysteresis = hysteresis.unwrap_or(0.0);
+        while level < max_level && diameter < threshold(level + 1) * (1.0 - hysteresis) {
+            level += 1;
+        }
+        while level > 0 && diameter > threshold(level) * (1.0 + hysteresis) {
+            level -= 1;
+        }
+        self.lod_level = level;
+        level != previous
+    }
+
+    /// 交换原始网格与当前层级的网格
+    ///
+    /// 渲染前后成对调用：渲染期间 `model.meshes` 是所选层级，其余时间始终是原始网格。
+    pub fn swap_lod_meshes(&mut self) {
+        if let Some(level_meshes) = self
+            .lod_level
+            .checked_sub(1)
+            .and_then(|index| self.model.lods.get_mut(index))
+        {
+            std::mem::swap(&mut self.model.meshes, level_meshes);
+        }
+    }
+
+    fn compute_bounding_sphere(model: &Model) -> Option<(Point3<f32>, f32)> {
+        let mut positions = model
+            .meshes
+            .iter()
+            .flat_map(|mesh| mesh.vertices.iter().map(|vertex| vertex.position));
+        let first = positions.next()?;
+        let (min, max) = positions
+            .clone()
+            .fold((first, first), |(min, max), p| (min.inf(&p), max.sup(&p)));
+        let center = nalgebra::center(&min, &max);
+        let radius = std::iter::once(first)
+            .chain(positions)
+            .map(|p| (p - center).norm())
+            .fold(0.0_f32, f32::max);
+        Some((center, radius))
+    }
+
+    /// 把骨骼动画摆到第 `time` 秒的姿态，模型没有骨架时不做任何事
+    pub fn set_pose(&mut self, time: f32, looping: bool) {
+        if let Some(skeleton) = &self.model.skeleton {
+            self.skin_matrices = skeleton.skin_matrices(time, looping);
+        }
+    }
+
+    /// 网格在当前姿态下的顶点：先叠加变形目标，再对绑定了骨骼的网格执行线性混合蒙皮
+    ///
+    /// 两者都不生效时直接借用静止姿态。
+    pub fn posed_vertices<'a>(&self, mesh: &'a Mesh) -> Cow<'a, [Vertex]> {
+        let morphed = mesh.morph_targets.iter().any(|target| {
+            self.morph_weights
+                .iter()
+                .any(|(name, weight)| *name == target.name && *weight != 0.0)
+        });
+        let mut vertices = if morphed {
+            Cow::Owned(morph_vertices(
+                &mesh.vertices,
+                &mesh.morph_targets,
+                &self.morph_weights,
+            ))
+        } else {
+            Cow::Borrowed(mesh.vertices.as_slice())
+        };
+        if !self.skin_matrices.is_empty() && mesh.skin_weights.len() == mesh.vertices.len() {
+            vertices = Cow::Owned(skin_vertices(
+                &vertices,
+                &mesh.skin_weights,
+                &self.skin_matrices,
+            ));
+        }
+        vertices
+    }
+
     /// 应用增量旋转（用于动画）
     pub fn rotate(&mut self, axis: &Vector3<f32>, angle_rad: f32) {
         let rotation_matrix = TransformFactory::rotation(axis, angle_rad);
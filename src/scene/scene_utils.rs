@@ -1,9 +1,12 @@
-use crate::geometry::camera::Camera;
-use crate::io::render_settings::{RenderSettings, parse_point3, parse_vec3};
+use crate::geometry::camera::{Camera, ProjectionType};
+use crate::io::render_settings::{NormalizeMode, RenderSettings, parse_point3, parse_vec3};
 use crate::material_system::light::Light;
 use crate::material_system::materials::Model;
 use crate::material_system::materials::apply_material_parameters;
+use crate::scene::ground::GroundPlane;
 use crate::scene::scene_object::SceneObject;
+use crate::scene::terrain::Terrain;
+use log::debug;
 use nalgebra::Vector3;
 
 /// 表示一个 3D 场景，包含对象、光源和相机
@@ -12,6 +15,12 @@ pub struct Scene {
     /// 场景中的主要对象（简化为单个对象）
     pub object: SceneObject,
 
+    /// 高度图地形（位于世界空间，不随对象变换）
+    pub terrain: Option<Terrain>,
+
+    /// 真实几何地面
+    pub ground: Option<GroundPlane>,
+
     /// 场景中的光源
     pub lights: Vec<Light>,
 
@@ -23,6 +32,9 @@ pub struct Scene {
 
     /// 环境光颜色
     pub ambient_color: Vector3<f32>,
+
+    /// 细节层级以上一次渲染的选择为起点并带滞后；逐帧独立渲染的动画关闭，每帧只取决于自身相机
+    pub lod_hysteresis: bool,
 }
 
 impl Scene {
@@ -48,27 +60,46 @@ impl Scene {
         let aspect_ratio = settings.width as f32 / settings.height as f32;
         let camera_from =
             parse_point3(&settings.camera_from).map_err(|e| format!("无效的相机位置格式: {e}"))?;
+
+        // 裁剪面与正交视野按单位尺寸模型标定，保留真实尺寸时按包围盒等比缩放；
+        // 远平面至少覆盖相机到包围盒最远角的距离，远离原点的模型也不会被裁掉
+        let bounds = object.world_bounds();
+        let scene_scale = match (&settings.object_normalize, bounds) {
+            (NormalizeMode::Unit, _) | (_, None) => 1.0,
+            (_, Some((min, max))) => ((max - min).max() / 1.6).max(1e-6),
+        };
+        let farthest_corner = bounds.map_or(0.0, |(min, max)| {
+            (camera_from - min)
+                .abs()
+                .sup(&(camera_from - max).abs())
+                .norm()
+        });
+        let (near, far) = (
+            0.1 * scene_scale,
+            (100.0 * scene_scale).max(farthest_corner * 1.5),
+        );
         let camera_at =
             parse_point3(&settings.camera_at).map_err(|e| format!("无效的相机目标格式: {e}"))?;
         let camera_up =
             parse_vec3(&settings.camera_up).map_err(|e| format!("无效的相机上方向格式: {e}"))?;
-        let camera = match settings.projection.as_str() {
+        let mut camera = match settings.projection.as_str() {
             "perspective" => Camera::perspective(
                 camera_from,
                 camera_at,
                 camera_up,
                 settings.camera_fov,
                 aspect_ratio,
-                0.1,
-                100.0,
+                near,
+                far,
             ),
             "orthographic" => {
-                let height = 4.0;
+                let height = 4.0 * scene_scale;
                 let width = height * aspect_ratio;
-                Camera::orthographic(camera_from, camera_at, camera_up, width, height, 0.1, 100.0)
+                Camera::orthographic(camera_from, camera_at, camera_up, width, height, near, far)
             }
             _ => return Err(format!("不支持的投影类型: {}", settings.projection)),
         };
+        camera.params.lens = settings.lens_params();
 
         // 光源
         let lights = settings.lights.clone();
@@ -77,13 +108,173 @@ impl Scene {
         let ambient_intensity = settings.ambient;
         let ambient_color = settings.get_ambient_color_vec();
 
-        Ok(Scene {
+        let mut scene = Scene {
             object,
+            terrain: None,
+            ground: None,
             lights,
             active_camera: camera,
             ambient_intensity,
             ambient_color,
-        })
+            lod_hysteresis: true,
+        };
+        scene.sync_terrain(settings)?.sync_ground(settings)?;
+        scene.pose_skeleton(settings, 0.0);
+        scene.set_morph_weights(settings);
+
+        Ok(scene)
+    }
+
+    /// 按设置创建、重建或移除地形，网格参数未变时只更新材质
+    pub fn sync_terrain(&mut self, settings: &RenderSettings) -> Result<&mut Self, String> {
+        if !settings.enable_terrain {
+            self.terrain = None;
+            return Ok(self);
+        }
+        match &mut self.terrain {
+            Some(terrain) if !terrain.needs_rebuild(settings) => {
+                terrain.update_parameters(settings);
+            }
+            _ => self.terrain = Some(Terrain::build(settings)?),
+        }
+        Ok(self)
+    }
+
+    /// 按设置创建、重建或移除几何地面
+    pub fn sync_ground(&mut self, settings: &RenderSettings) -> Result<&mut Self, String> {
+        if !settings.uses_ground_mesh() {
+            self.ground = None;
+            return Ok(self);
+        }
+        match &mut self.ground {
+            Some(ground) if !ground.needs_rebuild(settings) => {
+                ground.update_parameters(settings);
+            }
+            _ => self.ground = Some(GroundPlane::build(settings)?),
+        }
+        Ok(self)
+    }
+
+    /// 所有参与渲染的对象，主对象在最前
+    pub fn render_objects(&self) -> impl Iterator<Item = &SceneObject> {
+        std::iter::once(&self.object)
+            .chain(self.terrain.as_ref().map(|terrain| &terrain.object))
+            .chain(self.ground.as_ref().map(|ground| &ground.object))
+    }
+
+    /// 按包围球的屏幕尺寸为各对象选择细节层级，未启用 LOD 时回到原始网格
+    ///
+    /// 须在 [`Scene::swap_lod_meshes`] 换入网格之前调用。
+    /// 关闭 `lod_hysteresis` 时每次都不参考上一次的选择。
+    pub fn update_lods(&mut self, settings: &RenderSettings, viewport_height: usize) {
+        let camera = &self.active_camera;
+        let hysteresis = self.lod_hysteresis.then_some(settings.lod_hysteresis);
+        let objects = std::iter::once(&mut self.object)
+            .chain(self.terrain.as_mut().map(|terrain| &mut terrain.object))
+            .chain(self.ground.as_mut().map(|ground| &mut ground.object));
+        for object in objects {
+            if !settings.enable_lod {
+                object.lod_level = 0;
+                continue;
+            }
+            if object.update_lod(
+                camera,
+                viewport_height,
+                settings.lod_switch_size,
+                hysteresis,
+            ) {
+                debug!(
+                    "对象 '{}' 切换到 LOD{}（投影直径 {:.0} 像素）",
+                    object.model.name,
+                    object.lod_level,
+                    object
+                        .projected_diameter(camera, viewport_height)
+                        .unwrap_or(0.0)
+                );
+            }
+        }
+    }
+
+    /// 换入（或换回）各对象所选层级的网格，渲染前后成对调用
+    pub fn swap_lod_meshes(&mut self) {
+        self.object.swap_lod_meshes();
+    }
+
+    /// 换上新的模型数据（OBJ 序列的下一帧），按设置应用材质参数
+    pub fn replace_model(&mut self, model_data: &Model, settings: &RenderSettings) {
+        let mut model_data = model_data.clone();
+        apply_material_parameters(&mut model_data, settings);
+        self.object.set_model(model_data);
+    }
+
+    /// 把骨骼动画摆到动画开始后第 `animation_time` 秒的姿态
+    pub fn pose_skeleton(&mut self, settings: &RenderSettings, animation_time: f32) {
+        self.object.set_pose(
+            settings.skeleton_pose_time(animation_time),
+            settings.skeleton_loop,
+        );
+    }
+
+    /// 把设置中的变形目标权重同步到主对象
+    pub fn set_morph_weights(&mut self, settings: &RenderSettings) {
+        self.object.morph_weights = settings
+            .morph_targets
+            .iter()
+            .map(|target| (target.name.clone(), target.weight))
+            .collect();
+    }
+
+    /// 主对象带有可播放的骨骼动画，每帧的形状（及其阴影）都会变化
+    pub fn has_skeletal_animation(&self) -> bool {
+        self.object
+            .model
+            .skeleton
+            .as_ref()
+            .is_some_and(|skeleton| skeleton.clip_duration() > 0.0)
+    }
+
+    /// 把设置中可随时间变化的状态同步到场景：光源、相机、物体变换、材质、环境光、变形权重和静止时的骨骼姿态
+    ///
+    /// `original_model` 为加载时的原始模型，材质以它为起点重新应用，避免重复覆盖。
+    pub fn sync_animated_state(
+        &mut self,
+        settings: &RenderSettings,
+        original_model: Option<&Model>,
+    ) {
+        self.set_lights(settings.lights.clone());
+
+        let camera = &mut self.active_camera;
+        if let Ok(from) = parse_point3(&settings.camera_from) {
+            camera.params.position = from;
+        }
+        if let Ok(at) = parse_point3(&settings.camera_at) {
+            camera.params.target = at;
+        }
+        if let Ok(up) = parse_vec3(&settings.camera_up) {
+            camera.params.up = up.normalize();
+        }
+        if let ProjectionType::Perspective { fov_y_degrees, .. } = &mut camera.params.projection {
+            *fov_y_degrees = settings.camera_fov;
+        }
+        camera.params.lens = settings.lens_params();
+        camera.update_matrices();
+
+        let (position, rotation_rad, scale) = settings.get_object_transform_components();
+        let final_scale = if settings.object_scale != 1.0 {
+            scale * settings.object_scale
+        } else {
+            scale
+        };
+        self.set_object_transform(position, rotation_rad, final_scale);
+
+        if let Some(original_model) = original_model {
+            self.object.model.restore_materials_from(original_model);
+        }
+        apply_material_parameters(&mut self.object.model, settings);
+
+        self.set_ambient(settings.ambient, settings.get_ambient_color_vec());
+        self.set_morph_weights(settings);
+        self.pose_skeleton(settings, 0.0);
     }
 
     /// 链式设置对象变换
@@ -124,7 +315,10 @@ impl Scene {
         let material_count = self.object.model.materials.len();
         let mesh_count = self.object.model.meshes.len();
 
-        for mesh in &self.object.model.meshes {
+        for mesh in self
+            .render_objects()
+            .flat_map(|object| &object.model.meshes)
+        {
             vertex_count += mesh.vertices.len();
             triangle_count += mesh.indices.len() / 3;
         }
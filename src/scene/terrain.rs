@@ -0,0 +1,380 @@
+use crate::io::obj_loader::SharedTextures;
+use crate::io::render_settings::RenderSettings;
+use crate::material_system::materials::{Mesh, Vertex, environment_material};
+use crate::material_system::texture::Texture;
+use crate::scene::scene_object::SceneObject;
+use log::{debug, info};
+use nalgebra::{Point3, Vector2, Vector3};
+
+/// 启用 LOD 时每边的分块数
+const CHUNKS_PER_SIDE: usize = 8;
+/// 每个分块最多的细节层级数
+const MAX_LOD_LEVELS: usize = 4;
+/// LOD 切换的滞后比例，避免在层级边界附近来回跳变
+const LOD_HYSTERESIS: f32 = 0.1;
+
+/// 灰度高度图，值归一化到 [0, 1]
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+impl Heightmap {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let image = image::open(path)
+            .map_err(|e| format!("无法加载高度图 '{path}': {e}"))?
+            .into_luma16();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        if width < 2 || height < 2 {
+            return Err(format!("高度图尺寸过小: {width}x{height}"));
+        }
+        let values = image
+            .pixels()
+            .map(|pixel| pixel.0[0] as f32 / u16::MAX as f32)
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            values,
+        })
+    }
+
+    /// 双线性采样，u 沿图像宽度、v 沿图像高度（v=0 为首行）
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        let x = u.clamp(0.0, 1.0) * (self.width - 1) as f32;
+        let y = v.clamp(0.0, 1.0) * (self.height - 1) as f32;
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+        let at = |x: usize, y: usize| self.values[y * self.width + x];
+        let top = at(x0, y0) * (1.0 - tx) + at(x1, y0) * tx;
+        let bottom = at(x0, y1) * (1.0 - tx) + at(x1, y1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+}
+
+/// 决定地形网格的参数，变化时需要重新生成网格
+#[derive(Debug, Clone, PartialEq)]
+struct TerrainKey {
+    heightmap: Option<String>,
+    texture: Option<String>,
+    size: f32,
+    height_scale: f32,
+    height: f32,
+    resolution: usize,
+    texture_tiling: f32,
+    lod: bool,
+}
+
+impl TerrainKey {
+    fn from_settings(settings: &RenderSettings) -> Self {
+        Self {
+            heightmap: settings.terrain_heightmap.clone(),
+            texture: settings.terrain_texture.clone(),
+            size: settings.terrain_size,
+            height_scale: settings.terrain_height_scale,
+            height: settings.terrain_height,
+            resolution: settings.terrain_resolution,
+            texture_tiling: settings.terrain_texture_tiling,
+            lod: settings.terrain_lod,
+        }
+    }
+}
+
+/// 地形分块，每个层级是一份独立网格
+#[derive(Debug, Clone)]
+struct TerrainChunk {
+    levels: Vec<Mesh>,
+    center: Point3<f32>,
+}
+
+/// 由高度图生成的地形
+///
+/// 顶点直接位于世界空间（以原点为中心、位于 XZ 平面），不受物体变换和动画影响。
+/// 启用 LOD 时地形被切分为若干分块，按到相机的距离逐块选择网格密度，
+/// 分块边界处附加向下的裙边以遮住不同密度之间的裂缝。
+#[derive(Debug, Clone)]
+pub struct Terrain {
+    key: TerrainKey,
+    chunks: Vec<TerrainChunk>,
+    selected_levels: Vec<usize>,
+    lod_distance: f32,
+    texture: Option<Texture>,
+    shared_textures: SharedTextures,
+    /// 当前选中层级组成的可渲染对象
+    pub object: SceneObject,
+}
+
+impl Terrain {
+    pub fn build(settings: &RenderSettings) -> Result<Self, String> {
+        let key = TerrainKey::from_settings(settings);
+        let heightmap_path = key
+            .heightmap
+            .as_deref()
+            .ok_or("启用地形时必须指定高度图 (terrain.heightmap)")?;
+        let heightmap = Heightmap::load(heightmap_path)?;
+
+        let texture = match &key.texture {
+            Some(path) => {
+                Some(Texture::from_file(path).ok_or_else(|| format!("无法加载地形纹理: {path}"))?)
+            }
+            None => None,
+        };
+
+        let chunks_per_side = if key.lod { CHUNKS_PER_SIDE } else { 1 };
+        // 分块内的单元数须能被最粗层级的步长整除
+        let chunk_cells = (key.resolution.clamp(8, 1024) / chunks_per_side).max(1);
+        let level_count = if key.lod {
+            (chunk_cells.trailing_zeros() as usize + 1).min(MAX_LOD_LEVELS)
+        } else {
+            1
+        };
+        let resolution = chunk_cells * chunks_per_side;
+
+        let grid = HeightGrid::sample(&heightmap, resolution, &key);
+        let chunks: Vec<TerrainChunk> = (0..chunks_per_side * chunks_per_side)
+            .map(|chunk_index| {
+                let chunk_x = chunk_index % chunks_per_side;
+                let chunk_z = chunk_index / chunks_per_side;
+                let levels = (0..level_count)
+                    .map(|level| {
+                        grid.chunk_mesh(
+                            (chunk_x * chunk_cells, chunk_z * chunk_cells),
+                            chunk_cells,
+                            1 << level,
+                            key.lod,
+                        )
+                    })
+                    .collect();
+                let center_index = |cell: usize| cell * chunk_cells + chunk_cells / 2;
+                TerrainChunk {
+                    levels,
+                    center: grid.position(center_index(chunk_x), center_index(chunk_z)),
+                }
+            })
+            .collect();
+
+        info!(
+            "地形已生成: {}x{} 网格, {} 个分块, {} 个细节层级",
+            resolution,
+            resolution,
+            chunks.len(),
+            level_count
+        );
+
+        let mut terrain = Self {
+            selected_levels: vec![0; chunks.len()],
+            chunks,
+            key,
+            lod_distance: settings.terrain_lod_distance,
+            texture,
+            shared_textures: SharedTextures::load_lookups(settings),
+            object: SceneObject::empty("terrain"),
+        };
+        terrain.object.receive_shadows = true;
+        terrain.rebuild_object();
+        terrain.update_parameters(settings);
+        Ok(terrain)
+    }
+
+    /// 设置变化后是否需要重新生成网格
+    pub fn needs_rebuild(&self, settings: &RenderSettings) -> bool {
+        self.key != TerrainKey::from_settings(settings)
+    }
+
+    /// 同步无需重新生成网格的参数（材质、LOD 距离）
+    pub fn update_parameters(&mut self, settings: &RenderSettings) {
+        self.lod_distance = settings.terrain_lod_distance.max(0.1);
+        self.shared_textures.sync_lookups(settings);
+        self.object.model.materials = vec![environment_material(
+            "terrain",
+            settings.get_terrain_color_vec(),
+            self.texture.clone(),
+            &self.shared_textures,
+            settings,
+        )];
+    }
+
+    /// 按相机位置逐块选择细节层级，选择变化时才重建网格
+    ///
+    /// `hysteresis` 为 false 时不参考之前的选择，结果只取决于相机位置。
+    pub fn update_lod(&mut self, camera_position: &Point3<f32>, hysteresis: bool) {
+        if !self.key.lod {
+            return;
+        }
+        let mut changed = false;
+        for (chunk, selected) in self.chunks.iter().zip(self.selected_levels.iter_mut()) {
+            let distance = (chunk.center - camera_position).norm();
+            let level_for = |distance: f32| {
+                let ratio = distance / self.lod_distance;
+                if ratio < 1.0 {
+                    0
+                } else {
+                    (ratio.log2().floor() as usize + 1).min(chunk.levels.len() - 1)
+                }
+            };
+            let mut target = level_for(distance);
+            if hysteresis {
+                if target > *selected {
+                    target = level_for(distance / (1.0 + LOD_HYSTERESIS)).max(*selected);
+                } else if target < *selected {
+                    target = level_for(distance * (1.0 + LOD_HYSTERESIS)).min(*selected);
+                }
+            }
+            if target != *selected {
+                *selected = target;
+                changed = true;
+            }
+        }
+        if changed {
+            self.rebuild_object();
+            debug!("地形 LOD 更新: {:?}", self.selected_levels);
+        }
+    }
+
+    fn rebuild_object(&mut self) {
+        self.object.model.meshes = self
+            .chunks
+            .iter()
+            .zip(&self.selected_levels)
+            .map(|(chunk, &level)| chunk.levels[level].clone())
+            .collect();
+    }
+}
+
+/// 最高精度下的地形采样网格
+struct HeightGrid {
+    resolution: usize,
+    positions: Vec<Point3<f32>>,
+    normals: Vec<Vector3<f32>>,
+    texture_tiling: f32,
+    skirt_depth: f32,
+}
+
+impl HeightGrid {
+    fn sample(heightmap: &Heightmap, resolution: usize, key: &TerrainKey) -> Self {
+        let side = resolution + 1;
+        let cell_size = key.size / resolution as f32;
+        let height_at = |x: usize, z: usize| {
+            let u = x as f32 / resolution as f32;
+            let v = z as f32 / resolution as f32;
+            key.height + heightmap.sample(u, v) * key.height_scale
+        };
+
+        let heights: Vec<f32> = (0..side * side)
+            .map(|index| height_at(index % side, index / side))
+            .collect();
+        let positions = (0..side * side)
+            .map(|index| {
+                let (x, z) = (index % side, index / side);
+                Point3::new(
+                    (x as f32 / resolution as f32 - 0.5) * key.size,
+                    heights[index],
+                    (z as f32 / resolution as f32 - 0.5) * key.size,
+                )
+            })
+            .collect();
+        // 中心差分求法线，边缘退化为单侧差分
+        let normals = (0..side * side)
+            .map(|index| {
+                let (x, z) = (index % side, index / side);
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(resolution));
+                let (z0, z1) = (z.saturating_sub(1), (z + 1).min(resolution));
+                let dh_dx = (heights[z * side + x1] - heights[z * side + x0])
+                    / ((x1 - x0) as f32 * cell_size);
+                let dh_dz = (heights[z1 * side + x] - heights[z0 * side + x])
+                    / ((z1 - z0) as f32 * cell_size);
+                Vector3::new(-dh_dx, 1.0, -dh_dz).normalize()
+            })
+            .collect();
+
+        Self {
+            resolution,
+            positions,
+            normals,
+            texture_tiling: key.texture_tiling,
+            skirt_depth: key.height_scale.abs() * 0.1 + cell_size,
+        }
+    }
+
+    fn position(&self, x: usize, z: usize) -> Point3<f32> {
+        self.positions[z * (self.resolution + 1) + x]
+    }
+
+    fn vertex(&self, x: usize, z: usize) -> Vertex {
+        let index = z * (self.resolution + 1) + x;
+        let u = x as f32 / self.resolution as f32;
+        let v = z as f32 / self.resolution as f32;
+        Vertex {
+            position: self.positions[index],
+            normal: self.normals[index],
+            // 纹理 v 轴向上，高度图首行对应地形的 -Z 一侧
+            texcoord: Vector2::new(u, 1.0 - v) * self.texture_tiling,
+        }
+    }
+
+    /// 生成一个分块在给定步长下的网格
+    fn chunk_mesh(
+        &self,
+        (origin_x, origin_z): (usize, usize),
+        cells: usize,
+        step: usize,
+        with_skirt: bool,
+    ) -> Mesh {
+        let side = cells / step + 1;
+        let mut vertices = Vec::with_capacity(side * side);
+        for row in 0..side {
+            for column in 0..side {
+                vertices.push(self.vertex(origin_x + column * step, origin_z + row * step));
+            }
+        }
+
+        let mut indices = Vec::with_capacity((side - 1) * (side - 1) * 6);
+        for row in 0..side - 1 {
+            for column in 0..side - 1 {
+                let a = (row * side + column) as u32;
+                let b = a + 1;
+                let c = a + side as u32;
+                let d = c + 1;
+                // 从 +Y 俯视时为逆时针
+                indices.extend_from_slice(&[a, c, b, b, c, d]);
+            }
+        }
+
+        if with_skirt {
+            let border: Vec<u32> = (0..side)
+                .map(|i| i as u32)
+                .chain((1..side).map(|i| (i * side + side - 1) as u32))
+                .chain((0..side - 1).rev().map(|i| ((side - 1) * side + i) as u32))
+                .chain((1..side - 1).rev().map(|i| (i * side) as u32))
+                .chain(std::iter::once(0))
+                .collect();
+            for pair in border.windows(2) {
+                let (top_a, top_b) = (pair[0], pair[1]);
+                let base = vertices.len() as u32;
+                for &top in &[top_a, top_b] {
+                    let mut lowered = vertices[top as usize];
+                    lowered.position.y -= self.skirt_depth;
+                    vertices.push(lowered);
+                }
+                let (bottom_a, bottom_b) = (base, base + 1);
+                // 裙边两面都生成，保证背面剔除时任意方向都能遮住裂缝
+                indices.extend_from_slice(&[top_a, bottom_a, top_b, top_b, bottom_a, bottom_b]);
+                indices.extend_from_slice(&[top_a, top_b, bottom_a, top_b, bottom_b, bottom_a]);
+            }
+        }
+
+        Mesh {
+            vertices,
+            indices,
+            lines: Vec::new(),
+            points: Vec::new(),
+            material_id: 0,
+            name: "terrain".to_string(),
+            skin_weights: Vec::new(),
+            morph_targets: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,537 @@
+use crate::io::render_settings::{AnimationType, RenderSettings, parse_vec3};
+use crate::material_system::light::Light;
+use crate::material_system::materials::MaterialOverride;
+use crate::scene::camera_path::CameraPathSampler;
+use nalgebra::{UnitQuaternion, Vector3};
+
+/// 从一个关键帧到下一个关键帧的缓动曲线
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// 与 CSS `ease` 相同，起步稍快、收尾平缓
+    Ease,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// 三次贝塞尔缓动曲线的两个控制点 (x1, y1, x2, y2)，端点固定为 (0, 0) 和 (1, 1)
+    Bezier([f32; 4]),
+}
+
+impl Interpolation {
+    /// 按名称创建，`bezier` 需要提供控制点
+    pub fn parse(name: &str, control_points: Option<[f32; 4]>) -> Result<Self, String> {
+        let interpolation = match name {
+            "linear" => Self::Linear,
+            "ease" => Self::Ease,
+            "ease_in" => Self::EaseIn,
+            "ease_out" => Self::EaseOut,
+            "ease_in_out" => Self::EaseInOut,
+            "bezier" => {
+                let points = control_points.ok_or("bezier 插值需要 bezier = \"x1,y1,x2,y2\"")?;
+                if !(0.0..=1.0).contains(&points[0]) || !(0.0..=1.0).contains(&points[2]) {
+                    return Err("bezier 控制点的 x 坐标必须在 [0, 1] 范围内".to_string());
+                }
+                Self::Bezier(points)
+            }
+            _ => return Err(format!("未知的插值方式: {name}")),
+        };
+        Ok(interpolation)
+    }
+
+    /// 配置中使用的名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Ease => "ease",
+            Self::EaseIn => "ease_in",
+            Self::EaseOut => "ease_out",
+            Self::EaseInOut => "ease_in_out",
+            Self::Bezier(_) => "bezier",
+        }
+    }
+
+    /// 把线性进度 t ∈ [0, 1] 映射为缓动后的进度
+    pub fn ease(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::Ease => cubic_bezier_ease([0.25, 0.1, 0.25, 1.0], t),
+            Self::EaseIn => cubic_bezier_ease([0.42, 0.0, 1.0, 1.0], t),
+            Self::EaseOut => cubic_bezier_ease([0.0, 0.0, 0.58, 1.0], t),
+            Self::EaseInOut => cubic_bezier_ease([0.42, 0.0, 0.58, 1.0], t),
+            Self::Bezier(points) => cubic_bezier_ease(*points, t),
+        }
+    }
+}
+
+/// 求解三次贝塞尔缓动曲线：先找到 x(s) = t 的参数 s，再返回 y(s)
+fn cubic_bezier_ease([x1, y1, x2, y2]: [f32; 4], t: f32) -> f32 {
+    let curve = |a: f32, b: f32, s: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * s * a + 3.0 * inv * s * s * b + s * s * s
+    };
+    let slope = |a: f32, b: f32, s: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * a + 6.0 * inv * s * (b - a) + 3.0 * s * s * (1.0 - b)
+    };
+
+    // 牛顿迭代通常几步收敛，斜率接近 0 时退回二分法
+    let mut s = t;
+    for _ in 0..8 {
+        let error = curve(x1, x2, s) - t;
+        if error.abs() < 1e-6 {
+            return curve(y1, y2, s);
+        }
+        let derivative = slope(x1, x2, s);
+        if derivative.abs() < 1e-6 {
+            break;
+        }
+        s = (s - error / derivative).clamp(0.0, 1.0);
+    }
+
+    let (mut low, mut high) = (0.0_f32, 1.0_f32);
+    s = t;
+    for _ in 0..32 {
+        let x = curve(x1, x2, s);
+        if (x - t).abs() < 1e-6 {
+            break;
+        }
+        if x < t {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) * 0.5;
+    }
+    curve(y1, y2, s)
+}
+
+/// 可由关键帧设置的光源属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightProperty {
+    Intensity,
+    Color,
+    /// 仅方向光
+    Direction,
+    /// 仅点光源
+    Position,
+}
+
+impl LightProperty {
+    pub const ALL: [Self; 4] = [
+        Self::Intensity,
+        Self::Color,
+        Self::Direction,
+        Self::Position,
+    ];
+}
+
+/// 可由关键帧设置的材质属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaterialProperty {
+    BaseColor,
+    Emissive,
+    Alpha,
+    Metallic,
+    Roughness,
+    AmbientOcclusion,
+    Clearcoat,
+    ClearcoatRoughness,
+    Transmission,
+}
+
+impl MaterialProperty {
+    pub const ALL: [Self; 9] = [
+        Self::BaseColor,
+        Self::Emissive,
+        Self::Alpha,
+        Self::Metallic,
+        Self::Roughness,
+        Self::AmbientOcclusion,
+        Self::Clearcoat,
+        Self::ClearcoatRoughness,
+        Self::Transmission,
+    ];
+}
+
+/// 时间线上的一个动画通道
+#[derive(Debug, Clone, PartialEq)]
+pub enum Channel {
+    CameraFrom,
+    CameraAt,
+    CameraFov,
+    ObjectPosition,
+    /// 欧拉角（度），插值时转换为四元数做球面插值
+    ObjectRotation,
+    ObjectScale,
+    /// 按配置顺序编号的光源
+    Light(usize, LightProperty),
+    /// target 为 None 时作用于全局材质参数，否则作用于同名的 `[[material_override]]`
+    Material(Option<String>, MaterialProperty),
+    /// 同名 `[[morph_target]]` 的权重
+    Morph(String),
+}
+
+impl Channel {
+    /// `[[keyframe]]` 顶层可设置的通道
+    pub const SCENE_CHANNELS: [Self; 6] = [
+        Self::CameraFrom,
+        Self::CameraAt,
+        Self::CameraFov,
+        Self::ObjectPosition,
+        Self::ObjectRotation,
+        Self::ObjectScale,
+    ];
+
+    /// 配置中的键名（光源、材质和变形通道为其所在子表中的键名）
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::CameraFrom => "camera_from",
+            Self::CameraAt => "camera_at",
+            Self::CameraFov => "camera_fov",
+            Self::ObjectPosition => "object_position",
+            Self::ObjectRotation => "object_rotation",
+            Self::ObjectScale => "object_scale",
+            Self::Light(_, property) => match property {
+                LightProperty::Intensity => "intensity",
+                LightProperty::Color => "color",
+                LightProperty::Direction => "direction",
+                LightProperty::Position => "position",
+            },
+            Self::Material(_, property) => match property {
+                MaterialProperty::BaseColor => "base_color",
+                MaterialProperty::Emissive => "emissive",
+                MaterialProperty::Alpha => "alpha",
+                MaterialProperty::Metallic => "metallic",
+                MaterialProperty::Roughness => "roughness",
+                MaterialProperty::AmbientOcclusion => "ambient_occlusion",
+                MaterialProperty::Clearcoat => "clearcoat",
+                MaterialProperty::ClearcoatRoughness => "clearcoat_roughness",
+                MaterialProperty::Transmission => "transmission",
+            },
+            Self::Morph(_) => "weight",
+        }
+    }
+
+    /// 值为标量（否则为 "x,y,z" 向量）
+    pub fn is_scalar(&self) -> bool {
+        matches!(
+            self,
+            Self::CameraFov
+                | Self::Morph(_)
+                | Self::Light(_, LightProperty::Intensity)
+                | Self::Material(
+                    _,
+                    MaterialProperty::Alpha
+                        | MaterialProperty::Metallic
+                        | MaterialProperty::Roughness
+                        | MaterialProperty::AmbientOcclusion
+                        | MaterialProperty::Clearcoat
+                        | MaterialProperty::ClearcoatRoughness
+                        | MaterialProperty::Transmission
+                )
+        )
+    }
+}
+
+/// 关键帧中的一个值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyValue {
+    Scalar(f32),
+    Vector(Vector3<f32>),
+}
+
+impl KeyValue {
+    fn scalar(self) -> f32 {
+        match self {
+            Self::Scalar(value) => value,
+            Self::Vector(vector) => vector.x,
+        }
+    }
+
+    fn vector(self) -> Vector3<f32> {
+        match self {
+            Self::Scalar(value) => Vector3::repeat(value),
+            Self::Vector(vector) => vector,
+        }
+    }
+
+    /// 配置中的写法
+    pub fn to_toml(self) -> String {
+        match self {
+            Self::Scalar(value) => format!("{value}"),
+            Self::Vector(v) => format!("\"{},{},{}\"", v.x, v.y, v.z),
+        }
+    }
+}
+
+/// `[[keyframe]]`：某一时刻各通道的取值，未出现的通道由其他关键帧插值
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe {
+    /// 时刻（秒）
+    pub time: f32,
+    /// 从该关键帧到下一个关键帧使用的曲线
+    pub interpolation: Interpolation,
+    pub values: Vec<(Channel, KeyValue)>,
+}
+
+impl Keyframe {
+    /// 记录当前相机和物体变换
+    pub fn capture(time: f32, settings: &RenderSettings) -> Result<Self, String> {
+        let vector = |text: &str| parse_vec3(text).map(KeyValue::Vector);
+        Ok(Self {
+            time,
+            interpolation: Interpolation::default(),
+            values: vec![
+                (Channel::CameraFrom, vector(&settings.camera_from)?),
+                (Channel::CameraAt, vector(&settings.camera_at)?),
+                (Channel::CameraFov, KeyValue::Scalar(settings.camera_fov)),
+                (Channel::ObjectPosition, vector(&settings.object_position)?),
+                (Channel::ObjectRotation, vector(&settings.object_rotation)?),
+                (Channel::ObjectScale, vector(&settings.object_scale_xyz)?),
+            ],
+        })
+    }
+}
+
+struct TrackKey {
+    time: f32,
+    value: KeyValue,
+    interpolation: Interpolation,
+}
+
+/// 单个通道按时间排序的关键值
+struct Track {
+    channel: Channel,
+    keys: Vec<TrackKey>,
+}
+
+impl Track {
+    fn sample(&self, time: f32) -> KeyValue {
+        let next_index = self.keys.partition_point(|key| key.time <= time);
+        if next_index == 0 {
+            return self.keys[0].value;
+        }
+        if next_index == self.keys.len() {
+            return self.keys[next_index - 1].value;
+        }
+
+        let previous = &self.keys[next_index - 1];
+        let next = &self.keys[next_index];
+        let span = next.time - previous.time;
+        let t = if span > 1e-6 {
+            previous.interpolation.ease((time - previous.time) / span)
+        } else {
+            1.0
+        };
+
+        match (self.channel == Channel::ObjectRotation, previous.value) {
+            (true, _) => {
+                // 四元数球面插值走最短路径，超过 180° 的转动需要拆成多个关键帧
+                let from = euler_degrees_to_quaternion(&previous.value.vector());
+                let to = euler_degrees_to_quaternion(&next.value.vector());
+                let (roll, pitch, yaw) = from.slerp(&to, t).euler_angles();
+                KeyValue::Vector(Vector3::new(roll, pitch, yaw).map(f32::to_degrees))
+            }
+            (false, KeyValue::Scalar(from)) => {
+                KeyValue::Scalar(from + (next.value.scalar() - from) * t)
+            }
+            (false, KeyValue::Vector(from)) => {
+                KeyValue::Vector(from + (next.value.vector() - from) * t)
+            }
+        }
+    }
+}
+
+/// 与场景对象相同的旋转顺序 Rz * Ry * Rx
+fn euler_degrees_to_quaternion(degrees: &Vector3<f32>) -> UnitQuaternion<f32> {
+    let radians = degrees.map(f32::to_radians);
+    UnitQuaternion::from_euler_angles(radians.x, radians.y, radians.z)
+}
+
+/// 由 `[[keyframe]]` 构建的动画时间线，可附带覆盖相机通道的 `[camera_path]`
+pub struct Timeline {
+    tracks: Vec<Track>,
+    duration: f32,
+    camera_path: Option<CameraPathSampler>,
+}
+
+impl Timeline {
+    pub fn new(keyframes: &[Keyframe]) -> Self {
+        let mut sorted: Vec<&Keyframe> = keyframes.iter().collect();
+        sorted.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        let mut tracks: Vec<Track> = Vec::new();
+        for keyframe in &sorted {
+            for (channel, value) in &keyframe.values {
+                let key = TrackKey {
+                    time: keyframe.time,
+                    value: *value,
+                    interpolation: keyframe.interpolation,
+                };
+                match tracks.iter_mut().find(|track| track.channel == *channel) {
+                    Some(track) => track.keys.push(key),
+                    None => tracks.push(Track {
+                        channel: channel.clone(),
+                        keys: vec![key],
+                    }),
+                }
+            }
+        }
+
+        Self {
+            tracks,
+            duration: sorted.last().map_or(0.0, |keyframe| keyframe.time.max(0.0)),
+            camera_path: None,
+        }
+    }
+
+    /// 按设置构建：始终包含关键帧，动画类型为 CameraPath 时再加入相机路径
+    pub fn from_settings(settings: &RenderSettings) -> Result<Self, String> {
+        let mut timeline = Self::new(&settings.keyframes);
+        if settings.animation_type == AnimationType::CameraPath {
+            let camera_path = CameraPathSampler::new(&settings.camera_path)?;
+            timeline.duration = timeline.duration.max(camera_path.duration());
+            timeline.camera_path = Some(camera_path);
+        }
+        Ok(timeline)
+    }
+
+    /// 最后一个关键帧或相机路径结束的时刻（秒）
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// 按帧率播放完整时间线需要的帧数，首尾关键帧都会被渲染
+    ///
+    /// 时间线由闭合相机路径决定结束时刻时，终点与首帧位姿相同，不再重复渲染，循环播放无停顿。
+    pub fn frame_count(&self, fps: usize) -> usize {
+        let frames = (self.duration * fps as f32).round() as usize;
+        let loops = self.camera_path.as_ref().is_some_and(|camera_path| {
+            camera_path.is_closed() && camera_path.duration() >= self.duration
+        });
+        if loops { frames.max(1) } else { frames + 1 }
+    }
+
+    /// 把第 `time` 秒的取值写入设置
+    pub fn apply(&self, time: f32, settings: &mut RenderSettings) -> Result<(), String> {
+        for track in &self.tracks {
+            apply_value(&track.channel, track.sample(time), settings)?;
+        }
+        if let Some(camera_path) = &self.camera_path {
+            camera_path.apply(time, settings)?;
+        }
+        Ok(())
+    }
+}
+
+fn format_vector(vector: &Vector3<f32>) -> String {
+    format!("{},{},{}", vector.x, vector.y, vector.z)
+}
+
+fn apply_value(
+    channel: &Channel,
+    value: KeyValue,
+    settings: &mut RenderSettings,
+) -> Result<(), String> {
+    match channel {
+        Channel::CameraFrom => settings.camera_from = format_vector(&value.vector()),
+        Channel::CameraAt => settings.camera_at = format_vector(&value.vector()),
+        Channel::CameraFov => settings.camera_fov = value.scalar(),
+        Channel::ObjectPosition => settings.object_position = format_vector(&value.vector()),
+        Channel::ObjectRotation => settings.object_rotation = format_vector(&value.vector()),
+        Channel::ObjectScale => settings.object_scale_xyz = format_vector(&value.vector()),
+        Channel::Light(index, property) => {
+            let light = settings
+                .lights
+                .get_mut(*index)
+                .ok_or_else(|| format!("关键帧引用了不存在的光源 {index}"))?;
+            match (light, property) {
+                (
+                    Light::Directional { intensity, .. } | Light::Point { intensity, .. },
+                    LightProperty::Intensity,
+                ) => *intensity = value.scalar(),
+                (
+                    Light::Directional { color_str, .. } | Light::Point { color_str, .. },
+                    LightProperty::Color,
+                ) => *color_str = format_vector(&value.vector()),
+                (Light::Directional { direction_str, .. }, LightProperty::Direction) => {
+                    *direction_str = format_vector(&value.vector());
+                }
+                (Light::Point { position_str, .. }, LightProperty::Position) => {
+                    *position_str = format_vector(&value.vector());
+                }
+                _ => {
+                    return Err(format!(
+                        "光源 {index} 的类型不支持关键帧属性 {}",
+                        channel.key()
+                    ));
+                }
+            }
+            settings.lights[*index].update_runtime_fields()?;
+        }
+        Channel::Material(None, property) => {
+            let scalar = value.scalar();
+            match property {
+                // 非 PBR 着色模型的全局颜色取自 diffuse_color
+                MaterialProperty::BaseColor => {
+                    settings.base_color = format_vector(&value.vector());
+                    settings.diffuse_color = settings.base_color.clone();
+                }
+                MaterialProperty::Emissive => settings.emissive = format_vector(&value.vector()),
+                MaterialProperty::Alpha => settings.alpha = scalar,
+                MaterialProperty::Metallic => settings.metallic = scalar,
+                MaterialProperty::Roughness => settings.roughness = scalar,
+                MaterialProperty::AmbientOcclusion => settings.ambient_occlusion = scalar,
+                MaterialProperty::Clearcoat => settings.clearcoat = scalar,
+                MaterialProperty::ClearcoatRoughness => settings.clearcoat_roughness = scalar,
+                MaterialProperty::Transmission => settings.transmission = scalar,
+            }
+        }
+        Channel::Material(Some(target), property) => {
+            let index = match settings
+                .material_overrides
+                .iter()
+                .position(|o| o.target == *target)
+            {
+                Some(index) => index,
+                None => {
+                    settings.material_overrides.push(MaterialOverride {
+                        target: target.clone(),
+                        ..Default::default()
+                    });
+                    settings.material_overrides.len() - 1
+                }
+            };
+            let material_override = &mut settings.material_overrides[index];
+            let scalar = Some(value.scalar());
+            match property {
+                MaterialProperty::BaseColor => {
+                    material_override.base_color = Some(format_vector(&value.vector()));
+                }
+                MaterialProperty::Emissive => {
+                    material_override.emissive = Some(format_vector(&value.vector()));
+                }
+                MaterialProperty::Alpha => material_override.alpha = scalar,
+                MaterialProperty::Metallic => material_override.metallic = scalar,
+                MaterialProperty::Roughness => material_override.roughness = scalar,
+                MaterialProperty::AmbientOcclusion => material_override.ambient_occlusion = scalar,
+                MaterialProperty::Clearcoat => material_override.clearcoat = scalar,
+                MaterialProperty::ClearcoatRoughness => {
+                    material_override.clearcoat_roughness = scalar;
+                }
+                MaterialProperty::Transmission => material_override.transmission = scalar,
+            }
+        }
+        Channel::Morph(name) => {
+            settings
+                .morph_targets
+                .iter_mut()
+                .find(|target| target.name == *name)
+                .ok_or_else(|| format!("关键帧引用了不存在的变形目标 '{name}'"))?
+                .weight = value.scalar();
+        }
+    }
+    Ok(())
+}
@@ -1,14 +1,20 @@
 use crate::ModelLoader;
 use crate::core::renderer::Renderer;
-use crate::io::render_settings::{AnimationType, RenderSettings, get_animation_axis_vector};
+use crate::io::render_settings::{RenderSettings, get_animation_axis_vector};
+use crate::material_system::materials::Model;
 use crate::scene::scene_utils::Scene;
+use crate::scene::timeline::Timeline;
 use crate::utils::render_utils::{
-    animate_scene_step, calculate_rotation_delta, calculate_rotation_parameters,
+    animate_scene_step, animation_cycle, animation_total_frames, apply_timeline_frame,
+    calculate_rotation_delta, calculate_rotation_parameters, invalidate_animated_ground,
+    render_with_motion_blur,
 };
 use crate::utils::save_utils::save_image;
+use crate::utils::video_utils::encode_animation;
 use egui::{ColorImage, Context, TextureOptions};
-use log::debug;
+use log::{debug, warn};
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
@@ -29,11 +35,12 @@ pub fn frame_to_png_data(image: &ColorImage) -> Vec<u8> {
     rgb_data
 }
 
-/// 渲染一圈的动画帧
+/// 渲染一个动画周期（转一圈或整条时间线）的帧
 ///
 /// # 参数
 /// * `scene_copy` - 场景的克隆
 /// * `settings` - 渲染参数
+/// * `original_model` - 加载时的原始模型，时间线动画重新应用材质时使用
 /// * `progress_arc` - 进度计数器
 /// * `ctx_clone` - UI上下文，用于更新界面
 /// * `width` - 渲染宽度
@@ -42,9 +49,11 @@ pub fn frame_to_png_data(image: &ColorImage) -> Vec<u8> {
 ///
 /// # 返回值
 /// 渲染的总帧数
+#[allow(clippy::too_many_arguments)]
 fn render_one_rotation_cycle<F>(
     mut scene_copy: Scene,
     settings: &RenderSettings,
+    original_model: Option<&Model>,
     progress_arc: &Arc<AtomicUsize>,
     ctx_clone: &Context,
     width: usize,
@@ -55,7 +64,14 @@ where
     F: FnMut(usize, Vec<u8>),
 {
     let mut thread_renderer = Renderer::new(width, height);
-    let (effective_rotation_speed_dps, _, frames_to_render) =
+    // 逐帧渲染的结果不依赖上一帧的细节层级
+    scene_copy.lod_hysteresis = false;
+    let (_, frames_to_render) = animation_cycle(settings);
+    let timeline = Timeline::from_settings(settings).unwrap_or_else(|e| {
+        warn!("时间线构建失败: {e}");
+        Timeline::new(&settings.keyframes)
+    });
+    let (effective_rotation_speed_dps, _, _) =
         calculate_rotation_parameters(settings.rotation_speed, settings.fps);
 
     let rotation_axis_vec = get_animation_axis_vector(settings);
@@ -65,7 +81,8 @@ where
     for frame_num in 0..frames_to_render {
         progress_arc.store(frame_num, Ordering::SeqCst);
 
-        if frame_num > 0 {
+        let time = frame_num as f32 / settings.fps as f32;
+        if !settings.animation_type.is_timeline() && frame_num > 0 {
             animate_scene_step(
                 &mut scene_copy,
                 &settings.animation_type,
@@ -74,28 +91,51 @@ where
             );
         }
 
-        // === 缓存失效策略 ===
-        match settings.animation_type {
-            AnimationType::CameraOrbit => {
-                // 相机轨道动画：地面本体和阴影都依赖相机，必须全部失效
-                thread_renderer.frame_buffer.invalidate_ground_base_cache();
-                thread_renderer
-                    .frame_buffer
-                    .invalidate_ground_shadow_cache();
-            }
-            AnimationType::ObjectLocalRotation => {
-                if settings.enable_shadow_mapping {
-                    // 物体动画+阴影：只需失效阴影缓存，地面本体可复用
-                    thread_renderer
-                        .frame_buffer
-                        .invalidate_ground_shadow_cache();
+        // 运动模糊的子帧在当前帧的基础上继续推进，渲染后恢复到整帧状态
+        let camera = scene_copy.active_camera.clone();
+        let transform = scene_copy.object.transform;
+        let blurred =
+            render_with_motion_blur(&mut thread_renderer, settings, |renderer, offset| {
+                let sample_time = time + offset / settings.fps as f32;
+                let mut sample_settings = None;
+                if settings.animation_type.is_timeline() {
+                    match apply_timeline_frame(
+                        &mut scene_copy,
+                        &timeline,
+                        settings,
+                        sample_time,
+                        original_model,
+                    ) {
+                        Ok(sampled) => sample_settings = Some(sampled),
+                        Err(e) => warn!("时间线采样失败: {e}"),
+                    }
+                } else {
+                    scene_copy.set_camera(camera.clone());
+                    scene_copy.object.transform = transform;
+                    animate_scene_step(
+                        &mut scene_copy,
+                        &settings.animation_type,
+                        &rotation_axis_vec,
+                        rotation_increment_rad_per_frame * offset,
+                    );
+                    scene_copy.pose_skeleton(settings, sample_time);
                 }
-                // 未开阴影时，地面缓存可复用，无需清理
-            }
-            AnimationType::None => {}
+
+                invalidate_animated_ground(renderer, settings, &scene_copy);
+                renderer.render_scene(
+                    &mut scene_copy,
+                    sample_settings.as_ref().unwrap_or(settings),
+                );
+                Ok(())
+            });
+        if let Err(e) = blurred {
+            warn!("第 {frame_num} 帧渲染失败: {e}");
+        }
+        if !settings.animation_type.is_timeline() {
+            scene_copy.set_camera(camera);
+            scene_copy.object.transform = transform;
         }
 
-        thread_renderer.render_scene(&mut scene_copy, settings);
         let color_data_rgb = thread_renderer.frame_buffer.get_color_buffer_bytes();
         on_frame_rendered(frame_num, color_data_rgb);
 
@@ -138,15 +178,7 @@ impl AnimationMethods for RasterizerApp {
         {
             // 检查模型是否已加载
             if self.scene.is_none() {
-                let obj_path = match &self.settings.obj {
-                    Some(path) => path.clone(),
-                    None => {
-                        self.set_error("错误: 未指定OBJ文件路径".to_string());
-                        self.stop_animation_rendering();
-                        return;
-                    }
-                };
-                match ModelLoader::load_and_create_scene(&obj_path, &self.settings) {
+                match ModelLoader::load_and_create_scene(&mut self.settings) {
                     Ok((scene, model_data)) => {
                         self.scene = Some(scene);
                         self.model_data = Some(model_data);
@@ -181,15 +213,7 @@ impl AnimationMethods for RasterizerApp {
 
         // 确保场景已加载
         if self.scene.is_none() {
-            let obj_path = match &self.settings.obj {
-                Some(path) => path.clone(),
-                None => {
-                    self.set_error("错误: 未指定OBJ文件路径".to_string());
-                    self.stop_animation_rendering();
-                    return;
-                }
-            };
-            match ModelLoader::load_and_create_scene(&obj_path, &self.settings) {
+            match ModelLoader::load_and_create_scene(&mut self.settings) {
                 Ok((scene, model_data)) => {
                     self.scene = Some(scene);
                     self.model_data = Some(model_data);
@@ -243,6 +267,36 @@ impl AnimationMethods for RasterizerApp {
             let rotation_delta_rad = calculate_rotation_delta(self.settings.rotation_speed, dt);
             let rotation_axis_vec = get_animation_axis_vector(&self.settings);
 
+            // 时间线动画按累计时间循环采样，不修改界面上的设置
+            let mut frame_settings = None;
+            if self.settings.animation_type.is_timeline() {
+                // 时间线只在开始播放或设置变化后构建一次，见 render_if_anything_changed
+                let timeline = match self.playback_timeline.take() {
+                    Some(timeline) => Ok(timeline),
+                    None => Timeline::from_settings(&self.settings),
+                };
+                let sampled = timeline.and_then(|timeline| {
+                    let time = self.animation_time % timeline.duration().max(1e-3);
+                    let sampled = apply_timeline_frame(
+                        scene,
+                        &timeline,
+                        &self.settings,
+                        time,
+                        self.model_data.as_ref(),
+                    );
+                    self.playback_timeline = Some(timeline);
+                    sampled
+                });
+                match sampled {
+                    Ok(sampled) => frame_settings = Some(sampled),
+                    Err(e) => {
+                        self.status_message = format!("时间线采样失败: {e}");
+                    }
+                }
+            } else {
+                scene.pose_skeleton(&self.settings, self.animation_time);
+            }
+
             // 使用通用函数执行动画步骤
             animate_scene_step(
                 scene,
@@ -252,40 +306,30 @@ impl AnimationMethods for RasterizerApp {
             );
 
             debug!(
-                "实时渲染中: FPS={:.1}, 动画类型={:?}, 轴={:?}, 旋转速度={}, 角度增量={:.3}rad, Phong={}",
+                "实时渲染中: FPS={:.1}, 动画类型={:?}, 轴={:?}, 旋转速度={}, 角度增量={:.3}rad, 着色模型={:?}",
                 self.avg_fps,
                 self.settings.animation_type,
                 self.settings.rotation_axis,
                 self.settings.rotation_speed,
                 rotation_delta_rad,
-                self.settings.use_phong
+                self.settings.shading_model
             );
 
-            match self.settings.animation_type {
-                AnimationType::CameraOrbit => {
-                    // 相机轨道动画：地面本体和阴影都依赖相机，必须全部失效
-                    self.renderer.frame_buffer.invalidate_ground_base_cache();
-                    self.renderer.frame_buffer.invalidate_ground_shadow_cache();
-                }
-                AnimationType::ObjectLocalRotation => {
-                    if self.settings.enable_shadow_mapping {
-                        // 物体动画+阴影：只需失效阴影缓存，地面本体可复用
-                        self.renderer.frame_buffer.invalidate_ground_shadow_cache();
-                    }
-                    // 未开阴影时，地面缓存可复用，无需清理
-                }
-                AnimationType::None => {}
-            }
+            invalidate_animated_ground(&mut self.renderer, &self.settings, scene);
 
-            self.renderer.render_scene(scene, &self.settings);
+            self.renderer
+                .render_scene(scene, frame_settings.as_ref().unwrap_or(&self.settings));
             self.display_render_result(ctx);
             ctx.request_repaint();
         }
     }
 
     fn start_video_generation(&mut self, ctx: &Context) {
-        if !self.ffmpeg_available {
-            self.set_error("无法生成视频：未检测到ffmpeg。请安装ffmpeg后重试。".to_string());
+        if self.settings.video_format.needs_ffmpeg() && !self.ffmpeg_available {
+            self.set_error(
+                "无法生成 MP4：未检测到ffmpeg。请安装ffmpeg，或改用 GIF、APNG、WebP 格式。"
+                    .to_string(),
+            );
             return;
         }
         if self.is_generating_video {
@@ -311,23 +355,13 @@ impl AnimationMethods for RasterizerApp {
                     return;
                 }
 
-                // 计算旋转参数，获取视频帧数
-                let (_, _, frames_per_rotation) =
-                    calculate_rotation_parameters(self.settings.rotation_speed, self.settings.fps);
-
-                let total_frames =
-                    (frames_per_rotation as f32 * self.settings.rotation_cycles) as usize;
+                // 视频帧数：旋转动画按圈数，时间线动画为整条时间线
+                let (_, frames_per_rotation) = animation_cycle(&self.settings);
+                let total_frames = animation_total_frames(&self.settings);
 
                 // 如果场景未加载，尝试加载
                 if self.scene.is_none() {
-                    let obj_path = match &self.settings.obj {
-                        Some(path) => path.clone(),
-                        None => {
-                            self.set_error("错误: 未指定OBJ文件路径".to_string());
-                            return;
-                        }
-                    };
-                    match ModelLoader::load_and_create_scene(&obj_path, &self.settings) {
+                    match ModelLoader::load_and_create_scene(&mut self.settings) {
                         Ok((scene, model_data)) => {
                             self.scene = Some(scene);
                             self.model_data = Some(model_data);
@@ -344,19 +378,22 @@ impl AnimationMethods for RasterizerApp {
                 let video_progress_arc = self.video_progress.clone();
                 let fps = self.settings.fps;
                 let scene_clone = self.scene.as_ref().expect("场景已检查").clone();
+                let original_model = self.model_data.clone();
 
-                // 检查是否有预渲染帧
-                let has_pre_rendered_frames = {
+                // 检查是否有预渲染帧；运动模糊需要重新渲染子帧，不能复用预渲染帧
+                let has_pre_rendered_frames = !self.settings.motion_blur && {
                     let frames_guard = self.pre_rendered_frames.lock().unwrap();
                     !frames_guard.is_empty()
                 };
 
                 // 如果没有预渲染帧，那么我们需要同时为预渲染缓冲区生成帧
-                let frames_for_pre_render = if !has_pre_rendered_frames {
-                    Some(self.pre_rendered_frames.clone())
-                } else {
-                    None
-                };
+                // 模糊后的帧只用于视频，不放入预渲染缓冲区
+                let frames_for_pre_render =
+                    if !has_pre_rendered_frames && !self.settings.motion_blur {
+                        Some(self.pre_rendered_frames.clone())
+                    } else {
+                        None
+                    };
 
                 // 设置渲染状态
                 self.is_generating_video = true;
@@ -371,7 +408,11 @@ impl AnimationMethods for RasterizerApp {
 
                 ctx.request_repaint();
                 let ctx_clone = ctx.clone();
-                let video_filename = format!("{}.mp4", settings_for_thread.output);
+                let video_filename = format!(
+                    "{}.{}",
+                    settings_for_thread.output,
+                    settings_for_thread.video_format.extension()
+                );
                 let video_output_path = format!("{output_dir}/{video_filename}");
                 let frames_dir_clone = frames_dir.clone();
 
@@ -422,6 +463,7 @@ impl AnimationMethods for RasterizerApp {
                         let rendered_frame_count = render_one_rotation_cycle(
                             scene_clone,
                             &settings_for_thread,
+                            original_model.as_ref(),
                             &video_progress_arc,
                             &ctx_clone,
                             width,
@@ -481,26 +523,43 @@ impl AnimationMethods for RasterizerApp {
                     video_progress_arc.store(total_frames, Ordering::SeqCst);
                     ctx_clone.request_repaint();
 
-                    // 使用ffmpeg将帧序列合成为视频，并解决阻塞问题
-                    let frames_pattern = format!("{frames_dir_clone}/frame_%04d.png");
-                    let ffmpeg_status = std::process::Command::new("ffmpeg")
-                        .args([
-                            "-y",
-                            "-framerate",
-                            &fps.to_string(),
-                            "-i",
-                            &frames_pattern,
-                            "-c:v",
-                            "libx264",
-                            "-pix_fmt",
-                            "yuv420p",
-                            "-crf",
-                            "23",
-                            &video_output_path,
-                        ])
-                        .status();
-
-                    let success = ffmpeg_status.is_ok_and(|s| s.success());
+                    let success = if settings_for_thread.video_format.needs_ffmpeg() {
+                        // 使用ffmpeg将帧序列合成为视频，并解决阻塞问题
+                        let frames_pattern = format!("{frames_dir_clone}/frame_%04d.png");
+                        let ffmpeg_status = std::process::Command::new("ffmpeg")
+                            .args([
+                                "-y",
+                                "-framerate",
+                                &fps.to_string(),
+                                "-i",
+                                &frames_pattern,
+                                "-c:v",
+                                "libx264",
+                                "-pix_fmt",
+                                "yuv420p",
+                                "-crf",
+                                "23",
+                                &video_output_path,
+                            ])
+                            .status();
+
+                        ffmpeg_status.is_ok_and(|s| s.success())
+                    } else {
+                        // GIF、APNG、WebP 在进程内编码
+                        let frame_paths: Vec<PathBuf> = (0..total_frames)
+                            .map(|frame_num| {
+                                Path::new(&frames_dir_clone)
+                                    .join(format!("frame_{frame_num:04}.png"))
+                            })
+                            .collect();
+                        encode_animation(
+                            &frame_paths,
+                            &settings_for_thread,
+                            Path::new(&video_output_path),
+                        )
+                        .inspect_err(|e| warn!("动画编码失败: {e}"))
+                        .is_ok()
+                    };
 
                     // 视频生成后清理临时文件
                     let _ = fs::remove_dir_all(&frames_dir_clone);
@@ -523,15 +582,7 @@ impl AnimationMethods for RasterizerApp {
         match self.settings.validate() {
             Ok(_) => {
                 if self.scene.is_none() {
-                    let obj_path = match &self.settings.obj {
-                        Some(path) => path.clone(),
-                        None => {
-                            self.set_error("错误: 未指定OBJ文件路径".to_string());
-                            self.stop_animation_rendering();
-                            return;
-                        }
-                    };
-                    match ModelLoader::load_and_create_scene(&obj_path, &self.settings) {
+                    match ModelLoader::load_and_create_scene(&mut self.settings) {
                         Ok((scene, model_data)) => {
                             self.scene = Some(scene);
                             self.model_data = Some(model_data);
@@ -544,9 +595,7 @@ impl AnimationMethods for RasterizerApp {
                     }
                 }
 
-                // 使用通用函数计算旋转参数
-                let (_, seconds_per_rotation, frames_to_render) =
-                    calculate_rotation_parameters(self.settings.rotation_speed, self.settings.fps);
+                let (seconds_per_rotation, frames_to_render) = animation_cycle(&self.settings);
 
                 self.total_frames_for_pre_render_cycle = frames_to_render;
 
@@ -561,9 +610,10 @@ impl AnimationMethods for RasterizerApp {
                 let width = settings_for_thread.width;
                 let height = settings_for_thread.height;
                 let scene_clone = self.scene.as_ref().expect("场景已检查存在").clone();
+                let original_model = self.model_data.clone();
 
                 self.status_message = format!(
-                    "开始预渲染动画 (0/{frames_to_render} 帧，转一圈需 {seconds_per_rotation:.1} 秒)..."
+                    "开始预渲染动画 (0/{frames_to_render} 帧，一个周期 {seconds_per_rotation:.1} 秒)..."
                 );
                 ctx.request_repaint();
                 let ctx_clone = ctx.clone();
@@ -573,6 +623,7 @@ impl AnimationMethods for RasterizerApp {
                     render_one_rotation_cycle(
                         scene_clone,
                         &settings_for_thread,
+                        original_model.as_ref(),
                         &progress_arc,
                         &ctx_clone,
                         width,
@@ -602,12 +653,10 @@ impl AnimationMethods for RasterizerApp {
         let progress = self.pre_render_progress.load(Ordering::SeqCst);
         let expected_total_frames = self.total_frames_for_pre_render_cycle;
 
-        // 使用通用函数计算参数
-        let (_, seconds_per_rotation, _) =
-            calculate_rotation_parameters(self.settings.rotation_speed, self.settings.fps);
+        let (seconds_per_rotation, _) = animation_cycle(&self.settings);
 
         self.status_message = format!(
-            "预渲染动画中... ({}/{} 帧，{:.1}%，转一圈约需 {:.1} 秒)",
+            "预渲染动画中... ({}/{} 帧，{:.1}%，一个周期约 {:.1} 秒)",
             progress,
             expected_total_frames,
             if expected_total_frames > 0 {
@@ -622,7 +671,7 @@ impl AnimationMethods for RasterizerApp {
             self.is_pre_rendering = false;
             let final_frame_count = self.pre_rendered_frames.lock().unwrap().len();
             self.status_message = format!(
-                "预渲染完成！已缓存 {} 帧动画 (目标 {} FPS, 转一圈 {:.1} 秒)",
+                "预渲染完成！已缓存 {} 帧动画 (目标 {} FPS, 一个周期 {:.1} 秒)",
                 final_frame_count, self.settings.fps, seconds_per_rotation
             );
             if self.is_realtime_rendering || self.pre_render_mode {
@@ -673,12 +722,10 @@ impl AnimationMethods for RasterizerApp {
             Some(ctx.load_texture(texture_name, frame_image, TextureOptions::LINEAR));
         self.current_frame_index = (self.current_frame_index + 1) % frames_len;
 
-        // 使用通用函数计算参数
-        let (_, seconds_per_rotation, _) =
-            calculate_rotation_parameters(self.settings.rotation_speed, self.settings.fps);
+        let (seconds_per_rotation, _) = animation_cycle(&self.settings);
 
         self.status_message = format!(
-            "播放预渲染: 帧 {}/{} (目标 {} FPS, 平均 {:.1} FPS, 1圈 {:.1}秒)",
+            "播放预渲染: 帧 {}/{} (目标 {} FPS, 平均 {:.1} FPS, 周期 {:.1}秒)",
             frame_to_display_idx + 1,
             frames_len,
             self.settings.fps,
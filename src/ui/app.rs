@@ -1,11 +1,13 @@
 use super::animation::AnimationMethods;
 use super::core::CoreMethods;
 use super::widgets::WidgetMethods;
+use crate::core::depth_of_field::pixel_depth;
 use crate::core::renderer::Renderer;
 use crate::io::render_settings::RenderSettings;
 use crate::material_system::materials::Model;
 use crate::scene::scene_utils::Scene;
-use crate::utils::render_utils::calculate_rotation_parameters;
+use crate::scene::timeline::Timeline;
+use crate::utils::render_utils::animation_total_frames;
 use egui::{Color32, ColorImage, RichText, Vec2};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
@@ -34,6 +36,7 @@ pub struct RasterizerApp {
     pub avg_fps: f32,
     pub is_realtime_rendering: bool,
     pub last_frame_time: Option<std::time::Instant>,
+    pub playback_timeline: Option<Timeline>, // 实时播放采样的时间线，开始播放或设置变化时重建
 
     // 预渲染状态
     pub pre_render_mode: bool,
@@ -57,6 +60,12 @@ pub struct RasterizerApp {
     // 相机交互状态
     pub interface_interaction: InterfaceInteraction,
 
+    // 渐进累积状态
+    pub progressive_accumulation: bool,
+    pub accumulation_target: usize,
+    pub accumulated_samples: usize,
+    pub accumulation_buffer: Vec<f32>,
+
     // 系统状态
     pub ffmpeg_available: bool,
 }
@@ -125,6 +134,7 @@ impl RasterizerApp {
             avg_fps: 0.0,
             is_realtime_rendering: false,
             last_frame_time: None,
+            playback_timeline: None,
 
             // ===== 预渲染状态 =====
             pre_render_mode: false,
@@ -148,6 +158,12 @@ impl RasterizerApp {
             // ===== 相机交互状态 =====
             interface_interaction: InterfaceInteraction::default(),
 
+            // ===== 渐进累积状态 =====
+            progressive_accumulation: true,
+            accumulation_target: 64,
+            accumulated_samples: 0,
+            accumulation_buffer: Vec::new(),
+
             // ===== 系统状态 =====
             ffmpeg_available,
         }
@@ -250,6 +266,27 @@ impl RasterizerApp {
                 }
             });
 
+            // 景深开启时单击视图，按深度缓冲对焦到该点
+            if self.settings.enable_depth_of_field
+                && image_response.clicked()
+                && let Some(pos) = image_response.interact_pointer_pos()
+            {
+                let frame_buffer = &self.renderer.frame_buffer;
+                let uv = (pos - image_response.rect.min) / image_response.rect.size();
+                let x = ((uv.x * frame_buffer.width as f32) as usize).min(frame_buffer.width - 1);
+                let y = ((uv.y * frame_buffer.height as f32) as usize).min(frame_buffer.height - 1);
+                let depth = pixel_depth(frame_buffer, &scene.active_camera, &self.settings, x, y);
+                if depth.is_finite() {
+                    self.settings.camera_focus_distance = Some(depth);
+                    scene.active_camera.params.lens = self.settings.lens_params();
+                    self.status_message = format!("对焦距离: {depth:.3}");
+                    self.interface_interaction.anything_changed = true;
+                    ctx.request_repaint();
+                } else {
+                    self.status_message = "单击处没有几何体，无法对焦".to_string();
+                }
+            }
+
             // 如果相机发生变化，直接更新settings并标记
             if camera_changed {
                 // 如果相机变化，清除地面缓存（但保留背景缓存）
@@ -301,42 +338,37 @@ impl eframe::App for RasterizerApp {
         }
 
         // 检查视频生成进度
-        if self.is_generating_video {
-            if let Some(handle) = &self.video_generation_thread {
-                if handle.is_finished() {
-                    let result = self
-                        .video_generation_thread
-                        .take()
-                        .unwrap()
-                        .join()
-                        .unwrap_or_else(|_| (false, "线程崩溃".to_string()));
-
-                    self.is_generating_video = false;
-
-                    if result.0 {
-                        self.status_message = format!("视频生成成功: {}", result.1);
-                    } else {
-                        self.set_error(format!("视频生成失败: {}", result.1));
-                    }
-
-                    self.video_progress.store(0, Ordering::SeqCst);
+        if self.is_generating_video
+            && let Some(handle) = &self.video_generation_thread
+        {
+            if handle.is_finished() {
+                let result = self
+                    .video_generation_thread
+                    .take()
+                    .unwrap()
+                    .join()
+                    .unwrap_or_else(|_| (false, "线程崩溃".to_string()));
+
+                self.is_generating_video = false;
+
+                if result.0 {
+                    self.status_message = format!("视频生成成功: {}", result.1);
                 } else {
-                    let progress = self.video_progress.load(Ordering::SeqCst);
+                    self.set_error(format!("视频生成失败: {}", result.1));
+                }
 
-                    let (_, _, frames_per_rotation) = calculate_rotation_parameters(
-                        self.settings.rotation_speed,
-                        self.settings.fps,
-                    );
-                    let total_frames =
-                        (frames_per_rotation as f32 * self.settings.rotation_cycles) as usize;
+                self.video_progress.store(0, Ordering::SeqCst);
+            } else {
+                let progress = self.video_progress.load(Ordering::SeqCst);
 
-                    let percent = (progress as f32 / total_frames as f32 * 100.0).round();
+                let total_frames = animation_total_frames(&self.settings);
 
-                    self.status_message =
-                        format!("生成视频中... ({progress}/{total_frames}，{percent:.0}%)");
+                let percent = (progress as f32 / total_frames as f32 * 100.0).round();
 
-                    ctx.request_repaint_after(std::time::Duration::from_millis(500));
-                }
+                self.status_message =
+                    format!("生成视频中... ({progress}/{total_frames}，{percent:.0}%)");
+
+                ctx.request_repaint_after(std::time::Duration::from_millis(500));
             }
         }
 
@@ -410,6 +442,9 @@ impl eframe::App for RasterizerApp {
                             ui.small("• 滚轮 - 推拉缩放");
                             ui.small("• R键 - 重置视角");
                             ui.small("• F键 - 聚焦物体");
+                            if self.settings.enable_depth_of_field {
+                                ui.small("• 单击 - 景深对焦");
+                            }
                             ui.separator();
                             ui.small(format!("平移敏感度: {:.1}x", self.camera_pan_sensitivity));
                             ui.small(format!("旋转敏感度: {:.1}x", self.camera_orbit_sensitivity));
@@ -436,6 +471,9 @@ impl eframe::App for RasterizerApp {
         // 统一处理所有变化引起的重新渲染
         CoreMethods::render_if_anything_changed(self, ctx);
 
+        // 视图静止时逐帧累积抖动采样
+        CoreMethods::accumulate_idle_frame(self, ctx);
+
         // 在每帧更新结束时清理不需要的资源
         self.cleanup_resources();
     }
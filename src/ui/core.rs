@@ -1,10 +1,8 @@
 use crate::ModelLoader;
 use crate::core::renderer::Renderer;
-use crate::geometry::camera::ProjectionType;
-use crate::io::render_settings::{RenderSettings, parse_point3, parse_vec3};
-use crate::material_system::materials::apply_material_parameters;
+use crate::io::render_settings::RenderSettings;
 use crate::ui::app::RasterizerApp;
-use crate::utils::render_utils::calculate_rotation_parameters;
+use crate::utils::render_utils::animation_total_frames;
 use crate::utils::save_utils::save_render_with_settings;
 use egui::{Color32, Context};
 use log::{debug, error, warn};
@@ -35,6 +33,9 @@ pub trait CoreMethods {
     /// 如果任何事情发生变化，执行重新渲染
     fn render_if_anything_changed(&mut self, ctx: &Context);
 
+    /// 视图静止时渲染一帧子像素抖动采样并累积平均，逐帧收敛到高质量图像
+    fn accumulate_idle_frame(&mut self, ctx: &Context);
+
     /// 保存当前渲染结果为截图
     fn take_screenshot(&mut self) -> Result<String, String>;
 
@@ -102,20 +103,20 @@ impl CoreMethods for RasterizerApp {
             return;
         }
 
-        // 获取OBJ路径
-        let obj_path = match &self.settings.obj {
-            Some(path) => path.clone(),
+        // 获取模型来源
+        let model_source = match self.settings.model_source() {
+            Some(source) => source,
             None => {
-                self.set_error("错误: 未指定OBJ文件路径".to_string());
+                self.set_error("错误: 未指定OBJ文件路径或 [[object]] 图元".to_string());
                 return;
             }
         };
 
-        self.status_message = format!("正在加载 {obj_path}...");
+        self.status_message = format!("正在加载 {model_source}...");
         ctx.request_repaint(); // 立即更新状态消息
 
         // 加载模型
-        match ModelLoader::load_and_create_scene(&obj_path, &self.settings) {
+        match ModelLoader::load_and_create_scene(&mut self.settings) {
             Ok((scene, model_data)) => {
                 debug!(
                     "场景创建完成: 光源数量={}, 使用光照={}, 环境光强度={}",
@@ -152,6 +153,7 @@ impl CoreMethods for RasterizerApp {
         if let Some(scene) = &mut self.scene {
             // 渲染到帧缓冲区
             self.renderer.render_scene(scene, &self.settings);
+            self.accumulated_samples = 0;
 
             // 保存输出文件
             if let Err(e) = save_render_with_settings(&self.renderer, &self.settings, None) {
@@ -212,6 +214,9 @@ impl CoreMethods for RasterizerApp {
     /// 统一同步入口
     fn render_if_anything_changed(&mut self, ctx: &Context) {
         if self.interface_interaction.anything_changed && self.scene.is_some() {
+            // 关键帧或相机路径可能已被修改，播放时重新构建时间线
+            self.playback_timeline = None;
+
             if let Some(scene) = &mut self.scene {
                 // 检测渲染尺寸变化
                 if self.renderer.frame_buffer.width != self.settings.width
@@ -224,53 +229,100 @@ impl CoreMethods for RasterizerApp {
                 self.renderer.frame_buffer.invalidate_ground_base_cache();
                 self.renderer.frame_buffer.invalidate_ground_shadow_cache();
 
-                // 统一同步所有状态
+                // 1-5. 光源、相机、物体变换、材质（以原始MTL材质为起点）与环境光同步
+                scene.sync_animated_state(&self.settings, self.model_data.as_ref());
 
-                // 1. 光源同步
-                scene.set_lights(self.settings.lights.clone());
-
-                // 2. 相机同步
-                if let Ok(from) = parse_point3(&self.settings.camera_from) {
-                    scene.active_camera.params.position = from;
-                }
-                if let Ok(at) = parse_point3(&self.settings.camera_at) {
-                    scene.active_camera.params.target = at;
+                // 6. 地形与几何地面同步（网格参数变化时重新生成）
+                if let Err(e) = scene.sync_terrain(&self.settings) {
+                    warn!("{e}");
+                    self.status_message = format!("地形生成失败: {e}");
                 }
-                if let Ok(up) = parse_vec3(&self.settings.camera_up) {
-                    scene.active_camera.params.up = up.normalize();
+                if let Err(e) = scene.sync_ground(&self.settings) {
+                    warn!("{e}");
+                    self.status_message = format!("地面生成失败: {e}");
                 }
-                if let ProjectionType::Perspective { fov_y_degrees, .. } =
-                    &mut scene.active_camera.params.projection
-                {
-                    *fov_y_degrees = self.settings.camera_fov;
-                }
-                scene.active_camera.update_matrices();
-
-                // 3. 物体变换同步
-                let (position, rotation_rad, scale) =
-                    self.settings.get_object_transform_components();
-                let final_scale = if self.settings.object_scale != 1.0 {
-                    scale * self.settings.object_scale
-                } else {
-                    scale
-                };
-                scene.set_object_transform(position, rotation_rad, final_scale);
-
-                // 4. 材质参数同步
-                apply_material_parameters(&mut scene.object.model, &self.settings);
 
-                // 5. 环境光同步
-                scene.set_ambient(self.settings.ambient, self.settings.get_ambient_color_vec());
-
-                // 6. 执行渲染
+                // 7. 执行渲染
                 self.renderer.render_scene(scene, &self.settings);
             }
 
             self.display_render_result(ctx);
             self.interface_interaction.anything_changed = false;
+            self.accumulated_samples = 0;
         }
     }
 
+    fn accumulate_idle_frame(&mut self, ctx: &Context) {
+        if !self.progressive_accumulation
+            || self.is_realtime_rendering
+            || self.is_pre_rendering
+            || self.is_generating_video
+            || self.interface_interaction.anything_changed
+            || self.rendered_image.is_none()
+            || self.accumulated_samples >= self.accumulation_target
+        {
+            return;
+        }
+        let Some(scene) = &mut self.scene else {
+            return;
+        };
+
+        // 帧缓冲区中是刚渲染好的未抖动画面，直接作为第 0 个样本
+        let use_gamma = self.settings.use_gamma;
+        if self.accumulated_samples == 0 {
+            self.accumulation_buffer = self
+                .renderer
+                .frame_buffer
+                .get_color_buffer_linear(use_gamma);
+            self.accumulated_samples = 1;
+        }
+
+        // 之后的样本按 Halton(2, 3) 序列在像素内偏移，PCF 核和景深圆盘按 Halton(5) 旋转
+        let sample = self.accumulated_samples;
+        let (jitter_x, jitter_y) = (halton(sample, 2) - 0.5, halton(sample, 3) - 0.5);
+        let (width, height) = (self.settings.width, self.settings.height);
+        scene
+            .active_camera
+            .set_subpixel_jitter(jitter_x, jitter_y, width, height);
+        scene
+            .active_camera
+            .set_sample_rotation(halton(sample, 5) * std::f32::consts::TAU);
+        self.renderer.frame_buffer.invalidate_ground_base_cache();
+        self.renderer.frame_buffer.invalidate_ground_shadow_cache();
+        self.renderer.render_scene(scene, &self.settings);
+        scene
+            .active_camera
+            .set_subpixel_jitter(0.0, 0.0, width, height);
+        scene.active_camera.set_sample_rotation(0.0);
+
+        // 在线性空间累积，gamma 编码后的值直接平均会使边缘偏暗
+        let color_data = self
+            .renderer
+            .frame_buffer
+            .get_color_buffer_linear(use_gamma);
+        if self.accumulation_buffer.len() == color_data.len() {
+            for (sum, value) in self.accumulation_buffer.iter_mut().zip(&color_data) {
+                *sum += value;
+            }
+            self.accumulated_samples += 1;
+        } else {
+            self.accumulation_buffer = color_data;
+            self.accumulated_samples = 1;
+        }
+
+        let count = self.accumulated_samples as f32;
+        let averaged: Vec<f32> = self
+            .accumulation_buffer
+            .iter()
+            .map(|sum| sum / count)
+            .collect();
+        self.renderer
+            .frame_buffer
+            .set_color_buffer_linear(&averaged, use_gamma);
+        self.display_render_result(ctx);
+        ctx.request_repaint();
+    }
+
     /// 保存当前渲染结果为截图
     fn take_screenshot(&mut self) -> Result<String, String> {
         // 确保输出目录存在
@@ -344,11 +396,13 @@ impl CoreMethods for RasterizerApp {
     fn reset_to_defaults(&mut self) {
         // 保留当前的文件路径设置
         let obj_path = self.settings.obj.clone();
+        let primitives = self.settings.primitives.clone();
         let output_dir = self.settings.output_dir.clone();
         let output_name = self.settings.output.clone();
 
         let new_settings = RenderSettings {
             obj: obj_path,
+            primitives,
             output_dir,
             output: output_name,
             ..Default::default()
@@ -433,10 +487,7 @@ impl CoreMethods for RasterizerApp {
             self.pre_render_progress.store(0, Ordering::SeqCst);
 
             if self.is_generating_video {
-                let (_, _, frames_per_rotation) =
-                    calculate_rotation_parameters(self.settings.rotation_speed, self.settings.fps);
-                let total_frames =
-                    (frames_per_rotation as f32 * self.settings.rotation_cycles) as usize;
+                let total_frames = animation_total_frames(&self.settings);
                 let progress = self.video_progress.load(Ordering::SeqCst);
                 let percent = (progress as f32 / total_frames as f32 * 100.0).round();
 
@@ -467,7 +518,9 @@ impl CoreMethods for RasterizerApp {
     }
 
     fn can_generate_video(&self) -> bool {
-        !self.is_realtime_rendering && !self.is_generating_video && self.ffmpeg_available
+        !self.is_realtime_rendering
+            && !self.is_generating_video
+            && (self.ffmpeg_available || !self.settings.video_format.needs_ffmpeg())
     }
 
     // === 动画状态管理实现 ===
@@ -479,6 +532,7 @@ impl CoreMethods for RasterizerApp {
 
         self.is_realtime_rendering = true;
         self.last_frame_time = None;
+        self.playback_timeline = None;
         self.current_fps = 0.0;
         self.fps_history.clear();
         self.avg_fps = 0.0;
@@ -489,6 +543,7 @@ impl CoreMethods for RasterizerApp {
 
     fn stop_animation_rendering(&mut self) {
         self.is_realtime_rendering = false;
+        self.accumulated_samples = 0;
         self.status_message = "已停止动画渲染".to_string();
     }
 
@@ -536,11 +591,11 @@ impl CoreMethods for RasterizerApp {
         }
 
         // 2. 清理已完成的视频生成线程
-        if let Some(handle) = &self.video_generation_thread {
-            if handle.is_finished() {
-                // 线程已完成，标记需要在主循环中处理
-                debug!("检测到已完成的视频生成线程，等待主循环处理");
-            }
+        if let Some(handle) = &self.video_generation_thread
+            && handle.is_finished()
+        {
+            // 线程已完成，标记需要在主循环中处理
+            debug!("检测到已完成的视频生成线程，等待主循环处理");
         }
 
         // 3. 在空闲状态下进行额外清理
@@ -558,3 +613,15 @@ impl CoreMethods for RasterizerApp {
         }
     }
 }
+
+/// 以 base 为底的 Halton 低差异序列第 index 项，取值 [0, 1)
+fn halton(mut index: usize, base: usize) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0 / base as f32;
+    while index > 0 {
+        result += (index % base) as f32 * fraction;
+        index /= base;
+        fraction /= base as f32;
+    }
+    result
+}
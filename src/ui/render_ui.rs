@@ -23,6 +23,15 @@ pub trait RenderUIMethods {
     /// 选择背景图片
     fn select_background_image(&mut self);
 
+    /// 选择卡通着色渐变纹理或Matcap纹理
+    fn select_shading_texture(&mut self, is_matcap: bool);
+
+    /// 选择地形高度图或地形纹理
+    fn select_terrain_file(&mut self, is_heightmap: bool);
+
+    /// 选择几何地面纹理
+    fn select_ground_texture(&mut self);
+
     /// 选择输出目录
     fn select_output_dir(&mut self);
 
@@ -137,6 +146,105 @@ impl RenderUIMethods for RasterizerApp {
         }
     }
 
+    /// 选择卡通着色渐变纹理或Matcap纹理
+    fn select_shading_texture(&mut self, is_matcap: bool) {
+        let title = if is_matcap {
+            "选择Matcap纹理"
+        } else {
+            "选择卡通渐变纹理"
+        };
+        let result = FileDialogBuilder::default()
+            .set_title(title)
+            .add_filter("图像文件", ["png", "jpg", "jpeg", "bmp", "tga"])
+            .open_single_file()
+            .show();
+
+        match result {
+            Ok(Some(path)) => {
+                if let Some(path_str) = path.to_str() {
+                    if is_matcap {
+                        self.settings.matcap_texture = Some(path_str.to_string());
+                    } else {
+                        self.settings.toon_ramp_texture = Some(path_str.to_string());
+                    }
+                    self.status_message = format!("已选择纹理: {path_str}");
+
+                    // 查找纹理在模型加载时附加到材质，需要重新加载场景
+                    self.interface_interaction.anything_changed = true;
+                    self.scene = None;
+                    self.rendered_image = None;
+                }
+            }
+            Ok(None) => {
+                self.status_message = "纹理选择被取消".to_string();
+            }
+            Err(e) => {
+                self.set_error(format!("纹理选择错误: {e}"));
+            }
+        }
+    }
+
+    /// 选择地形高度图或地形纹理
+    fn select_terrain_file(&mut self, is_heightmap: bool) {
+        let title = if is_heightmap {
+            "选择地形高度图"
+        } else {
+            "选择地形纹理"
+        };
+        let result = FileDialogBuilder::default()
+            .set_title(title)
+            .add_filter("图像文件", ["png", "jpg", "jpeg", "bmp", "tga"])
+            .open_single_file()
+            .show();
+
+        match result {
+            Ok(Some(path)) => {
+                if let Some(path_str) = path.to_str() {
+                    if is_heightmap {
+                        self.settings.terrain_heightmap = Some(path_str.to_string());
+                    } else {
+                        self.settings.terrain_texture = Some(path_str.to_string());
+                    }
+                    self.status_message = format!("已选择: {path_str}");
+
+                    // 地形在场景同步时按新路径重新生成
+                    self.interface_interaction.anything_changed = true;
+                }
+            }
+            Ok(None) => {
+                self.status_message = "文件选择被取消".to_string();
+            }
+            Err(e) => {
+                self.set_error(format!("文件选择错误: {e}"));
+            }
+        }
+    }
+
+    /// 选择几何地面纹理
+    fn select_ground_texture(&mut self) {
+        let result = FileDialogBuilder::default()
+            .set_title("选择地面纹理")
+            .add_filter("图像文件", ["png", "jpg", "jpeg", "bmp", "tga"])
+            .open_single_file()
+            .show();
+
+        match result {
+            Ok(Some(path)) => {
+                if let Some(path_str) = path.to_str() {
+                    self.settings.ground_plane_texture = Some(path_str.to_string());
+                    self.status_message = format!("已选择地面纹理: {path_str}");
+                    self.interface_interaction.anything_changed = true;
+                }
+            }
+            Ok(None) => {
+                self.status_message = "纹理选择被取消".to_string();
+            }
+            Err(e) => {
+                self.set_error(format!("纹理选择错误: {e}"));
+            }
+        }
+    }
+
     /// 选择输出目录
     fn select_output_dir(&mut self) {
         let result = FileDialogBuilder::default()
@@ -7,10 +7,18 @@ use super::core::CoreMethods;
 use super::render_ui::RenderUIMethods;
 use crate::core::renderer::Renderer;
 use crate::geometry::camera::ProjectionType;
+use crate::geometry::mesh_ops::triangle_count;
+use crate::geometry::primitives::{PrimitiveObject, PrimitiveShape};
 use crate::io::config_loader::TomlConfigLoader;
-use crate::io::render_settings::{AnimationType, RotationAxis, parse_point3, parse_vec3};
+use crate::io::render_settings::{
+    AnimationType, FeatureLineMode, HiddenEdgeStyle, MAX_LOD_LEVELS, MAX_SUBDIVISION_LEVEL,
+    MaterialMode, NormalizeMode, RotationAxis, VideoFormat, parse_point3, parse_vec3,
+};
 use crate::material_system::light::Light;
-use crate::utils::render_utils::calculate_rotation_parameters;
+use crate::material_system::materials::{MaterialOverride, MaterialType, resolve_material};
+use crate::scene::camera_path::SplineType;
+use crate::scene::timeline::Keyframe;
+use crate::utils::render_utils::{animation_cycle, animation_total_frames};
 
 /// UI组件和工具提示相关方法的特质
 pub trait WidgetMethods {
@@ -34,9 +42,21 @@ pub trait WidgetMethods {
     /// 绘制物体变换控制面板
     fn ui_object_transform_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
 
+    /// 网格处理（细分、简化、重算法线）面板
+    fn ui_mesh_operations_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
+
     /// 绘制背景与环境设置面板
     fn ui_background_settings(app: &mut RasterizerApp, ui: &mut egui::Ui);
 
+    /// 高度图地形设置面板
+    fn ui_terrain_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
+
+    /// 特征线（线稿）设置面板
+    fn ui_feature_lines_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
+
+    /// 网格检查调试可视化面板
+    fn ui_debug_overlay_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
+
     /// 绘制相机设置面板
     fn ui_camera_settings_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
 
@@ -49,6 +69,12 @@ pub trait WidgetMethods {
     /// 绘制Phong材质设置面板
     fn ui_phong_material_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
 
+    /// 绘制风格化着色（卡通、Lambert、Oren-Nayar、无光照、Matcap）参数面板
+    fn ui_stylized_shading_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
+
+    /// 绘制逐材质编辑面板
+    fn ui_material_list_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
+
     /// 绘制动画设置面板
     fn ui_animation_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context);
 
@@ -107,6 +133,27 @@ impl WidgetMethods for RasterizerApp {
                     ui.label(RichText::new("背景设置").size(14.0).strong());
                     Self::ui_background_settings(self, ui);
                 });
+
+                ui.separator();
+
+                ui.group(|ui| {
+                    ui.label(RichText::new("地形设置").size(14.0).strong());
+                    Self::ui_terrain_panel(self, ui, ctx);
+                });
+
+                ui.separator();
+
+                ui.group(|ui| {
+                    ui.label(RichText::new("线稿设置").size(14.0).strong());
+                    Self::ui_feature_lines_panel(self, ui, ctx);
+                });
+
+                ui.separator();
+
+                ui.group(|ui| {
+                    ui.label(RichText::new("调试可视化").size(14.0).strong());
+                    Self::ui_debug_overlay_panel(self, ui, ctx);
+                });
             });
 
             // === 3D变换组 ===
@@ -118,6 +165,13 @@ impl WidgetMethods for RasterizerApp {
 
                 ui.separator();
 
+                ui.group(|ui| {
+                    ui.label(RichText::new("网格处理").size(14.0).strong());
+                    Self::ui_mesh_operations_panel(self, ui, ctx);
+                });
+
+                ui.separator();
+
                 ui.group(|ui| {
                     ui.label(RichText::new("相机控制").size(14.0).strong());
                     Self::ui_camera_settings_panel(self, ui, ctx);
@@ -132,19 +186,33 @@ impl WidgetMethods for RasterizerApp {
                 ui.separator();
 
                 // 然后根据着色模型显示专用设置
-                if self.settings.use_pbr {
-                    ui.group(|ui| {
-                        ui.label(RichText::new("✨ PBR专用参数").size(14.0).strong());
-                        Self::ui_pbr_material_panel(self, ui, ctx);
-                    });
+                match self.settings.shading_model {
+                    MaterialType::PBR => {
+                        ui.group(|ui| {
+                            ui.label(RichText::new("✨ PBR专用参数").size(14.0).strong());
+                            Self::ui_pbr_material_panel(self, ui, ctx);
+                        });
+                    }
+                    MaterialType::Phong => {
+                        ui.group(|ui| {
+                            ui.label(RichText::new("✨ Phong专用参数").size(14.0).strong());
+                            Self::ui_phong_material_panel(self, ui, ctx);
+                        });
+                    }
+                    _ => {
+                        ui.group(|ui| {
+                            ui.label(RichText::new("✨ 风格化着色参数").size(14.0).strong());
+                            Self::ui_stylized_shading_panel(self, ui, ctx);
+                        });
+                    }
                 }
 
-                if self.settings.use_phong {
-                    ui.group(|ui| {
-                        ui.label(RichText::new("✨ Phong专用参数").size(14.0).strong());
-                        Self::ui_phong_material_panel(self, ui, ctx);
-                    });
-                }
+                ui.separator();
+
+                ui.group(|ui| {
+                    ui.label(RichText::new("🧩 逐材质编辑").size(14.0).strong());
+                    Self::ui_material_list_panel(self, ui, ctx);
+                });
             });
 
             // === 动画与渲染组 ===
@@ -227,6 +295,43 @@ impl WidgetMethods for RasterizerApp {
             }
         });
 
+        ui.horizontal(|ui| {
+            ui.label("程序化图元：");
+            for kind in PrimitiveShape::KINDS {
+                let resp = ui.small_button(kind);
+                if resp.clicked()
+                    && let Ok(shape) = PrimitiveShape::with_defaults(kind)
+                {
+                    // 新图元沿 X 轴依次排开，便于并排对比材质
+                    let index = app.settings.primitives.len();
+                    let mut primitive =
+                        PrimitiveObject::new(format!("{kind}_{}", index + 1), shape);
+                    primitive.position = format!("{},0,0", index as f32 * 2.5);
+                    app.settings.primitives.push(primitive);
+                    app.interface_interaction.anything_changed = true;
+                    app.scene = None;
+                    app.rendered_image = None;
+                }
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "添加图元；存在图元时代替OBJ文件，材质名即图元名，可用材质覆盖单独调整",
+                );
+            }
+            if !app.settings.primitives.is_empty() && ui.button("清空").clicked() {
+                app.settings.primitives.clear();
+                app.interface_interaction.anything_changed = true;
+                app.scene = None;
+                app.rendered_image = None;
+            }
+        });
+        if !app.settings.primitives.is_empty() && app.settings.obj.is_some() {
+            ui.colored_label(
+                Color32::from_rgb(230, 150, 50),
+                "已添加程序化图元，OBJ文件被忽略（清空图元后恢复）",
+            );
+        }
+
         // 配置文件管理
         ui.separator();
         ui.horizontal(|ui| {
@@ -349,6 +454,28 @@ impl WidgetMethods for RasterizerApp {
         }
         Self::add_tooltip(resp1, ctx, "启用Z缓冲进行深度测试，处理物体遮挡关系");
 
+        // 渐进累积
+        ui.horizontal(|ui| {
+            let old_accumulation = app.progressive_accumulation;
+            let resp = ui.checkbox(&mut app.progressive_accumulation, "渐进累积");
+            if app.progressive_accumulation != old_accumulation {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "视图静止时逐帧累积子像素抖动采样，消除几何边缘和纹理的锯齿\n每个样本旋转PCF采样核和景深采样圆盘，软阴影和散景逐步变得平滑\n任何交互都会重新开始累积",
+            );
+
+            let resp = ui.add_enabled(
+                app.progressive_accumulation,
+                egui::DragValue::new(&mut app.accumulation_target)
+                    .range(2..=1024)
+                    .suffix(" 样本"),
+            );
+            Self::add_tooltip(resp, ctx, "累积达到该样本数后停止渲染");
+        });
+
         // 表面颜色设置
         ui.horizontal(|ui| {
             ui.label("表面颜色：");
@@ -400,25 +527,27 @@ impl WidgetMethods for RasterizerApp {
         // 着色模型设置
         ui.horizontal(|ui| {
             ui.label("着色模型：");
-            let old_phong = app.settings.use_phong;
-            let old_pbr = app.settings.use_pbr;
-
-            let phong_response = ui.radio_value(&mut app.settings.use_phong, true, "Phong着色");
-            if phong_response.clicked() && app.settings.use_phong {
-                app.settings.use_pbr = false;
-            }
-
-            let pbr_response = ui.radio_value(&mut app.settings.use_pbr, true, "PBR渲染");
-            if pbr_response.clicked() && app.settings.use_pbr {
-                app.settings.use_phong = false;
-            }
-
-            if app.settings.use_phong != old_phong || app.settings.use_pbr != old_pbr {
+            let old_model = app.settings.shading_model;
+            let resp = egui::ComboBox::from_id_salt("shading_model")
+                .selected_text(app.settings.shading_model.display_name())
+                .show_ui(ui, |ui| {
+                    for model in MaterialType::ALL {
+                        ui.selectable_value(
+                            &mut app.settings.shading_model,
+                            model,
+                            model.display_name(),
+                        );
+                    }
+                })
+                .response;
+            if app.settings.shading_model != old_model {
                 app.interface_interaction.anything_changed = true;
             }
-
-            Self::add_tooltip(phong_response, ctx, "使用 Phong 着色（逐像素着色）和 Blinn-Phong 光照模型\n提供高质量的光照效果，适合大多数场景");
-            Self::add_tooltip(pbr_response, ctx, "使用基于物理的渲染（PBR）\n提供更真实的材质效果，但需要更多的参数调整");
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "Phong：Blinn-Phong 光照\nPBR：基于物理的渲染\n卡通：色阶或渐变纹理\nLambert / Oren-Nayar：纯漫反射（后者适合粗糙表面）\n无光照 / Matcap：不依赖光源",
+            );
         });
 
         ui.separator();
@@ -476,8 +605,11 @@ impl WidgetMethods for RasterizerApp {
                         let resp = ui.add(
                             egui::Slider::new(&mut app.settings.shadow_distance, 1.0..=100.0)
                                 .suffix(" 单位")
+                                // 只限制拖动，按包围盒自适应的值可以超出滑块范围
+                                .clamping(egui::SliderClamping::Edits)
                         );
                         if (app.settings.shadow_distance - old_distance).abs() > f32::EPSILON {
+                            app.settings.shadow_distance_explicit = true;
                             app.interface_interaction.anything_changed = true;
                         }
                         Self::add_tooltip(resp, ctx, "阴影渲染的最大距离\n距离越大覆盖范围越广，但阴影精度可能降低");
@@ -573,6 +705,55 @@ impl WidgetMethods for RasterizerApp {
         }
         Self::add_tooltip(resp9, ctx, "仅渲染三角形边缘，显示为线框");
 
+        let resp = ui.checkbox(&mut app.settings.shaded_wireframe, "着色+线框");
+        if resp.changed() {
+            app.interface_interaction.anything_changed = true;
+        }
+        Self::add_tooltip(
+            resp,
+            ctx,
+            "在深度测试后的着色表面上叠加抗锯齿线框，用于检查网格",
+        );
+
+        if app.settings.shaded_wireframe {
+            ui.horizontal(|ui| {
+                ui.label("隐藏边：");
+                for (style, label, tooltip) in [
+                    (HiddenEdgeStyle::Culled, "不显示", "被遮挡的边不绘制"),
+                    (HiddenEdgeStyle::Dashed, "虚线", "被遮挡的边以虚线绘制"),
+                    (HiddenEdgeStyle::Faded, "淡化", "被遮挡的边以半透明绘制"),
+                ] {
+                    let resp = ui.radio_value(&mut app.settings.hidden_edge_style, style, label);
+                    if resp.changed() {
+                        app.interface_interaction.anything_changed = true;
+                    }
+                    Self::add_tooltip(resp, ctx, tooltip);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let resp = ui.add(
+                    egui::Slider::new(&mut app.settings.wireframe_width, 0.5..=4.0)
+                        .text("线宽")
+                        .step_by(0.1),
+                );
+                if resp.changed() {
+                    app.interface_interaction.anything_changed = true;
+                }
+                Self::add_tooltip(resp, ctx, "叠加线框的宽度（像素）");
+
+                let wire_color = app.settings.get_wireframe_color_vec();
+                let mut wire_color_array = [wire_color.x, wire_color.y, wire_color.z];
+                if ui.color_edit_button_rgb(&mut wire_color_array).changed() {
+                    app.settings.wireframe_color = format!(
+                        "{},{},{}",
+                        wire_color_array[0], wire_color_array[1], wire_color_array[2]
+                    );
+                    app.interface_interaction.anything_changed = true;
+                }
+            });
+        }
+
         // 小三角形剔除设置
         ui.horizontal(|ui| {
             let old_cull = app.settings.cull_small_triangles;
@@ -597,6 +778,38 @@ impl WidgetMethods for RasterizerApp {
             }
         });
 
+        // 线段/点图元设置（仅模型包含 l/p 图元时显示）
+        let has_primitives = app.scene.as_ref().is_some_and(|scene| {
+            scene
+                .object
+                .model
+                .meshes
+                .iter()
+                .any(|mesh| !mesh.lines.is_empty() || !mesh.points.is_empty())
+        });
+        if has_primitives {
+            ui.horizontal(|ui| {
+                let resp = ui.add(
+                    egui::Slider::new(&mut app.settings.primitive_line_width, 0.5..=8.0)
+                        .text("图元线宽")
+                        .step_by(0.1),
+                );
+                if resp.changed() {
+                    app.interface_interaction.anything_changed = true;
+                }
+                Self::add_tooltip(resp, ctx, "OBJ 线段（l）图元的抗锯齿线宽（像素）");
+            });
+            let resp = ui.add(
+                egui::Slider::new(&mut app.settings.point_size, 0.001..=0.2)
+                    .logarithmic(true)
+                    .text("点大小"),
+            );
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "OBJ 点（p）图元的直径（世界单位），近大远小");
+        }
+
         ui.separator();
 
         // 纹理设置
@@ -670,6 +883,33 @@ impl WidgetMethods for RasterizerApp {
                 Self::add_tooltip(resp, ctx, "整体缩放倍率，影响所有轴");
             });
         });
+
+        // 归一化方式（加载时生效）
+        ui.horizontal(|ui| {
+            ui.label("归一化：");
+            for (mode, label, tooltip) in [
+                (NormalizeMode::Unit, "单位尺寸", "移到原点并缩放到单位尺寸"),
+                (
+                    NormalizeMode::CenterOnly,
+                    "仅居中",
+                    "移到原点，保留真实尺寸；裁剪面、阴影距离和地面高度按包围盒自适应",
+                ),
+                (
+                    NormalizeMode::None,
+                    "原始坐标",
+                    "保留原始坐标和尺寸；裁剪面、阴影距离和地面高度按包围盒自适应",
+                ),
+            ] {
+                let resp = ui.radio_value(&mut app.settings.object_normalize, mode, label);
+                if resp.changed() {
+                    // 归一化在加载时执行，需要重新加载模型
+                    app.scene = None;
+                    app.rendered_image = None;
+                    app.interface_interaction.anything_changed = true;
+                }
+                Self::add_tooltip(resp, ctx, tooltip);
+            }
+        });
     }
 
     /// 背景与环境设置面板
@@ -786,10 +1026,12 @@ impl WidgetMethods for RasterizerApp {
                     .add(
                         egui::Slider::new(&mut app.settings.ground_plane_height, -10.0..=5.0)
                             .text("地面高度")
-                            .step_by(0.1),
+                            .step_by(0.1)
+                            .clamping(egui::SliderClamping::Edits),
                     )
                     .changed()
                 {
+                    app.settings.ground_plane_height_explicit = true;
                     app.interface_interaction.anything_changed = true;
                 }
 
@@ -797,6 +1039,7 @@ impl WidgetMethods for RasterizerApp {
                 if ui.button("自动适配").clicked() {
                     if let Some(optimal_height) = app.calculate_optimal_ground_height() {
                         app.settings.ground_plane_height = optimal_height;
+                        app.settings.ground_plane_height_explicit = true;
 
                         app.interface_interaction.anything_changed = true;
                         app.status_message = format!("地面高度已自动调整为 {optimal_height:.2}");
@@ -805,128 +1048,677 @@ impl WidgetMethods for RasterizerApp {
                     }
                 }
             });
-        }
-    }
-
-    fn ui_camera_settings_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
-        ui.horizontal(|ui| {
-            ui.label("相机位置 (x,y,z)：");
-            let old = app.settings.camera_from.clone();
-            let resp = ui.text_edit_singleline(&mut app.settings.camera_from);
-            if app.settings.camera_from != old {
-                if let Some(scene) = &mut app.scene {
-                    if let Ok(from) = parse_point3(&app.settings.camera_from) {
-                        scene.active_camera.params.position = from;
-                        scene.active_camera.update_matrices();
-                        app.interface_interaction.anything_changed = true;
-                    }
-                }
-            }
-            Self::add_tooltip(resp, ctx, "相机的位置坐标，格式为x,y,z");
-        });
 
-        ui.horizontal(|ui| {
-            ui.label("相机目标 (x,y,z)：");
-            let old = app.settings.camera_at.clone();
-            let resp = ui.text_edit_singleline(&mut app.settings.camera_at);
-            if app.settings.camera_at != old {
-                if let Some(scene) = &mut app.scene {
-                    if let Ok(at) = parse_point3(&app.settings.camera_at) {
-                        scene.active_camera.params.target = at;
-                        scene.active_camera.update_matrices();
-                        app.interface_interaction.anything_changed = true;
-                    }
-                }
+            if ui
+                .checkbox(&mut app.settings.ground_plane_mesh, "几何地面")
+                .on_hover_text("使用真实网格地面：与模型做深度测试，受所有光源照明并接收阴影")
+                .changed()
+            {
+                app.interface_interaction.anything_changed = true;
             }
-            Self::add_tooltip(resp, ctx, "相机看向的目标点坐标，格式为x,y,z");
-        });
 
-        ui.horizontal(|ui| {
-            ui.label("相机上方向 (x,y,z)：");
-            let old = app.settings.camera_up.clone();
-            let resp = ui.text_edit_singleline(&mut app.settings.camera_up);
-            if app.settings.camera_up != old {
-                if let Some(scene) = &mut app.scene {
-                    if let Ok(up) = parse_vec3(&app.settings.camera_up) {
-                        scene.active_camera.params.up = up.normalize();
-                        scene.active_camera.update_matrices();
-                        app.interface_interaction.anything_changed = true;
-                    }
+            if app.settings.ground_plane_mesh {
+                if ui
+                    .add(
+                        egui::Slider::new(&mut app.settings.ground_plane_size, 1.0..=100.0)
+                            .logarithmic(true)
+                            .text("地面边长"),
+                    )
+                    .changed()
+                {
+                    app.interface_interaction.anything_changed = true;
                 }
-            }
-            Self::add_tooltip(resp, ctx, "相机的上方向向量，格式为x,y,z");
-        });
 
-        ui.horizontal(|ui| {
-            ui.label("视场角 (度)：");
-            let old_fov = app.settings.camera_fov;
-            let resp = ui.add(egui::Slider::new(
-                &mut app.settings.camera_fov,
-                10.0..=120.0,
-            ));
-            if (app.settings.camera_fov - old_fov).abs() > 0.1 {
-                if let Some(scene) = &mut app.scene {
-                    if let ProjectionType::Perspective { fov_y_degrees, .. } =
-                        &mut scene.active_camera.params.projection
+                ui.horizontal(|ui| {
+                    ui.label("地面纹理：");
+                    let mut text = app
+                        .settings
+                        .ground_plane_texture
+                        .clone()
+                        .unwrap_or_default();
+                    let resp = ui.text_edit_singleline(&mut text);
+                    if resp.lost_focus()
+                        && text
+                            != app
+                                .settings
+                                .ground_plane_texture
+                                .clone()
+                                .unwrap_or_default()
                     {
-                        *fov_y_degrees = app.settings.camera_fov;
-                        scene.active_camera.update_matrices();
+                        app.settings.ground_plane_texture = (!text.is_empty()).then_some(text);
                         app.interface_interaction.anything_changed = true;
                     }
+                    if ui.button("浏览").clicked() {
+                        app.select_ground_texture();
+                    }
+                });
+
+                if app.settings.ground_plane_texture.is_some()
+                    && ui
+                        .add(
+                            egui::Slider::new(
+                                &mut app.settings.ground_plane_texture_tiling,
+                                0.1..=50.0,
+                            )
+                            .logarithmic(true)
+                            .text("纹理平铺"),
+                        )
+                        .changed()
+                {
+                    app.interface_interaction.anything_changed = true;
                 }
             }
-            Self::add_tooltip(resp, ctx, "相机视场角，值越大视野范围越广（鱼眼效果）");
-        });
-        ui.separator();
+        }
+    }
 
-        // 相机交互控制设置（敏感度设置不需要立即响应，它们只影响交互行为）
-        ui.group(|ui| {
-            ui.label(RichText::new("相机交互控制").size(16.0).strong());
-            ui.separator();
+    fn ui_terrain_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
+        let resp = ui.checkbox(&mut app.settings.enable_terrain, "启用高度图地形");
+        if resp.changed() {
+            app.interface_interaction.anything_changed = true;
+        }
+        Self::add_tooltip(
+            resp,
+            ctx,
+            "由灰度高度图生成地形网格，参与光照与阴影；可与地面平面同时使用",
+        );
 
-            ui.horizontal(|ui| {
-                ui.label("平移敏感度:");
-                let resp = ui.add(
-                    egui::Slider::new(&mut app.camera_pan_sensitivity, 0.1..=5.0)
-                        .step_by(0.1)
-                        .text("倍率"),
-                );
-                Self::add_tooltip(
-                    resp,
-                    ctx,
-                    "鼠标拖拽时的平移敏感度\n数值越大，鼠标移动相同距离时相机移动越快",
-                );
-            });
+        if !app.settings.enable_terrain {
+            return;
+        }
 
+        for is_heightmap in [true, false] {
             ui.horizontal(|ui| {
-                ui.label("旋转敏感度:");
-                let resp = ui.add(
-                    egui::Slider::new(&mut app.camera_orbit_sensitivity, 0.1..=5.0)
-                        .step_by(0.1)
-                        .text("倍率"),
-                );
+                let (label, path) = if is_heightmap {
+                    ("高度图：", &mut app.settings.terrain_heightmap)
+                } else {
+                    ("纹理：", &mut app.settings.terrain_texture)
+                };
+                ui.label(label);
+                let mut text = path.clone().unwrap_or_default();
+                let resp = ui.text_edit_singleline(&mut text);
+                if resp.lost_focus() && text != path.clone().unwrap_or_default() {
+                    *path = (!text.is_empty()).then_some(text);
+                    app.interface_interaction.anything_changed = true;
+                }
                 Self::add_tooltip(
                     resp,
                     ctx,
-                    "Shift+拖拽时的轨道旋转敏感度\n数值越大，鼠标移动相同距离时相机旋转角度越大",
+                    if is_heightmap {
+                        "灰度图像，白色最高、黑色最低"
+                    } else {
+                        "可选，按平铺次数铺满整个地形"
+                    },
                 );
+                if ui.button("浏览").clicked() {
+                    app.select_terrain_file(is_heightmap);
+                }
             });
+        }
 
-            ui.horizontal(|ui| {
-                ui.label("缩放敏感度:");
+        let sliders = [
+            (
+                &mut app.settings.terrain_size,
+                1.0..=50.0,
+                "边长",
+                "地形在 XZ 平面上的边长（世界单位），以原点为中心",
+            ),
+            (
+                &mut app.settings.terrain_height_scale,
+                0.0..=5.0,
+                "高度缩放",
+                "高度图白色处相对黑色处的抬升量",
+            ),
+            (
+                &mut app.settings.terrain_height,
+                -10.0..=5.0,
+                "基准高度",
+                "高度图黑色处的 Y 坐标",
+            ),
+            (
+                &mut app.settings.terrain_texture_tiling,
+                0.1..=20.0,
+                "纹理平铺",
+                "纹理在地形上重复的次数",
+            ),
+        ];
+        for (value, range, label, tooltip) in sliders {
+            let resp = ui.add(egui::Slider::new(value, range).text(label));
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, tooltip);
+        }
+
+        let resp = ui.add(
+            egui::Slider::new(&mut app.settings.terrain_resolution, 8..=512)
+                .logarithmic(true)
+                .text("网格精度"),
+        );
+        if resp.changed() {
+            app.interface_interaction.anything_changed = true;
+        }
+        Self::add_tooltip(resp, ctx, "每边的网格单元数，越大越精细");
+
+        ui.horizontal(|ui| {
+            let color = app.settings.get_terrain_color_vec();
+            let mut color_array = [color.x, color.y, color.z];
+            if ui.color_edit_button_rgb(&mut color_array).changed() {
+                app.settings.terrain_color =
+                    format!("{},{},{}", color_array[0], color_array[1], color_array[2]);
+                app.interface_interaction.anything_changed = true;
+            }
+            ui.label("地形颜色");
+        });
+
+        ui.horizontal(|ui| {
+            let resp = ui.checkbox(&mut app.settings.terrain_lod, "分块LOD");
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "远处的地形分块使用较稀疏的网格");
+            if app.settings.terrain_lod {
                 let resp = ui.add(
-                    egui::Slider::new(&mut app.camera_dolly_sensitivity, 0.1..=5.0)
-                        .step_by(0.1)
-                        .text("倍率"),
-                );
-                Self::add_tooltip(
-                    resp,
-                    ctx,
-                    "鼠标滚轮的推拉缩放敏感度\n数值越大，滚轮滚动相同距离时相机前后移动越快",
+                    egui::Slider::new(&mut app.settings.terrain_lod_distance, 0.5..=50.0)
+                        .logarithmic(true)
+                        .text("LOD距离"),
                 );
-            });
+                if resp.changed() {
+                    app.interface_interaction.anything_changed = true;
+                }
+                Self::add_tooltip(resp, ctx, "超过该距离开始降级，此后距离每翻倍再降一级");
+            }
+        });
+    }
 
-            // 重置按钮
+    fn ui_feature_lines_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
+        let resp = ui.checkbox(&mut app.settings.enable_feature_lines, "绘制特征线");
+        if resp.changed() {
+            app.interface_interaction.anything_changed = true;
+        }
+        Self::add_tooltip(resp, ctx, "只绘制轮廓、折痕和边界，比线框模式更清晰");
+
+        if !app.settings.enable_feature_lines {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("绘制方式：");
+            let resp = ui.radio_value(
+                &mut app.settings.feature_line_mode,
+                FeatureLineMode::Overlay,
+                "叠加",
+            );
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "线条叠加在着色结果之上");
+
+            let resp = ui.radio_value(
+                &mut app.settings.feature_line_mode,
+                FeatureLineMode::Standalone,
+                "线稿",
+            );
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "只显示线条，表面仅用于遮挡隐藏线");
+        });
+
+        ui.horizontal(|ui| {
+            let resp = ui.checkbox(&mut app.settings.silhouette_lines, "轮廓");
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "正面与背面交界处的边");
+
+            let resp = ui.checkbox(&mut app.settings.crease_lines, "折痕");
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "相邻面夹角超过阈值的边");
+
+            let resp = ui.checkbox(&mut app.settings.boundary_lines, "边界");
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "只属于一个面的开放边");
+        });
+
+        if app.settings.crease_lines {
+            let resp = ui.add(
+                egui::Slider::new(&mut app.settings.crease_angle, 1.0..=180.0)
+                    .text("折痕角度")
+                    .suffix("°"),
+            );
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "相邻面法线夹角超过此值时视为折痕");
+        }
+
+        let resp = ui.add(
+            egui::Slider::new(&mut app.settings.line_width, 0.5..=8.0)
+                .text("线宽")
+                .step_by(0.1),
+        );
+        if resp.changed() {
+            app.interface_interaction.anything_changed = true;
+        }
+        Self::add_tooltip(resp, ctx, "线条宽度（像素），边缘带抗锯齿");
+
+        ui.horizontal(|ui| {
+            let line_color = app.settings.get_line_color_vec();
+            let mut line_color_array = [line_color.x, line_color.y, line_color.z];
+            if ui.color_edit_button_rgb(&mut line_color_array).changed() {
+                app.settings.line_color = format!(
+                    "{},{},{}",
+                    line_color_array[0], line_color_array[1], line_color_array[2]
+                );
+                app.interface_interaction.anything_changed = true;
+            }
+            ui.label("线条颜色");
+        });
+    }
+
+    fn ui_mesh_operations_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
+        let resp = ui.add(
+            egui::Slider::new(
+                &mut app.settings.subdivision_level,
+                0..=MAX_SUBDIVISION_LEVEL,
+            )
+            .text("Loop 细分次数"),
+        );
+        Self::add_tooltip(resp, ctx, "每次细分使三角形数量变为 4 倍，并平滑曲面");
+
+        ui.horizontal(|ui| {
+            let mut decimate = app.settings.decimate_target > 0;
+            let resp = ui.checkbox(&mut decimate, "简化到");
+            if resp.changed() {
+                app.settings.decimate_target = if decimate {
+                    app.model_data
+                        .as_ref()
+                        .map_or(1000, |model| (triangle_count(model) / 2).max(1))
+                } else {
+                    0
+                };
+            }
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "二次误差边折叠简化；边界和UV接缝上的顶点保持不变",
+            );
+            if decimate {
+                ui.add(
+                    egui::DragValue::new(&mut app.settings.decimate_target)
+                        .range(1..=10_000_000)
+                        .speed(10.0),
+                );
+                ui.label("个三角形");
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let resp = ui.checkbox(&mut app.settings.recompute_normals, "重算法线");
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "按角度加权重新计算顶点法线，夹角超过平滑角的边保持为硬边",
+            );
+            if app.settings.recompute_normals {
+                ui.add(
+                    egui::Slider::new(&mut app.settings.smoothing_angle, 0.0..=180.0)
+                        .text("平滑角(度)"),
+                );
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("应用网格处理").clicked() {
+                // 网格处理在加载时执行，需要重新加载模型
+                app.scene = None;
+                app.rendered_image = None;
+                app.interface_interaction.anything_changed = true;
+            }
+            if let Some(model) = &app.model_data {
+                ui.label(format!("当前 {} 个三角形", triangle_count(model)));
+            }
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            let resp = ui.checkbox(&mut app.settings.enable_lod, "细节层级 (LOD)");
+            if resp.changed() {
+                // 层级在加载时生成，开启时需要重新加载模型
+                if app.settings.enable_lod {
+                    app.scene = None;
+                    app.rendered_image = None;
+                }
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "按包围球的屏幕尺寸切换网格精度；优先加载同目录下的 <名称>_lod1.obj 等文件，没有时由简化生成\n不能与变形目标同时使用",
+            );
+            if let Some(scene) = &app.scene
+                && app.settings.enable_lod
+            {
+                ui.label(format!(
+                    "当前 LOD{} / 共 {} 级",
+                    scene.object.lod_level,
+                    scene.object.model.lods.len()
+                ));
+            }
+        });
+
+        if app.settings.enable_lod {
+            let resp = ui.add(
+                egui::Slider::new(&mut app.settings.lod_levels, 1..=MAX_LOD_LEVELS)
+                    .text("生成层级数"),
+            );
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "没有 LOD 文件时由简化生成的层级数，点击应用网格处理后生效",
+            );
+
+            let resp = ui.add(
+                egui::Slider::new(&mut app.settings.lod_ratio, 0.05..=0.9).text("每级保留比例"),
+            );
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "每一级相对上一级保留的三角形比例，点击应用网格处理后生效",
+            );
+
+            let resp = ui.add(
+                egui::Slider::new(&mut app.settings.lod_switch_size, 10.0..=2000.0)
+                    .logarithmic(true)
+                    .text("切换尺寸(像素)"),
+            );
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "包围球投影直径小于该值时切换到 LOD1，之后每缩小一半再降一级",
+            );
+
+            let resp = ui.add(
+                egui::Slider::new(&mut app.settings.lod_hysteresis, 0.0..=0.45).text("缓冲比例"),
+            );
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "阈值两侧的缓冲带，避免动画中尺寸在阈值附近时反复切换",
+            );
+        }
+    }
+
+    fn ui_debug_overlay_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
+        let surface_modes = [
+            (
+                &mut app.settings.debug_uv_checker,
+                "UV棋盘格",
+                "用棋盘格替换表面颜色，检查UV拉伸与接缝",
+            ),
+            (
+                &mut app.settings.debug_face_orientation,
+                "面朝向",
+                "正面显示为蓝色，背面显示为红色，用于发现翻转的三角形",
+            ),
+        ];
+        ui.horizontal(|ui| {
+            for (value, label, tooltip) in surface_modes {
+                let resp = ui.checkbox(value, label);
+                if resp.changed() {
+                    app.interface_interaction.anything_changed = true;
+                }
+                Self::add_tooltip(resp, ctx, tooltip);
+            }
+        });
+
+        let line_overlays = [
+            (
+                &mut app.settings.debug_normals,
+                "顶点法线",
+                "以青色线段显示顶点法线",
+            ),
+            (
+                &mut app.settings.debug_tangents,
+                "切线",
+                "以橙色线段显示由UV推导的三角形切线",
+            ),
+            (
+                &mut app.settings.debug_bounding_boxes,
+                "包围盒",
+                "以黄色线框显示每个网格的包围盒",
+            ),
+            (
+                &mut app.settings.debug_shadow_frustum,
+                "阴影视锥",
+                "以品红线框显示阴影贴图覆盖的光源视锥",
+            ),
+        ];
+        ui.horizontal_wrapped(|ui| {
+            for (value, label, tooltip) in line_overlays {
+                let resp = ui.checkbox(value, label);
+                if resp.changed() {
+                    app.interface_interaction.anything_changed = true;
+                }
+                Self::add_tooltip(resp, ctx, tooltip);
+            }
+        });
+
+        if app.settings.debug_normals || app.settings.debug_tangents {
+            let resp = ui.add(
+                egui::Slider::new(&mut app.settings.debug_normal_length, 0.005..=0.5)
+                    .logarithmic(true)
+                    .text("线段长度"),
+            );
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "法线与切线线段的长度（视图空间单位）");
+        }
+
+        if app.settings.debug_shadow_frustum && !app.settings.enable_shadow_mapping {
+            ui.label(
+                RichText::new("⚠️ 需要启用阴影映射")
+                    .color(Color32::DARK_GRAY)
+                    .size(12.0),
+            );
+        }
+    }
+
+    fn ui_camera_settings_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
+        ui.horizontal(|ui| {
+            ui.label("相机位置 (x,y,z)：");
+            let old = app.settings.camera_from.clone();
+            let resp = ui.text_edit_singleline(&mut app.settings.camera_from);
+            if app.settings.camera_from != old
+                && let Some(scene) = &mut app.scene
+                && let Ok(from) = parse_point3(&app.settings.camera_from)
+            {
+                scene.active_camera.params.position = from;
+                scene.active_camera.update_matrices();
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "相机的位置坐标，格式为x,y,z");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("相机目标 (x,y,z)：");
+            let old = app.settings.camera_at.clone();
+            let resp = ui.text_edit_singleline(&mut app.settings.camera_at);
+            if app.settings.camera_at != old
+                && let Some(scene) = &mut app.scene
+                && let Ok(at) = parse_point3(&app.settings.camera_at)
+            {
+                scene.active_camera.params.target = at;
+                scene.active_camera.update_matrices();
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "相机看向的目标点坐标，格式为x,y,z");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("相机上方向 (x,y,z)：");
+            let old = app.settings.camera_up.clone();
+            let resp = ui.text_edit_singleline(&mut app.settings.camera_up);
+            if app.settings.camera_up != old
+                && let Some(scene) = &mut app.scene
+                && let Ok(up) = parse_vec3(&app.settings.camera_up)
+            {
+                scene.active_camera.params.up = up.normalize();
+                scene.active_camera.update_matrices();
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "相机的上方向向量，格式为x,y,z");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("视场角 (度)：");
+            let old_fov = app.settings.camera_fov;
+            let resp = ui.add(egui::Slider::new(
+                &mut app.settings.camera_fov,
+                10.0..=120.0,
+            ));
+            if (app.settings.camera_fov - old_fov).abs() > 0.1
+                && let Some(scene) = &mut app.scene
+                && let ProjectionType::Perspective { fov_y_degrees, .. } =
+                    &mut scene.active_camera.params.projection
+            {
+                *fov_y_degrees = app.settings.camera_fov;
+                scene.active_camera.update_matrices();
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "相机视场角，值越大视野范围越广（鱼眼效果）");
+        });
+
+        // 景深：镜头参数改变后同步到场景相机
+        let old_lens = app.settings.lens_params();
+        let old_depth_of_field = app.settings.enable_depth_of_field;
+        ui.horizontal(|ui| {
+            let resp = ui.checkbox(&mut app.settings.enable_depth_of_field, "景深");
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "按深度缓冲和镜头参数模糊焦外区域（仅透视投影）\n启用后单击视图可对焦到该点",
+            );
+        });
+        if app.settings.enable_depth_of_field {
+            ui.horizontal(|ui| {
+                ui.label("光圈 f/：");
+                let resp = ui.add(
+                    egui::Slider::new(&mut app.settings.camera_aperture, 0.1..=32.0)
+                        .logarithmic(true),
+                );
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "f 值越小景深越浅；场景单位按米计算，归一化模型约 2 米大小，需要较小的 f 值才有明显虚化",
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("传感器高度 (mm)：");
+                let resp = ui.add(egui::Slider::new(
+                    &mut app.settings.camera_sensor_size,
+                    4.0..=100.0,
+                ));
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "24 为 35mm 全画幅；视场角不变时传感器越大焦距越长，虚化越强",
+                );
+            });
+            ui.horizontal(|ui| {
+                let mut manual_focus = app.settings.camera_focus_distance.is_some();
+                let resp = ui.checkbox(&mut manual_focus, "对焦距离：");
+                Self::add_tooltip(resp, ctx, "不勾选时对焦到相机目标点");
+                if manual_focus != app.settings.camera_focus_distance.is_some() {
+                    app.settings.camera_focus_distance = manual_focus.then(|| {
+                        app.scene
+                            .as_ref()
+                            .map_or(3.0, |scene| scene.active_camera.focus_distance())
+                    });
+                }
+                if let Some(focus_distance) = &mut app.settings.camera_focus_distance {
+                    ui.add(
+                        egui::DragValue::new(focus_distance)
+                            .speed(0.01)
+                            .range(0.01..=1000.0),
+                    );
+                } else if let Some(scene) = &app.scene {
+                    ui.label(format!(
+                        "{:.3}（目标点）",
+                        scene.active_camera.focus_distance()
+                    ));
+                }
+            });
+        }
+        if (app.settings.lens_params() != old_lens
+            || app.settings.enable_depth_of_field != old_depth_of_field)
+            && let Some(scene) = &mut app.scene
+        {
+            scene.active_camera.params.lens = app.settings.lens_params();
+            app.interface_interaction.anything_changed = true;
+        }
+        ui.separator();
+
+        // 相机交互控制设置（敏感度设置不需要立即响应，它们只影响交互行为）
+        ui.group(|ui| {
+            ui.label(RichText::new("相机交互控制").size(16.0).strong());
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("平移敏感度:");
+                let resp = ui.add(
+                    egui::Slider::new(&mut app.camera_pan_sensitivity, 0.1..=5.0)
+                        .step_by(0.1)
+                        .text("倍率"),
+                );
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "鼠标拖拽时的平移敏感度\n数值越大，鼠标移动相同距离时相机移动越快",
+                );
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("旋转敏感度:");
+                let resp = ui.add(
+                    egui::Slider::new(&mut app.camera_orbit_sensitivity, 0.1..=5.0)
+                        .step_by(0.1)
+                        .text("倍率"),
+                );
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "Shift+拖拽时的轨道旋转敏感度\n数值越大，鼠标移动相同距离时相机旋转角度越大",
+                );
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("缩放敏感度:");
+                let resp = ui.add(
+                    egui::Slider::new(&mut app.camera_dolly_sensitivity, 0.1..=5.0)
+                        .step_by(0.1)
+                        .text("倍率"),
+                );
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "鼠标滚轮的推拉缩放敏感度\n数值越大，滚轮滚动相同距离时相机前后移动越快",
+                );
+            });
+
+            // 重置按钮
             ui.horizontal(|ui| {
                 if ui.button("重置交互敏感度").clicked() {
                     app.camera_pan_sensitivity = 1.0;
@@ -1011,7 +1803,7 @@ impl WidgetMethods for RasterizerApp {
     // 基础颜色（通用于PBR和Phong）
     ui.horizontal(|ui| {
         ui.label("基础颜色 (Base Color / Diffuse):");
-        let base_color_vec = if app.settings.use_pbr {
+        let base_color_vec = if app.settings.shading_model == MaterialType::PBR {
             parse_vec3(&app.settings.base_color)
         } else {
             parse_vec3(&app.settings.diffuse_color)
@@ -1026,7 +1818,7 @@ impl WidgetMethods for RasterizerApp {
             );
 
             // 同时更新PBR和Phong的颜色设置
-            if app.settings.use_pbr {
+            if app.settings.shading_model == MaterialType::PBR {
                 app.settings.base_color = color_str;
             } else {
                 app.settings.diffuse_color = color_str;
@@ -1054,6 +1846,20 @@ impl WidgetMethods for RasterizerApp {
         );
     });
 
+    // Alpha 测试阈值（用于 map_d 遮罩或带 alpha 通道的纹理）
+    ui.horizontal(|ui| {
+        ui.label("镂空阈值 (Cutoff)：");
+        let resp = ui.add(egui::Slider::new(&mut app.settings.alpha_cutoff, 0.0..=1.0));
+        if resp.changed() {
+            app.interface_interaction.anything_changed = true;
+        }
+        Self::add_tooltip(
+            resp,
+            ctx,
+            "Alpha测试阈值，遮罩纹理(map_d)或纹理alpha低于此值的像素将被丢弃\n用于树叶、贴花等镂空效果",
+        );
+    });
+
     // 自发光控制（通用于PBR和Phong）
     ui.horizontal(|ui| {
         ui.label("自发光颜色 (Emissive):");
@@ -1293,46 +2099,140 @@ impl WidgetMethods for RasterizerApp {
                 app.settings.lights.remove(index);
             }
 
-            // 如果没有光源，显示提示
-            if app.settings.lights.is_empty() {
-                ui.group(|ui| {
-                    ui.label("💡 提示：当前没有光源");
-                    ui.label("点击上方的「➕ 添加」按钮来添加光源");
-                });
+            // 如果没有光源，显示提示
+            if app.settings.lights.is_empty() {
+                ui.group(|ui| {
+                    ui.label("💡 提示：当前没有光源");
+                    ui.label("点击上方的「➕ 添加」按钮来添加光源");
+                });
+            }
+        }
+    }
+
+    /// PBR材质设置面板
+    fn ui_pbr_material_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
+        ui.horizontal(|ui| {
+            ui.label("金属度 (Metallic)：");
+            let resp = ui.add(egui::Slider::new(&mut app.settings.metallic, 0.0..=1.0));
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "材质的金属特性，0为非金属，1为纯金属");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("粗糙度 (Roughness)：");
+            let resp = ui.add(egui::Slider::new(&mut app.settings.roughness, 0.0..=1.0));
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "材质的粗糙程度，影响高光的散射");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("环境光遮蔽 (AO)：");
+            let resp = ui.add(egui::Slider::new(
+                &mut app.settings.ambient_occlusion,
+                0.0..=1.0,
+            ));
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "环境光遮蔽程度，模拟凹陷处的阴影");
+        });
+
+        ui.separator();
+        ui.label(RichText::new("扩展层").strong());
+
+        ui.horizontal(|ui| {
+            ui.label("清漆 (Clearcoat)：");
+            let resp = ui.add(egui::Slider::new(&mut app.settings.clearcoat, 0.0..=1.0));
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "表面清漆层的强度，适用于车漆、漆木等材质");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("清漆粗糙度：");
+            let resp = ui.add(egui::Slider::new(
+                &mut app.settings.clearcoat_roughness,
+                0.0..=1.0,
+            ));
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(resp, ctx, "清漆层独立的粗糙度，值小时高光更锐利");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("布料光泽 (Sheen)：");
+            let sheen_vec = parse_vec3(&app.settings.sheen_color)
+                .unwrap_or_else(|_| nalgebra::Vector3::new(0.0, 0.0, 0.0));
+            let mut sheen_rgb = [sheen_vec.x, sheen_vec.y, sheen_vec.z];
+            let resp = ui.color_edit_button_rgb(&mut sheen_rgb);
+            if resp.changed() {
+                app.settings.sheen_color = format!(
+                    "{:.3},{:.3},{:.3}",
+                    sheen_rgb[0], sheen_rgb[1], sheen_rgb[2]
+                );
+                app.interface_interaction.anything_changed = true;
             }
-        }
-    }
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "Charlie布料光泽颜色，黑色表示关闭\n适用于天鹅绒、绒布等",
+            );
+        });
 
-    /// PBR材质设置面板
-    fn ui_pbr_material_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
         ui.horizontal(|ui| {
-            ui.label("金属度 (Metallic)：");
-            let resp = ui.add(egui::Slider::new(&mut app.settings.metallic, 0.0..=1.0));
+            ui.label("光泽粗糙度：");
+            let resp = ui.add(egui::Slider::new(
+                &mut app.settings.sheen_roughness,
+                0.0..=1.0,
+            ));
             if resp.changed() {
                 app.interface_interaction.anything_changed = true;
             }
-            Self::add_tooltip(resp, ctx, "材质的金属特性，0为非金属，1为纯金属");
+            Self::add_tooltip(resp, ctx, "布料光泽的扩散程度");
         });
 
         ui.horizontal(|ui| {
-            ui.label("粗糙度 (Roughness)：");
-            let resp = ui.add(egui::Slider::new(&mut app.settings.roughness, 0.0..=1.0));
+            ui.label("各向异性：");
+            let resp = ui.add(egui::Slider::new(&mut app.settings.anisotropy, -1.0..=1.0));
             if resp.changed() {
                 app.interface_interaction.anything_changed = true;
             }
-            Self::add_tooltip(resp, ctx, "材质的粗糙程度，影响高光的散射");
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "各向异性GGX高光拉伸程度，适用于拉丝金属\n切线方向由模型UV推导",
+            );
         });
 
         ui.horizontal(|ui| {
-            ui.label("环境光遮蔽 (AO)：");
+            ui.label("切线旋转：");
             let resp = ui.add(egui::Slider::new(
-                &mut app.settings.ambient_occlusion,
-                0.0..=1.0,
+                &mut app.settings.anisotropy_rotation,
+                0.0..=360.0,
             ));
             if resp.changed() {
                 app.interface_interaction.anything_changed = true;
             }
-            Self::add_tooltip(resp, ctx, "环境光遮蔽程度，模拟凹陷处的阴影");
+            Self::add_tooltip(resp, ctx, "各向异性切线绕法线旋转的角度（度）");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("透射 (Transmission)：");
+            let resp = ui.add(egui::Slider::new(&mut app.settings.transmission, 0.0..=1.0));
+            if resp.changed() {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "薄表面透射比例，背光时光线穿透表面并被基础色染色\n不改变透明度，适用于纸张、树叶、薄布",
+            );
         });
     }
 
@@ -1392,6 +2292,253 @@ impl WidgetMethods for RasterizerApp {
         });
     }
 
+    /// 风格化着色参数面板
+    fn ui_stylized_shading_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
+        match app.settings.shading_model {
+            MaterialType::Toon => {
+                ui.horizontal(|ui| {
+                    ui.label("色阶数：");
+                    let resp = ui.add(egui::Slider::new(&mut app.settings.toon_bands, 2..=8));
+                    if resp.changed() {
+                        app.interface_interaction.anything_changed = true;
+                    }
+                    Self::add_tooltip(resp, ctx, "光照被量化的层级数量\n设置渐变纹理后此项无效");
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("渐变纹理：");
+                    let text = app
+                        .settings
+                        .toon_ramp_texture
+                        .as_deref()
+                        .unwrap_or("未选择");
+                    ui.label(text);
+                });
+                ui.horizontal(|ui| {
+                    let resp = ui.button("选择渐变纹理");
+                    if resp.clicked() {
+                        app.select_shading_texture(false);
+                    }
+                    Self::add_tooltip(
+                        resp,
+                        ctx,
+                        "横向从左（背光）到右（迎光）的渐变图\n选择后需重新加载模型",
+                    );
+                    if app.settings.toon_ramp_texture.is_some() && ui.button("清除").clicked() {
+                        app.settings.toon_ramp_texture = None;
+                        app.scene = None;
+                        app.rendered_image = None;
+                        app.interface_interaction.anything_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("高光锐度：");
+                    let resp = ui.add(egui::Slider::new(&mut app.settings.shininess, 1.0..=100.0));
+                    if resp.changed() {
+                        app.interface_interaction.anything_changed = true;
+                    }
+                    Self::add_tooltip(resp, ctx, "卡通硬边高光的大小，值越大高光越小");
+                });
+            }
+            MaterialType::OrenNayar => {
+                ui.horizontal(|ui| {
+                    ui.label("粗糙度：");
+                    let resp = ui.add(egui::Slider::new(&mut app.settings.roughness, 0.0..=1.0));
+                    if resp.changed() {
+                        app.interface_interaction.anything_changed = true;
+                    }
+                    Self::add_tooltip(
+                        resp,
+                        ctx,
+                        "微表面朝向的离散程度，0时退化为Lambert\n适合陶土、布料、月面等粗糙表面",
+                    );
+                });
+            }
+            MaterialType::Matcap => {
+                ui.horizontal(|ui| {
+                    ui.label("Matcap纹理：");
+                    let text = app.settings.matcap_texture.as_deref().unwrap_or("内置");
+                    ui.label(text);
+                });
+                ui.horizontal(|ui| {
+                    let resp = ui.button("选择Matcap纹理");
+                    if resp.clicked() {
+                        app.select_shading_texture(true);
+                    }
+                    Self::add_tooltip(resp, ctx, "球形材质捕获图片\n选择后需重新加载模型");
+                    if app.settings.matcap_texture.is_some() && ui.button("清除").clicked() {
+                        app.settings.matcap_texture = None;
+                        app.scene = None;
+                        app.rendered_image = None;
+                        app.interface_interaction.anything_changed = true;
+                    }
+                });
+            }
+            MaterialType::Lambert => {
+                ui.label("Lambert模型仅使用基础颜色和漫反射强度");
+            }
+            MaterialType::Unlit => {
+                ui.label("无光照模型直接输出纹理或基础颜色");
+            }
+            MaterialType::Phong | MaterialType::PBR => {}
+        }
+
+        if matches!(
+            app.settings.shading_model,
+            MaterialType::Toon | MaterialType::Lambert | MaterialType::OrenNayar
+        ) {
+            ui.horizontal(|ui| {
+                ui.label("漫反射强度：");
+                let resp = ui.add(egui::Slider::new(
+                    &mut app.settings.diffuse_intensity,
+                    0.0..=2.0,
+                ));
+                if resp.changed() {
+                    app.interface_interaction.anything_changed = true;
+                }
+                Self::add_tooltip(resp, ctx, "漫反射光的强度倍数");
+            });
+        }
+    }
+
+    /// 逐材质编辑面板
+    fn ui_material_list_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
+        ui.horizontal(|ui| {
+            ui.label("材质来源：");
+            let old_mode = app.settings.material_mode.clone();
+            let resp = egui::ComboBox::from_id_salt("material_mode")
+                .selected_text(match app.settings.material_mode {
+                    MaterialMode::Global => "全局参数",
+                    MaterialMode::FromFile => "保留MTL",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(
+                        &mut app.settings.material_mode,
+                        MaterialMode::Global,
+                        "全局参数",
+                    );
+                    ui.selectable_value(
+                        &mut app.settings.material_mode,
+                        MaterialMode::FromFile,
+                        "保留MTL",
+                    );
+                })
+                .response;
+            if app.settings.material_mode != old_mode {
+                app.interface_interaction.anything_changed = true;
+            }
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "全局参数：上方的材质参数覆盖所有材质\n保留MTL：使用模型文件中各材质自己的参数",
+            );
+        });
+
+        let Some(model_data) = &app.model_data else {
+            ui.label("加载模型后可逐个编辑材质");
+            return;
+        };
+        let source_materials = model_data.materials.clone();
+
+        for (index, source) in source_materials.iter().enumerate() {
+            let target = source.name.clone();
+            let override_index = app
+                .settings
+                .material_overrides
+                .iter()
+                .position(|o| o.target == target);
+            let mut edited = resolve_material(source, &app.settings);
+            let header = if override_index.is_some() {
+                format!("{target} ✏")
+            } else {
+                target.clone()
+            };
+
+            egui::CollapsingHeader::new(header)
+                .id_salt(("material_item", index))
+                .show(ui, |ui| {
+                    let mut changed = false;
+
+                    let mut edit_color =
+                        |ui: &mut egui::Ui, label: &str, color: &mut nalgebra::Vector3<f32>| {
+                            ui.horizontal(|ui| {
+                                ui.label(label);
+                                let mut rgb = [color.x, color.y, color.z];
+                                if ui.color_edit_button_rgb(&mut rgb).changed() {
+                                    *color = nalgebra::Vector3::new(rgb[0], rgb[1], rgb[2]);
+                                    changed = true;
+                                }
+                            });
+                        };
+                    edit_color(ui, "基础颜色：", &mut edited.base_color);
+                    edit_color(ui, "自发光：", &mut edited.emissive);
+                    match edited.material_type {
+                        MaterialType::PBR => edit_color(ui, "布料光泽：", &mut edited.sheen_color),
+                        _ => edit_color(ui, "镜面反射颜色：", &mut edited.specular),
+                    }
+
+                    let mut sliders: Vec<(&str, &mut f32, std::ops::RangeInclusive<f32>)> =
+                        vec![("透明度：", &mut edited.alpha, 0.0..=1.0)];
+                    match edited.material_type {
+                        MaterialType::PBR => sliders.extend([
+                            ("金属度：", &mut edited.metallic, 0.0..=1.0),
+                            ("粗糙度：", &mut edited.roughness, 0.0..=1.0),
+                            ("环境光遮蔽：", &mut edited.ambient_occlusion, 0.0..=1.0),
+                            ("清漆：", &mut edited.clearcoat, 0.0..=1.0),
+                            ("清漆粗糙度：", &mut edited.clearcoat_roughness, 0.0..=1.0),
+                            ("光泽粗糙度：", &mut edited.sheen_roughness, 0.0..=1.0),
+                            ("各向异性：", &mut edited.anisotropy, -1.0..=1.0),
+                            ("切线旋转：", &mut edited.anisotropy_rotation, 0.0..=360.0),
+                            ("透射：", &mut edited.transmission, 0.0..=1.0),
+                        ]),
+                        _ => {
+                            sliders.push((
+                                "漫反射强度：",
+                                &mut edited.diffuse_intensity,
+                                0.0..=2.0,
+                            ));
+                            sliders.push((
+                                "镜面反射强度：",
+                                &mut edited.specular_intensity,
+                                0.0..=2.0,
+                            ));
+                            sliders.push(("光泽度：", &mut edited.shininess, 1.0..=100.0));
+                            if edited.material_type == MaterialType::OrenNayar {
+                                sliders.push(("粗糙度：", &mut edited.roughness, 0.0..=1.0));
+                            }
+                        }
+                    }
+                    for (label, value, range) in sliders {
+                        ui.horizontal(|ui| {
+                            ui.label(label);
+                            if ui.add(egui::Slider::new(value, range)).changed() {
+                                changed = true;
+                            }
+                        });
+                    }
+
+                    if changed {
+                        let material_override = MaterialOverride::from_material(&target, &edited);
+                        match override_index {
+                            Some(i) => app.settings.material_overrides[i] = material_override,
+                            None => app.settings.material_overrides.push(material_override),
+                        }
+                        app.interface_interaction.anything_changed = true;
+                    }
+
+                    if let Some(i) = override_index {
+                        let resp = ui.button("↺ 恢复此材质");
+                        if resp.clicked() {
+                            app.settings.material_overrides.remove(i);
+                            app.interface_interaction.anything_changed = true;
+                        }
+                        Self::add_tooltip(resp, ctx, "删除该材质的覆盖参数，恢复为材质来源中的值");
+                    }
+                });
+        }
+    }
+
     /// 动画设置面板
     fn ui_animation_panel(app: &mut RasterizerApp, ui: &mut egui::Ui, ctx: &Context) {
         ui.horizontal(|ui| {
@@ -1414,15 +2561,83 @@ impl WidgetMethods for RasterizerApp {
             Self::add_tooltip(resp, ctx, "生成视频的每秒帧数");
         });
 
-        let (_, seconds_per_rotation, frames_per_rotation) =
-            calculate_rotation_parameters(app.settings.rotation_speed, app.settings.fps);
-        let total_frames = (frames_per_rotation as f32 * app.settings.rotation_cycles) as usize;
-        let total_seconds = seconds_per_rotation * app.settings.rotation_cycles;
+        ui.horizontal(|ui| {
+            ui.label("视频格式:");
+            let resp = egui::ComboBox::from_id_salt("video_format_combo")
+                .selected_text(app.settings.video_format.name().to_uppercase())
+                .show_ui(ui, |ui| {
+                    for format in VideoFormat::ALL {
+                        ui.selectable_value(
+                            &mut app.settings.video_format,
+                            format,
+                            format.name().to_uppercase(),
+                        );
+                    }
+                })
+                .response;
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "MP4 需要系统安装 ffmpeg\nGIF、APNG、WebP 由内置编码器生成，无需外部程序",
+            );
+        });
+
+        if app.settings.video_format == VideoFormat::Gif {
+            ui.horizontal(|ui| {
+                ui.label("GIF 颜色数:");
+                let resp = ui.add(
+                    egui::DragValue::new(&mut app.settings.gif_colors)
+                        .speed(1)
+                        .range(2..=256),
+                );
+                Self::add_tooltip(resp, ctx, "所有帧共用的调色板大小，颜色越少文件越小");
+                let resp = ui.checkbox(&mut app.settings.gif_dither, "抖动");
+                Self::add_tooltip(resp, ctx, "使用 Floyd-Steinberg 误差扩散减轻渐变处的色带");
+            });
+        }
+
+        let (cycle_seconds, _) = animation_cycle(&app.settings);
+        let total_frames = animation_total_frames(&app.settings);
+        let total_seconds = if app.settings.animation_type.is_timeline() {
+            cycle_seconds
+        } else {
+            cycle_seconds * app.settings.rotation_cycles
+        };
 
         ui.label(format!(
             "估计总帧数: {total_frames} (视频长度: {total_seconds:.1}秒)"
         ));
 
+        ui.horizontal(|ui| {
+            let resp = ui.checkbox(&mut app.settings.motion_blur, "运动模糊");
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "视频生成时每帧在快门开启期间渲染多个子帧并取平均，减轻快速运动的频闪\n渲染时间随采样数成倍增加",
+            );
+            if app.settings.motion_blur {
+                ui.label("采样:");
+                let resp = ui.add(
+                    egui::DragValue::new(&mut app.settings.motion_blur_samples)
+                        .speed(1)
+                        .range(1..=64),
+                );
+                Self::add_tooltip(resp, ctx, "每帧的子帧数");
+                ui.label("快门角:");
+                let resp = ui.add(
+                    egui::DragValue::new(&mut app.settings.shutter_angle)
+                        .speed(5.0)
+                        .range(1.0..=360.0)
+                        .suffix("°"),
+                );
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "快门开启时长占一帧的比例，180° 为半帧，360° 为整帧",
+                );
+            }
+        });
+
         // 动画类型选择
         ui.horizontal(|ui| {
             ui.label("动画类型:");
@@ -1431,6 +2646,8 @@ impl WidgetMethods for RasterizerApp {
                 .selected_text(match current_animation_type {
                     AnimationType::CameraOrbit => "相机轨道旋转",
                     AnimationType::ObjectLocalRotation => "物体局部旋转",
+                    AnimationType::Keyframes => "关键帧时间线",
+                    AnimationType::CameraPath => "相机路径",
                     AnimationType::None => "无动画",
                 })
                 .show_ui(ui, |ui| {
@@ -1444,6 +2661,16 @@ impl WidgetMethods for RasterizerApp {
                         AnimationType::ObjectLocalRotation,
                         "物体局部旋转",
                     );
+                    ui.selectable_value(
+                        &mut app.settings.animation_type,
+                        AnimationType::Keyframes,
+                        "关键帧时间线",
+                    );
+                    ui.selectable_value(
+                        &mut app.settings.animation_type,
+                        AnimationType::CameraPath,
+                        "相机路径",
+                    );
                     ui.selectable_value(
                         &mut app.settings.animation_type,
                         AnimationType::None,
@@ -1452,8 +2679,102 @@ impl WidgetMethods for RasterizerApp {
                 });
         });
 
-        // 旋转轴选择 (仅当动画类型不是 None 时显示)
-        if app.settings.animation_type != AnimationType::None {
+        // 关键帧：记录当前相机和物体变换，时刻排在最后一个关键帧之后 1 秒
+        if app.settings.animation_type == AnimationType::Keyframes {
+            ui.horizontal(|ui| {
+                let next_time = app
+                    .settings
+                    .keyframes
+                    .iter()
+                    .map(|keyframe| keyframe.time + 1.0)
+                    .fold(0.0_f32, f32::max);
+                let resp = ui.button(format!("记录关键帧 (t={next_time:.1}s)"));
+                if resp.clicked() {
+                    match Keyframe::capture(next_time, &app.settings) {
+                        Ok(keyframe) => {
+                            app.settings.keyframes.push(keyframe);
+                            app.status_message = format!("已记录 t={next_time:.1}s 的关键帧");
+                        }
+                        Err(e) => app.set_error(format!("记录关键帧失败: {e}")),
+                    }
+                }
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "记录当前的相机位置、目标、视场角和物体变换；插值方式等可在配置文件中编辑",
+                );
+                if ui.button("清空").clicked() {
+                    app.settings.keyframes.clear();
+                }
+                ui.label(format!("{} 个关键帧", app.settings.keyframes.len()));
+            });
+        }
+
+        // 相机路径：以当前相机位置追加控制点，注视点和滚转在配置文件中设置
+        if app.settings.animation_type == AnimationType::CameraPath {
+            ui.horizontal(|ui| {
+                let resp = ui.button("添加路径点");
+                if resp.clicked() {
+                    match parse_point3(&app.settings.camera_from) {
+                        Ok(point) => {
+                            let points = &mut app.settings.camera_path.points;
+                            points.push(point);
+                            app.status_message = format!("已添加第 {} 个路径点", points.len());
+                        }
+                        Err(e) => app.set_error(format!("添加路径点失败: {e}")),
+                    }
+                }
+                Self::add_tooltip(resp, ctx, "把当前相机位置追加为路径控制点");
+                if ui.button("清空").clicked() {
+                    app.settings.camera_path.points.clear();
+                }
+                ui.label(format!(
+                    "{} 个路径点",
+                    app.settings.camera_path.points.len()
+                ));
+            });
+
+            let path = &mut app.settings.camera_path;
+
+            ui.horizontal(|ui| {
+                ui.label("曲线:");
+                ui.radio_value(&mut path.spline, SplineType::CatmullRom, "Catmull-Rom");
+                let resp = ui.radio_value(&mut path.spline, SplineType::Bezier, "贝塞尔");
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "分段三次贝塞尔：锚点与两个控制点交替，开放路径需要 3n+1 个点",
+                );
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("时长:");
+                ui.add(
+                    egui::DragValue::new(&mut path.duration)
+                        .speed(0.1)
+                        .range(0.1..=600.0)
+                        .suffix(" 秒"),
+                );
+                let resp = ui.checkbox(&mut path.closed, "闭合");
+                Self::add_tooltip(resp, ctx, "首尾相连，循环播放时没有跳变");
+                let resp = ui.checkbox(&mut path.constant_speed, "匀速");
+                Self::add_tooltip(
+                    resp,
+                    ctx,
+                    "按弧长参数化，相机沿路径匀速移动；关闭时每段曲线用时相同",
+                );
+            });
+
+            if let Err(e) = path.validate() {
+                ui.colored_label(Color32::from_rgb(230, 150, 50), e);
+            }
+        }
+
+        // 旋转轴选择 (仅旋转动画显示)
+        if matches!(
+            app.settings.animation_type,
+            AnimationType::CameraOrbit | AnimationType::ObjectLocalRotation
+        ) {
             ui.horizontal(|ui| {
                 ui.label("旋转轴:");
                 let current_rotation_axis = app.settings.rotation_axis.clone();
@@ -1528,6 +2849,100 @@ impl WidgetMethods for RasterizerApp {
             ));
             Self::add_tooltip(resp, ctx, "实时渲染中的旋转速度倍率");
         });
+
+        // 变形目标：权重只影响顶点偏移，无需重新加载
+        if !app.settings.morph_targets.is_empty() {
+            ui.separator();
+            ui.label("变形目标：");
+            for target in &mut app.settings.morph_targets {
+                let resp =
+                    ui.add(egui::Slider::new(&mut target.weight, 0.0..=1.0).text(&target.name));
+                if resp.changed() {
+                    app.interface_interaction.anything_changed = true;
+                }
+                Self::add_tooltip(resp, ctx, &format!("变形目标文件: {}", target.obj));
+            }
+        }
+
+        // 骨骼动画：BVH 和对齐参数在加载时绑定，修改后重新加载模型
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("BVH 动作：");
+            let current = app.settings.skeleton_bvh.clone().unwrap_or_default();
+            let mut text = current.clone();
+            let resp = ui.text_edit_singleline(&mut text);
+            if resp.lost_focus() && text != current {
+                app.settings.skeleton_bvh = (!text.is_empty()).then_some(text);
+                app.interface_interaction.anything_changed = true;
+                app.scene = None;
+            }
+            Self::add_tooltip(
+                resp,
+                ctx,
+                "BVH 动作捕捉文件，模型按顶点到骨骼的距离自动绑定，网格需与 BVH 的静止姿态对齐",
+            );
+        });
+
+        if app.settings.skeleton_bvh.is_none() {
+            return;
+        }
+
+        let skeleton = app
+            .scene
+            .as_ref()
+            .and_then(|scene| scene.object.model.skeleton.as_ref());
+        let clip_duration = skeleton.map_or(0.0, |skeleton| skeleton.clip_duration());
+        if let Some(skeleton) = skeleton {
+            ui.label(format!(
+                "{} 个关节（根关节 {}），动作时长 {clip_duration:.2} 秒",
+                skeleton.joints.len(),
+                skeleton
+                    .joints
+                    .first()
+                    .map_or("-", |joint| joint.name.as_str())
+            ));
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("缩放:");
+            let resp = ui.add(
+                egui::DragValue::new(&mut app.settings.skeleton_scale)
+                    .speed(0.001)
+                    .range(0.0001..=100.0),
+            );
+            Self::add_tooltip(
+                resp.clone(),
+                ctx,
+                "BVH 坐标到模型坐标的缩放，厘米单位的 BVH 通常为 0.01",
+            );
+            ui.label("偏移:");
+            let offset_resp = ui.text_edit_singleline(&mut app.settings.skeleton_offset);
+            if resp.drag_stopped() || resp.lost_focus() || offset_resp.lost_focus() {
+                app.interface_interaction.anything_changed = true;
+                app.scene = None;
+            }
+            Self::add_tooltip(offset_resp, ctx, "缩放之后再平移骨架，格式 x,y,z");
+        });
+
+        ui.horizontal(|ui| {
+            let resp = ui.add(
+                egui::Slider::new(&mut app.settings.skeleton_speed, 0.0..=4.0).text("播放速度"),
+            );
+            Self::add_tooltip(resp, ctx, "动画播放时动作片段的速度倍率");
+            ui.checkbox(&mut app.settings.skeleton_loop, "循环");
+        });
+
+        let resp = ui.add(
+            egui::Slider::new(
+                &mut app.settings.skeleton_time,
+                0.0..=clip_duration.max(0.01),
+            )
+            .text("姿态时刻 (秒)"),
+        );
+        if resp.changed() {
+            app.interface_interaction.anything_changed = true;
+        }
+        Self::add_tooltip(resp, ctx, "未播放动画时显示的姿态，播放动画时作为起始时刻");
     }
 
     /// 按钮控制面板
@@ -1655,17 +3070,14 @@ impl WidgetMethods for RasterizerApp {
                 let progress = app.video_progress.load(Ordering::SeqCst);
 
                 // 使用通用函数计算实际帧数
-                let (_, _, frames_per_rotation) =
-                    calculate_rotation_parameters(app.settings.rotation_speed, app.settings.fps);
-                let total_frames =
-                    (frames_per_rotation as f32 * app.settings.rotation_cycles) as usize;
+                let total_frames = animation_total_frames(&app.settings);
 
                 let percent = (progress as f32 / total_frames as f32 * 100.0).round();
                 format!("生成视频中... {percent}%")
-            } else if app.ffmpeg_available {
-                "生成视频".to_string()
+            } else if app.ffmpeg_available || !app.settings.video_format.needs_ffmpeg() {
+                format!("生成{}", app.settings.video_format.name().to_uppercase())
             } else {
-                "生成视频 (需ffmpeg)".to_string()
+                "生成MP4 (需ffmpeg)".to_string()
             };
 
             let is_video_button_enabled = app.can_generate_video();
@@ -1683,7 +3095,7 @@ impl WidgetMethods for RasterizerApp {
             Self::add_tooltip(
                 video_button_response,
                 ctx,
-                "在后台渲染多帧并生成MP4视频。\n需要系统安装ffmpeg。\n生成过程不会影响UI使用。",
+                "在后台渲染多帧并按所选格式生成视频。\nMP4 需要系统安装ffmpeg，GIF、APNG、WebP 使用内置编码器。\n生成过程不会影响UI使用。",
             );
 
             // 清空缓冲区按钮
@@ -1713,6 +3125,12 @@ impl WidgetMethods for RasterizerApp {
         if let Some(time) = app.last_render_time {
             ui.separator();
             ui.label(format!("渲染耗时: {time:.2?}"));
+            if app.progressive_accumulation && !app.is_realtime_rendering {
+                ui.label(format!(
+                    "累积样本: {}/{}",
+                    app.accumulated_samples, app.accumulation_target
+                ));
+            }
 
             // 显示场景统计信息（直接使用SceneStats）
             if let Some(scene) = &app.scene {
@@ -1,3 +1,4 @@
 pub mod model_utils;
 pub mod render_utils;
 pub mod save_utils;
+pub mod video_utils;
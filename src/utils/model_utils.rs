@@ -1,9 +1,17 @@
+use crate::io::render_settings::NormalizeMode;
 use crate::material_system::materials::Model;
-use nalgebra::{Point3, Vector3};
+use nalgebra::{Matrix4, Point3, Vector3};
 
 /// 归一化和中心化模型顶点
-pub fn normalize_and_center_model(model_data: &mut Model) -> (Vector3<f32>, f32) {
-    if model_data.meshes.is_empty() {
+///
+/// - `Unit`：移到原点并缩放到约 [-0.8, 0.8] 的立方体内
+/// - `CenterOnly`：只移到原点，保留真实尺寸
+/// - `None`：保留原始坐标
+pub fn normalize_and_center_model(
+    model_data: &mut Model,
+    mode: &NormalizeMode,
+) -> (Vector3<f32>, f32) {
+    if *mode == NormalizeMode::None || model_data.meshes.is_empty() {
         return (Vector3::zeros(), 1.0);
     }
 
@@ -30,18 +38,42 @@ pub fn normalize_and_center_model(model_data: &mut Model) -> (Vector3<f32>, f32)
     let extent = max_coord - min_coord;
     let max_extent = extent.x.max(extent.y).max(extent.z);
 
-    let scale_factor = if max_extent > 1e-6 {
+    let scale_factor = if *mode == NormalizeMode::CenterOnly {
+        1.0
+    } else if max_extent > 1e-6 {
         1.6 / max_extent // 缩放以大致适合[-0.8, 0.8]立方体（类似于Python的0.8因子）
     } else {
         1.0
     };
 
+    apply_normalization(model_data, &center, scale_factor);
+    model_data.normalization = (center, scale_factor);
+
+    (center, scale_factor)
+}
+
+/// 按给定的中心和缩放系数变换模型：顶点先平移 `-center` 再缩放
+///
+/// 序列帧复用首帧的中心和缩放，使各帧保持同一坐标系。
+pub fn apply_normalization(model_data: &mut Model, center: &Vector3<f32>, scale_factor: f32) {
     // 对所有顶点应用变换
-    for mesh in &mut model_data.meshes {
+    // 各细节层级使用同一变换，保持彼此对齐
+    for mesh in model_data.all_meshes_mut() {
         for vertex in &mut mesh.vertices {
             vertex.position = Point3::from((vertex.position.coords - center) * scale_factor);
         }
+        // 变形偏移是向量，只受缩放影响
+        for target in &mut mesh.morph_targets {
+            for delta in &mut target.position_deltas {
+                *delta *= scale_factor;
+            }
+        }
     }
 
-    (center, scale_factor)
+    // 骨架随网格一起变换，蒙皮矩阵在归一化后的模型空间中计算
+    if let Some(skeleton) = &mut model_data.skeleton {
+        skeleton.to_model = Matrix4::new_scaling(scale_factor)
+            * Matrix4::new_translation(&-center)
+            * skeleton.to_model;
+    }
 }
@@ -1,11 +1,15 @@
 use crate::core::renderer::Renderer;
+use crate::io::obj_sequence::SequenceLoader;
 use crate::io::render_settings::{
     AnimationType, RenderSettings, RotationAxis, get_animation_axis_vector,
 };
+use crate::material_system::materials::Model;
 use crate::scene::scene_utils::Scene;
-use crate::utils::save_utils::save_render_with_settings;
+use crate::scene::timeline::Timeline;
+use crate::utils::save_utils::{color_output_path, save_render_with_settings};
 use log::{debug, info};
 use nalgebra::Vector3;
+use std::path::Path;
 use std::time::Instant;
 
 const BASE_SPEED: f32 = 60.0; // 1s旋转60度
@@ -51,7 +55,123 @@ pub fn animate_scene_step(
         AnimationType::ObjectLocalRotation => {
             scene.object.rotate(rotation_axis, rotation_delta_rad);
         }
-        AnimationType::None => { /* 无动画 */ }
+        // 时间线按绝对时间采样，见 apply_timeline_frame
+        AnimationType::Keyframes | AnimationType::CameraPath | AnimationType::None => {
+            /* 无增量动画 */
+        }
+    }
+}
+
+/// 按动画类型使地面缓存失效：相机移动时地面本体和阴影都要重算，只有物体移动时只需重算阴影
+pub fn invalidate_animated_ground(
+    renderer: &mut Renderer,
+    settings: &RenderSettings,
+    scene: &Scene,
+) {
+    match settings.animation_type {
+        AnimationType::CameraOrbit | AnimationType::Keyframes | AnimationType::CameraPath => {
+            renderer.frame_buffer.invalidate_ground_base_cache();
+            renderer.frame_buffer.invalidate_ground_shadow_cache();
+        }
+        AnimationType::ObjectLocalRotation => {
+            if settings.enable_shadow_mapping {
+                renderer.frame_buffer.invalidate_ground_shadow_cache();
+            }
+        }
+        AnimationType::None => {}
+    }
+    if scene.has_skeletal_animation() {
+        // 蒙皮网格每帧变形，投到地面上的阴影随之变化
+        renderer.frame_buffer.invalidate_ground_shadow_cache();
+    }
+}
+
+/// 运动模糊各子帧相对当前帧的时间偏移（单位：帧）
+///
+/// 快门在帧时刻打开，持续 `shutter_angle / 360` 帧，子帧取各等分区间的中点；未启用运动模糊时只有偏移 0。
+pub fn shutter_offsets(settings: &RenderSettings) -> Vec<f32> {
+    if !settings.motion_blur || settings.motion_blur_samples <= 1 {
+        return vec![0.0];
+    }
+    let samples = settings.motion_blur_samples;
+    let open = settings.shutter_angle / 360.0;
+    (0..samples)
+        .map(|sample| open * (sample as f32 + 0.5) / samples as f32)
+        .collect()
+}
+
+/// 按快门偏移逐个渲染子帧并在线性空间平均颜色，结果写回帧缓冲区
+///
+/// `render_sample` 把场景摆放到给定偏移处并渲染一次；深度缓冲保留最后一个子帧的结果。
+pub fn render_with_motion_blur(
+    renderer: &mut Renderer,
+    settings: &RenderSettings,
+    mut render_sample: impl FnMut(&mut Renderer, f32) -> Result<(), String>,
+) -> Result<(), String> {
+    let offsets = shutter_offsets(settings);
+    if let [offset] = offsets[..] {
+        return render_sample(renderer, offset);
+    }
+
+    // gamma 编码后的值不能直接平均，否则运动拖影偏暗
+    let mut accumulated: Vec<f32> = Vec::new();
+    for &offset in &offsets {
+        render_sample(renderer, offset)?;
+        let color = renderer
+            .frame_buffer
+            .get_color_buffer_linear(settings.use_gamma);
+        accumulated.resize(color.len(), 0.0);
+        for (sum, value) in accumulated.iter_mut().zip(color) {
+            *sum += value;
+        }
+    }
+
+    let count = offsets.len() as f32;
+    for sum in &mut accumulated {
+        *sum /= count;
+    }
+    renderer
+        .frame_buffer
+        .set_color_buffer_linear(&accumulated, settings.use_gamma);
+    Ok(())
+}
+
+/// 采样时间线第 `time` 秒的设置并同步到场景（含骨骼姿态），返回渲染该帧应使用的设置
+pub fn apply_timeline_frame(
+    scene: &mut Scene,
+    timeline: &Timeline,
+    base_settings: &RenderSettings,
+    time: f32,
+    original_model: Option<&Model>,
+) -> Result<RenderSettings, String> {
+    let mut frame_settings = base_settings.clone();
+    timeline.apply(time, &mut frame_settings)?;
+    scene.sync_animated_state(&frame_settings, original_model);
+    scene.pose_skeleton(&frame_settings, time);
+    Ok(frame_settings)
+}
+
+/// 动画一个周期的时长（秒）和帧数：时间线动画为整条时间线，旋转动画为转一圈
+pub fn animation_cycle(settings: &RenderSettings) -> (f32, usize) {
+    if settings.animation_type.is_timeline() {
+        // 相机路径无效时只按关键帧估算，渲染前的 validate 会报告错误
+        let timeline = Timeline::from_settings(settings)
+            .unwrap_or_else(|_| Timeline::new(&settings.keyframes));
+        (timeline.duration(), timeline.frame_count(settings.fps))
+    } else {
+        let (_, seconds_per_rotation, frames_per_rotation) =
+            calculate_rotation_parameters(settings.rotation_speed, settings.fps);
+        (seconds_per_rotation, frames_per_rotation)
+    }
+}
+
+/// 动画的总帧数：时间线播放一遍，旋转动画按 `rotation_cycles` 重复
+pub fn animation_total_frames(settings: &RenderSettings) -> usize {
+    let (_, cycle_frames) = animation_cycle(settings);
+    if settings.animation_type.is_timeline() {
+        cycle_frames
+    } else {
+        (cycle_frames as f32 * settings.rotation_cycles) as usize
     }
 }
 
@@ -86,18 +206,85 @@ pub fn calculate_rotation_parameters(rotation_speed: f32, fps: usize) -> (f32, f
     )
 }
 
-/// 执行完整的动画渲染循环
+/// 按 `frame_start`/`frame_end` 和步长选出要渲染的帧号（完整动画中的帧号）
+pub fn selected_frames(
+    settings: &RenderSettings,
+    total_frames: usize,
+) -> Result<Vec<usize>, String> {
+    if total_frames == 0 {
+        return Ok(Vec::new());
+    }
+    let start = settings.frame_start.unwrap_or(0);
+    if start >= total_frames {
+        return Err(format!(
+            "帧范围起点 {start} 超出动画范围（共 {total_frames} 帧，帧号从 0 开始）"
+        ));
+    }
+    let end = settings
+        .frame_end
+        .map_or(total_frames - 1, |end| end.min(total_frames - 1));
+    Ok((start..=end).step_by(settings.frame_step.max(1)).collect())
+}
+
+/// 只渲染了动画的一部分（指定了帧范围或步长）
+pub fn is_partial_render(settings: &RenderSettings) -> bool {
+    settings.frame_start.is_some() || settings.frame_end.is_some() || settings.frame_step > 1
+}
+
+/// 第 `frame_num` 帧的输出名，按完整动画中的帧号命名，分段渲染的输出可以直接合并
+pub fn frame_output_name(frame_num: usize) -> String {
+    format!("frame_{frame_num:03}")
+}
+
+/// 开启 `skip_existing` 且该帧的输出文件都已存在
+fn frame_already_rendered(settings: &RenderSettings, output_name: &str) -> bool {
+    if !settings.skip_existing || !color_output_path(&settings.output_dir, output_name).exists() {
+        return false;
+    }
+    !(settings.use_zbuffer && settings.save_depth)
+        || Path::new(&settings.output_dir)
+            .join(format!("{output_name}_depth.png"))
+            .exists()
+}
+
+/// 分段或跳帧渲染时报告选中的帧
+fn log_frame_selection(settings: &RenderSettings, frames: &[usize], total_frames: usize) {
+    if let (Some(first), Some(last)) = (frames.first(), frames.last())
+        && is_partial_render(settings)
+    {
+        info!(
+            "分段渲染: 第 {first} - {last} 帧，步长 {}，共 {} / {total_frames} 帧",
+            settings.frame_step,
+            frames.len()
+        );
+    }
+}
+
+/// 执行动画渲染循环，返回选中各帧的输出名（含跳过的已有帧）
+///
+/// 每帧的场景状态只由帧号决定，与从哪一帧开始渲染无关，多台机器可以各自渲染同一配置的不同分段。
+/// 为此细节层级不带滞后，每帧（含运动模糊子帧）只按自身相机选择。
 pub fn run_animation_loop(
     scene: &mut Scene,
     renderer: &mut Renderer,
     settings: &RenderSettings,
-) -> Result<(), String> {
+    original_model: &Model,
+) -> Result<Vec<String>, String> {
+    scene.lod_hysteresis = false;
+    if settings.is_obj_sequence() {
+        return run_sequence_loop(scene, renderer, settings, original_model);
+    }
+    if settings.animation_type.is_timeline() {
+        return run_timeline_loop(scene, renderer, settings, original_model);
+    }
+
     // 使用通用函数计算旋转参数
     let (effective_rotation_speed_dps, _, frames_to_render) =
         calculate_rotation_parameters(settings.rotation_speed, settings.fps);
 
     // 根据用户要求的旋转圈数计算实际帧数
     let total_frames = (frames_to_render as f32 * settings.rotation_cycles) as usize;
+    let frames = selected_frames(settings, total_frames)?;
 
     info!(
         "开始动画渲染 ({} 帧, {:.2} 秒)...",
@@ -108,6 +295,7 @@ pub fn run_animation_loop(
         "动画类型: {:?}, 旋转轴类型: {:?}, 速度: {:.1}度/秒",
         settings.animation_type, settings.rotation_axis, effective_rotation_speed_dps
     );
+    log_frame_selection(settings, &frames, total_frames);
 
     // 计算旋转方向
     let rotation_axis_vec = get_animation_axis_vector(settings);
@@ -119,35 +307,270 @@ pub fn run_animation_loop(
     let rotation_per_frame_rad =
         (360.0 / frames_to_render as f32).to_radians() * settings.rotation_speed.signum();
 
-    // 渲染所有帧
-    for frame_num in 0..total_frames {
-        let frame_start_time = Instant::now();
+    // 每帧都从初始状态按帧号旋转，而不是在上一帧上累加
+    let (initial_camera, initial_transform) = (scene.active_camera.clone(), scene.object.transform);
+    let frame_names = render_selected_frames(settings, &frames, |frame_num, output_name, _| {
         debug!("--- 准备帧 {} / {} ---", frame_num + 1, total_frames);
 
-        // 第一帧通常不旋转，保留原始状态
-        if frame_num > 0 {
+        let time = frame_num as f32 / settings.fps as f32;
+        render_with_motion_blur(renderer, settings, |renderer, offset| {
+            scene.set_camera(initial_camera.clone());
+            scene.object.transform = initial_transform;
             animate_scene_step(
                 scene,
                 &settings.animation_type,
                 &rotation_axis_vec,
-                rotation_per_frame_rad,
+                rotation_per_frame_rad * (frame_num as f32 + offset),
             );
+            scene.pose_skeleton(settings, time + offset / settings.fps as f32);
+            invalidate_animated_ground(renderer, settings, scene);
+            renderer.render_scene(scene, settings);
+            Ok(())
+        })?;
+
+        // 保存当前帧
+        save_render_with_settings(renderer, settings, Some(output_name))
+    })?;
+
+    info!(
+        "动画渲染完成。总时长：{:.2}秒",
+        total_frames as f32 / settings.fps as f32
+    );
+    Ok(frame_names)
+}
+
+/// 依次渲染选中的帧，返回各帧的输出名（含跳过的已有帧）
+///
+/// 开启 `skip_existing` 时输出已存在的帧不再渲染；其余帧交给 `render_frame(帧号, 输出名, 之后仍要渲染的帧号)`，
+/// 由它渲染并保存。
+fn render_selected_frames(
+    settings: &RenderSettings,
+    frames: &[usize],
+    mut render_frame: impl FnMut(usize, &str, &[usize]) -> Result<(), String>,
+) -> Result<Vec<String>, String> {
+    // 预先确定要渲染的帧，后台只预读这些帧
+    let to_render: Vec<usize> = frames
+        .iter()
+        .copied()
+        .filter(|&frame_num| !frame_already_rendered(settings, &frame_output_name(frame_num)))
+        .collect();
+
+    let mut frame_names = Vec::with_capacity(frames.len());
+    let mut rendered = 0;
+    for &frame_num in frames {
+        let frame_output_name = frame_output_name(frame_num);
+        if to_render.get(rendered) == Some(&frame_num) {
+            rendered += 1;
+            let frame_start_time = Instant::now();
+            render_frame(frame_num, &frame_output_name, &to_render[rendered..])?;
+            debug!(
+                "帧 {} 渲染完成，耗时 {:?}",
+                frame_output_name,
+                frame_start_time.elapsed()
+            );
+        } else {
+            debug!("帧 {frame_output_name} 已存在，跳过");
         }
+        frame_names.push(frame_output_name);
+    }
 
-        // 渲染和保存当前帧
-        let frame_output_name = format!("frame_{frame_num:03}");
-        render_single_frame(scene, renderer, settings, &frame_output_name)?;
+    let skipped = frames.len() - rendered;
+    if skipped > 0 {
+        info!("跳过了 {skipped} 个已存在的帧");
+    }
+    Ok(frame_names)
+}
+
+/// 按时间线（关键帧和相机路径）逐帧采样并渲染
+fn run_timeline_loop(
+    scene: &mut Scene,
+    renderer: &mut Renderer,
+    settings: &RenderSettings,
+    original_model: &Model,
+) -> Result<Vec<String>, String> {
+    let timeline = Timeline::from_settings(settings)?;
+    let total_frames = timeline.frame_count(settings.fps);
+    let frames = selected_frames(settings, total_frames)?;
+    info!(
+        "开始时间线动画渲染 ({:?}, {} 个关键帧, {} 帧, {:.2} 秒)...",
+        settings.animation_type,
+        settings.keyframes.len(),
+        total_frames,
+        timeline.duration()
+    );
+    log_frame_selection(settings, &frames, total_frames);
 
+    let frame_names = render_selected_frames(settings, &frames, |frame_num, output_name, _| {
+        let time = frame_num as f32 / settings.fps as f32;
         debug!(
-            "帧 {} 渲染完成，耗时 {:?}",
-            frame_output_name,
-            frame_start_time.elapsed()
+            "--- 准备帧 {} / {} (t={time:.3}s) ---",
+            frame_num + 1,
+            total_frames
         );
-    }
+
+        let mut frame_settings = None;
+        render_with_motion_blur(renderer, settings, |renderer, offset| {
+            let sample_time = time + offset / settings.fps as f32;
+            let sample_settings = apply_timeline_frame(
+                scene,
+                &timeline,
+                settings,
+                sample_time,
+                Some(original_model),
+            )?;
+            // 相机和物体都可能移动，地面缓存每个子帧失效
+            renderer.frame_buffer.invalidate_ground_base_cache();
+            renderer.frame_buffer.invalidate_ground_shadow_cache();
+            renderer.render_scene(scene, &sample_settings);
+            frame_settings = Some(sample_settings);
+            Ok(())
+        })?;
+
+        save_render_with_settings(
+            renderer,
+            frame_settings.as_ref().unwrap_or(settings),
+            Some(output_name),
+        )
+    })?;
+
+    info!("时间线动画渲染完成。总时长：{:.2}秒", timeline.duration());
+    Ok(frame_names)
+}
+
+/// 逐帧加载 OBJ 序列并渲染，每个文件一帧；旋转和时间线动画照常叠加在序列上
+fn run_sequence_loop(
+    scene: &mut Scene,
+    renderer: &mut Renderer,
+    settings: &RenderSettings,
+    first_frame: &Model,
+) -> Result<Vec<String>, String> {
+    let mut sequence = SequenceLoader::new(settings, first_frame)?;
+    let total_frames = sequence.len();
+    let frames = selected_frames(settings, total_frames)?;
+    let timeline = if settings.animation_type.is_timeline() {
+        Some(Timeline::from_settings(settings)?)
+    } else {
+        None
+    };
+    info!(
+        "开始 OBJ 序列动画渲染 ({} 帧, {:.2} 秒, 动画类型: {:?})...",
+        total_frames,
+        total_frames as f32 / settings.fps as f32,
+        settings.animation_type
+    );
+    log_frame_selection(settings, &frames, total_frames);
+
+    let (_, _, frames_per_rotation) =
+        calculate_rotation_parameters(settings.rotation_speed, settings.fps);
+    let rotation_axis_vec = get_animation_axis_vector(settings);
+    let rotation_per_frame_rad =
+        (360.0 / frames_per_rotation as f32).to_radians() * settings.rotation_speed.signum();
+
+    let (initial_camera, initial_transform) = (scene.active_camera.clone(), scene.object.transform);
+    let frame_names =
+        render_selected_frames(settings, &frames, |frame_num, output_name, upcoming| {
+            let time = frame_num as f32 / settings.fps as f32;
+            debug!(
+                "--- 准备帧 {} / {} (序列第 {} 帧) ---",
+                frame_num + 1,
+                total_frames,
+                sequence.frame_number(frame_num)
+            );
+
+            let model = sequence.load(frame_num, upcoming)?;
+            scene.replace_model(model, settings);
+
+            // 网格本身不在文件之间插值，运动模糊只作用于相机、物体变换和时间线
+            let mut frame_settings = None;
+            render_with_motion_blur(renderer, settings, |renderer, offset| {
+                let sample_time = time + offset / settings.fps as f32;
+                match &timeline {
+                    Some(timeline) => {
+                        frame_settings = Some(apply_timeline_frame(
+                            scene,
+                            timeline,
+                            settings,
+                            sample_time,
+                            Some(model),
+                        )?);
+                    }
+                    None => {
+                        scene.set_camera(initial_camera.clone());
+                        scene.object.transform = initial_transform;
+                        animate_scene_step(
+                            scene,
+                            &settings.animation_type,
+                            &rotation_axis_vec,
+                            rotation_per_frame_rad * (frame_num as f32 + offset),
+                        );
+                        scene.pose_skeleton(settings, sample_time);
+                    }
+                }
+
+                // 网格每帧都不同，投到地面上的阴影随之变化
+                if timeline.is_some() || settings.animation_type == AnimationType::CameraOrbit {
+                    renderer.frame_buffer.invalidate_ground_base_cache();
+                }
+                renderer.frame_buffer.invalidate_ground_shadow_cache();
+                renderer.render_scene(scene, frame_settings.as_ref().unwrap_or(settings));
+                Ok(())
+            })?;
+
+            save_render_with_settings(
+                renderer,
+                frame_settings.as_ref().unwrap_or(settings),
+                Some(output_name),
+            )
+        })?;
 
     info!(
-        "动画渲染完成。总时长：{:.2}秒",
+        "OBJ 序列动画渲染完成。总时长：{:.2}秒",
         total_frames as f32 / settings.fps as f32
     );
-    Ok(())
+    Ok(frame_names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range_settings(start: Option<usize>, end: Option<usize>, step: usize) -> RenderSettings {
+        RenderSettings {
+            frame_start: start,
+            frame_end: end,
+            frame_step: step,
+            ..RenderSettings::default()
+        }
+    }
+
+    #[test]
+    fn selected_frames_maps_ranges() {
+        let frames = |start, end, step| selected_frames(&range_settings(start, end, step), 300);
+        assert_eq!(frames(Some(120), Some(240), 1), Ok((120..=240).collect()));
+        assert_eq!(frames(Some(120), None, 1), Ok((120..300).collect()));
+        assert_eq!(frames(None, Some(240), 1), Ok((0..=240).collect()));
+        assert_eq!(frames(Some(7), Some(7), 1), Ok(vec![7]));
+        // 末帧超出动画时截断到最后一帧
+        assert_eq!(frames(Some(290), Some(1000), 1), Ok((290..300).collect()));
+        // 步长保持完整动画中的帧号
+        assert_eq!(frames(Some(10), Some(20), 4), Ok(vec![10, 14, 18]));
+        assert_eq!(frames(None, None, 100), Ok(vec![0, 100, 200]));
+        assert_eq!(frames(None, None, 0), Ok((0..300).collect()));
+    }
+
+    #[test]
+    fn selected_frames_rejects_out_of_range() {
+        assert!(selected_frames(&range_settings(Some(300), None, 1), 300).is_err());
+        assert!(selected_frames(&range_settings(Some(500), Some(600), 1), 300).is_err());
+        assert_eq!(
+            selected_frames(&range_settings(Some(5), None, 1), 0),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn frame_output_names_use_full_animation_numbers() {
+        assert_eq!(frame_output_name(7), "frame_007");
+        assert_eq!(frame_output_name(1234), "frame_1234");
+    }
 }
@@ -1,18 +1,43 @@
 use crate::core::renderer::Renderer;
 use crate::io::render_settings::RenderSettings;
 use crate::material_system::color::apply_colormap_jet;
-use image::ColorType;
+use image::{ColorType, ImageFormat};
 use log::{debug, info, warn};
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 /// 保存RGB图像数据到PNG文件
+///
+/// 先写入同目录下的临时文件再重命名，中断时不会留下不完整的图像（`--skip-existing` 依赖这一点）
 pub fn save_image(path: &str, data: &[u8], width: u32, height: u32) {
-    match image::save_buffer(path, data, width, height, ColorType::Rgb8) {
+    let partial_path = format!("{path}.part");
+    let result = ImageFormat::from_path(path)
+        .and_then(|format| {
+            image::save_buffer_with_format(
+                &partial_path,
+                data,
+                width,
+                height,
+                ColorType::Rgb8,
+                format,
+            )
+        })
+        .map_err(|e| e.to_string())
+        .and_then(|_| fs::rename(&partial_path, path).map_err(|e| e.to_string()));
+    match result {
         Ok(_) => info!("图像已保存到 {path}"),
-        Err(e) => warn!("保存图像到 {path} 时出错: {e}"),
+        Err(e) => {
+            let _ = fs::remove_file(&partial_path);
+            warn!("保存图像到 {path} 时出错: {e}");
+        }
     }
 }
 
+/// 彩色图像的输出路径：`<输出目录>/<名称>_color.png`
+pub fn color_output_path(output_dir: &str, output_name: &str) -> PathBuf {
+    Path::new(output_dir).join(format!("{output_name}_color.png"))
+}
+
 /// 将深度缓冲数据归一化到指定的百分位数范围
 pub fn normalize_depth(depth_buffer: &[f32], min_percentile: f32, max_percentile: f32) -> Vec<f32> {
     // 1. 收集所有有限的深度值
@@ -83,8 +108,7 @@ pub fn save_render_result(
     save_depth: bool,
 ) -> Result<(), String> {
     // 保存彩色图像
-    let color_path = Path::new(output_dir)
-        .join(format!("{output_name}_color.png"))
+    let color_path = color_output_path(output_dir, output_name)
         .to_str()
         .ok_or_else(|| "创建彩色输出路径字符串失败".to_string())?
         .to_string();
@@ -92,27 +116,28 @@ pub fn save_render_result(
     save_image(&color_path, color_data, width as u32, height as u32);
 
     // 保存深度图（如果启用）
-    if settings.use_zbuffer && save_depth {
-        if let Some(depth_data_raw) = depth_data {
-            let depth_normalized = normalize_depth(depth_data_raw, 1.0, 99.0);
-            let depth_colored = apply_colormap_jet(
-                &depth_normalized
-                    .iter()
-                    .map(|&d| 1.0 - d) // 反转：越近 = 越热
-                    .collect::<Vec<_>>(),
-                width,
-                height,
-                settings.use_gamma,
-            );
+    if settings.use_zbuffer
+        && save_depth
+        && let Some(depth_data_raw) = depth_data
+    {
+        let depth_normalized = normalize_depth(depth_data_raw, 1.0, 99.0);
+        let depth_colored = apply_colormap_jet(
+            &depth_normalized
+                .iter()
+                .map(|&d| 1.0 - d) // 反转：越近 = 越热
+                .collect::<Vec<_>>(),
+            width,
+            height,
+            settings.use_gamma,
+        );
 
-            let depth_path = Path::new(output_dir)
-                .join(format!("{output_name}_depth.png"))
-                .to_str()
-                .ok_or_else(|| "创建深度输出路径字符串失败".to_string())?
-                .to_string();
+        let depth_path = Path::new(output_dir)
+            .join(format!("{output_name}_depth.png"))
+            .to_str()
+            .ok_or_else(|| "创建深度输出路径字符串失败".to_string())?
+            .to_string();
 
-            save_image(&depth_path, &depth_colored, width as u32, height as u32);
-        }
+        save_image(&depth_path, &depth_colored, width as u32, height as u32);
     }
 
     Ok(())
@@ -0,0 +1,567 @@
+use crate::io::render_settings::{RenderSettings, VideoFormat};
+use image::codecs::webp::WebPEncoder;
+use image::{ExtendedColorType, RgbImage};
+use log::{debug, info};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// 构建 GIF 调色板时最多采样的像素数
+const PALETTE_SAMPLES: usize = 1 << 19;
+/// 颜色到调色板索引的查找表每通道的位数
+const LOOKUP_BITS: u32 = 5;
+
+/// 把按顺序排列的 PNG 帧编码为动画文件，所有帧尺寸必须相同
+///
+/// 内置 GIF、APNG 和 WebP 编码器，不依赖外部程序；MP4 需要 ffmpeg，不在这里处理。
+pub fn encode_animation(
+    frame_paths: &[PathBuf],
+    settings: &RenderSettings,
+    output_path: &Path,
+) -> Result<(), String> {
+    if frame_paths.is_empty() {
+        return Err("没有可编码的帧".to_string());
+    }
+    let fps = settings.fps.max(1);
+    let encode_start = Instant::now();
+    match settings.video_format {
+        VideoFormat::Gif => encode_gif(
+            frame_paths,
+            fps,
+            settings.gif_colors,
+            settings.gif_dither,
+            output_path,
+        )?,
+        VideoFormat::Apng => encode_apng(frame_paths, fps, output_path)?,
+        VideoFormat::WebP => encode_webp(frame_paths, fps, output_path)?,
+        VideoFormat::Mp4 => return Err("MP4 需要通过 ffmpeg 编码".to_string()),
+    }
+    info!(
+        "{} 帧已编码为 {}: {}，耗时 {:?}",
+        frame_paths.len(),
+        settings.video_format.name(),
+        output_path.display(),
+        encode_start.elapsed()
+    );
+    Ok(())
+}
+
+fn load_frame(path: &Path, size: Option<(u32, u32)>) -> Result<RgbImage, String> {
+    let image = image::open(path)
+        .map_err(|e| format!("读取帧 {} 失败: {e}", path.display()))?
+        .to_rgb8();
+    if let Some(size) = size
+        && image.dimensions() != size
+    {
+        return Err(format!(
+            "帧 {} 的尺寸 {:?} 与首帧 {:?} 不一致",
+            path.display(),
+            image.dimensions(),
+            size
+        ));
+    }
+    Ok(image)
+}
+
+fn create_output(path: &Path) -> Result<BufWriter<File>, String> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| format!("创建 {} 失败: {e}", path.display()))
+}
+
+/// 第 `index` 帧的时长，以 `1 / units_per_second` 为单位
+///
+/// 按累计时间取整，帧率不能整除时误差不会逐帧累积。
+fn frame_delay(index: usize, fps: usize, units_per_second: usize) -> usize {
+    let at = |frame: usize| (frame * units_per_second + fps / 2) / fps;
+    at(index + 1) - at(index)
+}
+
+// ===== GIF =====
+
+/// 中位切分：反复沿跨度最大的通道把颜色最分散的盒子从中位数处一分为二，每个盒子的均值为一种调色板颜色
+fn median_cut_palette(mut samples: Vec<[u8; 3]>, colors: usize) -> Vec<[u8; 3]> {
+    let channel_ranges = |pixels: &[[u8; 3]]| -> [u8; 3] {
+        let mut min = [u8::MAX; 3];
+        let mut max = [0u8; 3];
+        for pixel in pixels {
+            for c in 0..3 {
+                min[c] = min[c].min(pixel[c]);
+                max[c] = max[c].max(pixel[c]);
+            }
+        }
+        [0, 1, 2].map(|c| max[c].saturating_sub(min[c]))
+    };
+
+    // 每个盒子是 samples 中的一段区间，连同其跨度最大的通道和跨度
+    let measure = |samples: &[[u8; 3]], range: std::ops::Range<usize>| {
+        let ranges = channel_ranges(&samples[range.clone()]);
+        let channel = (0..3).max_by_key(|&c| ranges[c]).unwrap_or(0);
+        (range, channel, ranges[channel])
+    };
+    let mut boxes = vec![measure(&samples, 0..samples.len())];
+    while boxes.len() < colors {
+        let Some(index) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, spread))| *spread > 0)
+            .max_by_key(|(_, (_, _, spread))| *spread)
+            .map(|(index, _)| index)
+        else {
+            break;
+        };
+        let (range, channel, _) = boxes.swap_remove(index);
+        samples[range.clone()].sort_unstable_by_key(|pixel| pixel[channel]);
+        let middle = range.start + range.len() / 2;
+        boxes.push(measure(&samples, range.start..middle));
+        boxes.push(measure(&samples, middle..range.end));
+    }
+
+    boxes
+        .into_iter()
+        .map(|(range, _, _)| range)
+        .filter(|range| !range.is_empty())
+        .map(|range| {
+            let count = range.len() as u64;
+            let mut sum = [0u64; 3];
+            for pixel in &samples[range] {
+                for c in 0..3 {
+                    sum[c] += pixel[c] as u64;
+                }
+            }
+            [0, 1, 2].map(|c| ((sum[c] + count / 2) / count) as u8)
+        })
+        .collect()
+}
+
+/// 颜色到最近调色板颜色的映射，按量化后的颜色惰性缓存
+struct PaletteLookup<'a> {
+    palette: &'a [[u8; 3]],
+    cache: Vec<u16>,
+}
+
+impl<'a> PaletteLookup<'a> {
+    const EMPTY: u16 = u16::MAX;
+
+    fn new(palette: &'a [[u8; 3]]) -> Self {
+        Self {
+            palette,
+            cache: vec![Self::EMPTY; 1 << (3 * LOOKUP_BITS)],
+        }
+    }
+
+    fn nearest(&mut self, color: [u8; 3]) -> u8 {
+        let shift = 8 - LOOKUP_BITS;
+        let key = color.iter().fold(0usize, |key, &c| {
+            (key << LOOKUP_BITS) | (c >> shift) as usize
+        });
+        if self.cache[key] == Self::EMPTY {
+            let index = self
+                .palette
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, entry)| {
+                    (0..3)
+                        .map(|c| {
+                            let d = entry[c] as i32 - color[c] as i32;
+                            d * d
+                        })
+                        .sum::<i32>()
+                })
+                .map_or(0, |(index, _)| index);
+            self.cache[key] = index as u16;
+        }
+        self.cache[key] as u8
+    }
+}
+
+/// 把一帧映射到调色板索引，启用抖动时按蛇形扫描做 Floyd-Steinberg 误差扩散
+fn quantize_frame(frame: &RgbImage, lookup: &mut PaletteLookup, dither: bool) -> Vec<u8> {
+    let (width, height) = (frame.width() as usize, frame.height() as usize);
+    let pixels = frame.as_raw();
+    let mut indices = vec![0u8; width * height];
+    if !dither {
+        for (index, pixel) in indices.iter_mut().zip(pixels.chunks_exact(3)) {
+            *index = lookup.nearest([pixel[0], pixel[1], pixel[2]]);
+        }
+        return indices;
+    }
+
+    // 当前行和下一行的累积误差，两端各留一个像素的余量
+    let mut current = vec![[0.0f32; 3]; width + 2];
+    let mut next = vec![[0.0f32; 3]; width + 2];
+    for y in 0..height {
+        let reverse = y % 2 == 1;
+        for step in 0..width {
+            let x = if reverse { width - 1 - step } else { step };
+            let offset = (y * width + x) * 3;
+            let wanted: [f32; 3] = [0, 1, 2]
+                .map(|c| (pixels[offset + c] as f32 + current[x + 1][c]).clamp(0.0, 255.0));
+            let index = lookup.nearest(wanted.map(|c| c.round() as u8));
+            indices[y * width + x] = index;
+
+            let chosen = lookup.palette[index as usize];
+            let error: [f32; 3] = [0, 1, 2].map(|c| wanted[c] - chosen[c] as f32);
+            // 前方、后下方、正下方、前下方
+            let (ahead, behind) = if reverse { (x, x + 2) } else { (x + 2, x) };
+            for c in 0..3 {
+                current[ahead][c] += error[c] * 7.0 / 16.0;
+                next[behind][c] += error[c] * 3.0 / 16.0;
+                next[x + 1][c] += error[c] * 5.0 / 16.0;
+                next[ahead][c] += error[c] * 1.0 / 16.0;
+            }
+        }
+        std::mem::swap(&mut current, &mut next);
+        next.iter_mut().for_each(|error| *error = [0.0; 3]);
+    }
+    indices
+}
+
+/// GIF：所有帧共用一个全局调色板，避免逐帧调色板造成的闪烁
+fn encode_gif(
+    frame_paths: &[PathBuf],
+    fps: usize,
+    colors: usize,
+    dither: bool,
+    output_path: &Path,
+) -> Result<(), String> {
+    let first = load_frame(&frame_paths[0], None)?;
+    let size = first.dimensions();
+    let (width, height) = (
+        u16::try_from(size.0).map_err(|_| "GIF 宽度不能超过 65535".to_string())?,
+        u16::try_from(size.1).map_err(|_| "GIF 高度不能超过 65535".to_string())?,
+    );
+
+    // 第一遍：从所有帧中均匀采样像素构建调色板
+    let pixels_per_frame = size.0 as usize * size.1 as usize;
+    let stride = (pixels_per_frame * frame_paths.len() / PALETTE_SAMPLES).max(1);
+    let mut samples = Vec::with_capacity(PALETTE_SAMPLES.min(pixels_per_frame * frame_paths.len()));
+    for (index, path) in frame_paths.iter().enumerate() {
+        let frame = if index == 0 {
+            first.clone()
+        } else {
+            load_frame(path, Some(size))?
+        };
+        samples.extend(
+            frame
+                .pixels()
+                .skip(index % stride)
+                .step_by(stride)
+                .map(|pixel| pixel.0),
+        );
+    }
+    let palette = median_cut_palette(samples, colors);
+    debug!("GIF 调色板: {} 种颜色", palette.len());
+
+    // 全局颜色表的长度必须是 2 的幂
+    let mut color_table: Vec<u8> = palette.iter().flatten().copied().collect();
+    color_table.resize(palette.len().next_power_of_two().max(2) * 3, 0);
+
+    let mut encoder = gif::Encoder::new(create_output(output_path)?, width, height, &color_table)
+        .map_err(|e| format!("写入 GIF 头失败: {e}"))?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|e| format!("写入 GIF 循环设置失败: {e}"))?;
+
+    // 第二遍：量化并逐帧写出
+    let mut lookup = PaletteLookup::new(&palette);
+    for (index, path) in frame_paths.iter().enumerate() {
+        let frame = if index == 0 {
+            first.clone()
+        } else {
+            load_frame(path, Some(size))?
+        };
+        let gif_frame = gif::Frame {
+            width,
+            height,
+            delay: frame_delay(index, fps, 100) as u16,
+            buffer: quantize_frame(&frame, &mut lookup, dither).into(),
+            ..Default::default()
+        };
+        encoder
+            .write_frame(&gif_frame)
+            .map_err(|e| format!("写入 GIF 第 {index} 帧失败: {e}"))?;
+    }
+    Ok(())
+}
+
+// ===== APNG =====
+
+fn encode_apng(frame_paths: &[PathBuf], fps: usize, output_path: &Path) -> Result<(), String> {
+    let first = load_frame(&frame_paths[0], None)?;
+    let size = first.dimensions();
+
+    let mut encoder = png::Encoder::new(create_output(output_path)?, size.0, size.1);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let frame_count = u32::try_from(frame_paths.len()).map_err(|_| "APNG 帧数过多")?;
+    encoder
+        .set_animated(frame_count, 0)
+        .map_err(|e| format!("设置 APNG 动画失败: {e}"))?;
+    let fps = u16::try_from(fps).map_err(|_| "APNG 帧率过高")?;
+    encoder
+        .set_frame_delay(1, fps)
+        .map_err(|e| format!("设置 APNG 帧间隔失败: {e}"))?;
+
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("写入 APNG 头失败: {e}"))?;
+    for (index, path) in frame_paths.iter().enumerate() {
+        let frame = if index == 0 {
+            first.clone()
+        } else {
+            load_frame(path, Some(size))?
+        };
+        writer
+            .write_image_data(frame.as_raw())
+            .map_err(|e| format!("写入 APNG 第 {index} 帧失败: {e}"))?;
+    }
+    writer
+        .finish()
+        .map_err(|e| format!("完成 APNG 文件失败: {e}"))
+}
+
+// ===== WebP =====
+
+/// 写入一个 RIFF 块，奇数长度的数据补一个字节
+fn push_chunk(output: &mut Vec<u8>, fourcc: &[u8; 4], payload: &[u8]) {
+    output.extend_from_slice(fourcc);
+    output.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    output.extend_from_slice(payload);
+    if payload.len() % 2 == 1 {
+        output.push(0);
+    }
+}
+
+fn push_u24(output: &mut Vec<u8>, value: u32) {
+    output.extend_from_slice(&value.to_le_bytes()[..3]);
+}
+
+/// 从单帧 WebP 文件中取出图像数据块（VP8L 或 VP8，连同块头）
+fn extract_image_chunk(webp: &[u8]) -> Result<&[u8], String> {
+    if webp.len() < 12 || &webp[0..4] != b"RIFF" || &webp[8..12] != b"WEBP" {
+        return Err("WebP 编码结果格式无效".to_string());
+    }
+    let mut offset = 12;
+    while offset + 8 <= webp.len() {
+        let fourcc = &webp[offset..offset + 4];
+        let size = u32::from_le_bytes([
+            webp[offset + 4],
+            webp[offset + 5],
+            webp[offset + 6],
+            webp[offset + 7],
+        ]) as usize;
+        let end = (offset + 8 + size + size % 2).min(webp.len());
+        if fourcc == b"VP8L" || fourcc == b"VP8 " {
+            return Ok(&webp[offset..end]);
+        }
+        offset = end;
+    }
+    Err("WebP 编码结果中没有图像数据".to_string())
+}
+
+/// 动画 WebP：每帧用无损编码器编码后放入 ANMF 块，外层为 VP8X + ANIM 扩展格式
+fn encode_webp(frame_paths: &[PathBuf], fps: usize, output_path: &Path) -> Result<(), String> {
+    let mut size = None;
+    let mut body = Vec::new();
+
+    for (index, path) in frame_paths.iter().enumerate() {
+        let frame = load_frame(path, size)?;
+        size = Some(frame.dimensions());
+        let (width, height) = frame.dimensions();
+        if width > 1 << 24 || height > 1 << 24 {
+            return Err("WebP 尺寸不能超过 16777216".to_string());
+        }
+
+        let mut encoded = Vec::new();
+        WebPEncoder::new_lossless(&mut encoded)
+            .encode(frame.as_raw(), width, height, ExtendedColorType::Rgb8)
+            .map_err(|e| format!("编码 WebP 第 {index} 帧失败: {e}"))?;
+
+        let mut anmf = Vec::new();
+        push_u24(&mut anmf, 0); // X / 2
+        push_u24(&mut anmf, 0); // Y / 2
+        push_u24(&mut anmf, width - 1);
+        push_u24(&mut anmf, height - 1);
+        push_u24(&mut anmf, frame_delay(index, fps, 1000) as u32);
+        anmf.push(0b10); // 不与上一帧混合，不清除
+        anmf.extend_from_slice(extract_image_chunk(&encoded)?);
+        push_chunk(&mut body, b"ANMF", &anmf);
+    }
+    let (width, height) = size.unwrap_or((1, 1));
+
+    let mut vp8x = vec![0b0000_0010, 0, 0, 0]; // 动画标志
+    push_u24(&mut vp8x, width - 1);
+    push_u24(&mut vp8x, height - 1);
+    let mut anim = vec![0, 0, 0, 255]; // 背景色 BGRA
+    anim.extend_from_slice(&0u16.to_le_bytes()); // 无限循环
+
+    let mut chunks = Vec::with_capacity(body.len() + 64);
+    chunks.extend_from_slice(b"WEBP");
+    push_chunk(&mut chunks, b"VP8X", &vp8x);
+    push_chunk(&mut chunks, b"ANIM", &anim);
+    chunks.extend_from_slice(&body);
+
+    let mut output = create_output(output_path)?;
+    output
+        .write_all(b"RIFF")
+        .and_then(|_| output.write_all(&(chunks.len() as u32).to_le_bytes()))
+        .and_then(|_| output.write_all(&chunks))
+        .and_then(|_| output.flush())
+        .map_err(|e| format!("写入 {} 失败: {e}", output_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::AnimationDecoder;
+    use image::codecs::webp::WebPDecoder;
+
+    const WIDTH: u32 = 12;
+    const HEIGHT: u32 = 8;
+
+    /// 每个测试独立的临时目录
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rasterizer-video-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 写出 `count` 帧颜色渐变各不相同的 PNG
+    fn write_frames(dir: &Path, count: usize) -> Vec<PathBuf> {
+        (0..count)
+            .map(|index| {
+                let frame = RgbImage::from_fn(WIDTH, HEIGHT, |x, y| {
+                    image::Rgb([
+                        (x * 255 / (WIDTH - 1)) as u8,
+                        (y * 255 / (HEIGHT - 1)) as u8,
+                        (index * 100) as u8,
+                    ])
+                });
+                let path = dir.join(format!("frame_{index}.png"));
+                frame.save(&path).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn frame_delay_sums_to_one_second() {
+        let centiseconds: Vec<usize> = (0..30).map(|index| frame_delay(index, 30, 100)).collect();
+        assert_eq!(centiseconds.iter().sum::<usize>(), 100);
+        assert!(centiseconds.iter().all(|&delay| delay == 3 || delay == 4));
+        let milliseconds: usize = (0..24).map(|index| frame_delay(index, 24, 1000)).sum();
+        assert_eq!(milliseconds, 1000);
+    }
+
+    #[test]
+    fn median_cut_respects_color_count() {
+        let samples: Vec<[u8; 3]> = (0..=255u8).map(|v| [v, 255 - v, v / 2]).collect();
+        for colors in [1, 5, 16, 256] {
+            let palette = median_cut_palette(samples.clone(), colors);
+            assert!(!palette.is_empty() && palette.len() <= colors);
+        }
+        // 颜色种类少于上限时不会凭空生成颜色
+        let two = vec![[0, 0, 0], [255, 255, 255], [0, 0, 0], [255, 255, 255]];
+        let mut palette = median_cut_palette(two, 16);
+        palette.sort();
+        assert_eq!(palette, vec![[0, 0, 0], [255, 255, 255]]);
+    }
+
+    #[test]
+    fn quantize_keeps_palette_colors() {
+        let palette = [[0, 0, 0], [255, 0, 0], [0, 0, 255]];
+        let frame = RgbImage::from_fn(3, 2, |x, _| image::Rgb(palette[x as usize]));
+        for dither in [false, true] {
+            let mut lookup = PaletteLookup::new(&palette);
+            assert_eq!(
+                quantize_frame(&frame, &mut lookup, dither),
+                vec![0, 1, 2, 0, 1, 2]
+            );
+        }
+    }
+
+    #[test]
+    fn gif_round_trip() {
+        let dir = scratch_dir("gif");
+        let frames = write_frames(&dir, 3);
+        let output = dir.join("out.gif");
+        encode_gif(&frames, 30, 16, true, &output).unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(File::open(&output).unwrap()).unwrap();
+        assert_eq!(
+            (decoder.width(), decoder.height()),
+            (WIDTH as u16, HEIGHT as u16)
+        );
+        let palette_len = decoder.global_palette().unwrap().len() / 3;
+        assert!(palette_len <= 16, "调色板有 {palette_len} 种颜色");
+
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), (WIDTH as u16, HEIGHT as u16));
+            assert!(
+                frame
+                    .buffer
+                    .iter()
+                    .all(|&index| (index as usize) < palette_len)
+            );
+            delays.push(frame.delay);
+        }
+        assert_eq!(delays, vec![3, 4, 3]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn apng_round_trip() {
+        let dir = scratch_dir("apng");
+        let frames = write_frames(&dir, 3);
+        let output = dir.join("out.png");
+        encode_apng(&frames, 25, &output).unwrap();
+
+        let mut reader = png::Decoder::new(File::open(&output).unwrap())
+            .read_info()
+            .unwrap();
+        let control = reader.info().animation_control.unwrap();
+        assert_eq!(control.num_frames, 3);
+        assert_eq!((reader.info().width, reader.info().height), (WIDTH, HEIGHT));
+
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        for path in &frames {
+            reader.next_frame(&mut buffer).unwrap();
+            let frame_control = reader.info().frame_control.unwrap();
+            assert_eq!((frame_control.delay_num, frame_control.delay_den), (1, 25));
+            // 无损格式，逐像素与源帧一致
+            let source = image::open(path).unwrap().to_rgb8();
+            assert_eq!(&buffer[..source.as_raw().len()], source.as_raw().as_slice());
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn webp_round_trip() {
+        let dir = scratch_dir("webp");
+        let frames = write_frames(&dir, 2);
+        let output = dir.join("out.webp");
+        encode_webp(&frames, 30, &output).unwrap();
+
+        let decoder =
+            WebPDecoder::new(std::io::BufReader::new(File::open(&output).unwrap())).unwrap();
+        let decoded = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(decoded.len(), 2);
+        for (index, frame) in decoded.iter().enumerate() {
+            assert_eq!(frame.buffer().dimensions(), (WIDTH, HEIGHT));
+            let (numerator, denominator) = frame.delay().numer_denom_ms();
+            assert_eq!(
+                numerator as f32 / denominator as f32,
+                frame_delay(index, 30, 1000) as f32
+            );
+            let source = image::open(&frames[index]).unwrap().to_rgba8();
+            assert_eq!(frame.buffer(), &source);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}